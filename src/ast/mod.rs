@@ -8,7 +8,8 @@ use crate::{
     types::*,
     workspace::{ModuleId, ModuleInfo},
 };
-use pat::{NamePat, Pat};
+use pat::{MatchPat, NamePat, Pat};
+use serde::{Deserialize, Serialize};
 use std::{
     ffi::OsStr,
     fmt::{self, Display},
@@ -24,6 +25,7 @@ pub struct Module {
     pub file_id: FileId,
     pub bindings: Vec<Binding>,
     pub comptime_blocks: Vec<Comptime>,
+    pub impls: Vec<Impl>,
 }
 
 impl Module {
@@ -34,6 +36,7 @@ impl Module {
             info: module_info,
             bindings: vec![],
             comptime_blocks: vec![],
+            impls: vec![],
         }
     }
 
@@ -65,10 +68,11 @@ impl Module {
 
 define_id_type!(FunctionId);
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Ast {
     Binding(Binding),
     Cast(Cast),
+    Try(Try),
     Import(Import),
     Builtin(Builtin),
     Comptime(Comptime),
@@ -79,11 +83,14 @@ pub enum Ast {
     Break(Empty),
     Continue(Empty),
     Return(Return),
+    Defer(Defer),
     If(If),
+    Match(Match),
     Block(Block),
     Binary(Binary),
     Unary(Unary),
     Subscript(Subscript),
+    Instantiation(Instantiation),
     Slice(Slice),
     Call(Call),
     MemberAccess(MemberAccess),
@@ -96,7 +103,10 @@ pub enum Ast {
     ArrayType(ArrayType),
     SliceType(SliceType),
     StructType(StructType),
+    EnumType(EnumType),
+    TraitType(TraitType),
     FunctionType(FunctionSig),
+    ClosureType(FunctionSig),
     SelfType(Empty),
     Placeholder(Empty),
 
@@ -110,6 +120,7 @@ impl Ast {
         match self {
             Self::Binding(x) => x.span,
             Self::Cast(x) => x.span,
+            Self::Try(x) => x.span,
             Self::Import(x) => x.span,
             Self::Builtin(x) => x.span,
             Self::Comptime(x) => x.span,
@@ -120,11 +131,14 @@ impl Ast {
             Self::Break(x) => x.span,
             Self::Continue(x) => x.span,
             Self::Return(x) => x.span,
+            Self::Defer(x) => x.span,
             Self::If(x) => x.span,
+            Self::Match(x) => x.span,
             Self::Block(x) => x.span,
             Self::Binary(x) => x.span,
             Self::Unary(x) => x.span,
             Self::Subscript(x) => x.span,
+            Self::Instantiation(x) => x.span,
             Self::Slice(x) => x.span,
             Self::Call(x) => x.span,
             Self::MemberAccess(x) => x.span,
@@ -137,7 +151,10 @@ impl Ast {
             Self::ArrayType(x) => x.span,
             Self::SliceType(x) => x.span,
             Self::StructType(x) => x.span,
+            Self::EnumType(x) => x.span,
+            Self::TraitType(x) => x.span,
             Self::FunctionType(x) => x.span,
+            Self::ClosureType(x) => x.span,
             Self::SelfType(x) => x.span,
             Self::Placeholder(x) => x.span,
             Self::Error(x) => x.span,
@@ -149,6 +166,7 @@ impl Ast {
         match self {
             Self::Binding(x) => &mut x.span,
             Self::Cast(x) => &mut x.span,
+            Self::Try(x) => &mut x.span,
             Self::Import(x) => &mut x.span,
             Self::Builtin(x) => &mut x.span,
             Self::Comptime(x) => &mut x.span,
@@ -159,11 +177,14 @@ impl Ast {
             Self::Break(x) => &mut x.span,
             Self::Continue(x) => &mut x.span,
             Self::Return(x) => &mut x.span,
+            Self::Defer(x) => &mut x.span,
             Self::If(x) => &mut x.span,
+            Self::Match(x) => &mut x.span,
             Self::Block(x) => &mut x.span,
             Self::Binary(x) => &mut x.span,
             Self::Unary(x) => &mut x.span,
             Self::Subscript(x) => &mut x.span,
+            Self::Instantiation(x) => &mut x.span,
             Self::Slice(x) => &mut x.span,
             Self::Call(x) => &mut x.span,
             Self::MemberAccess(x) => &mut x.span,
@@ -176,7 +197,10 @@ impl Ast {
             Self::ArrayType(x) => &mut x.span,
             Self::SliceType(x) => &mut x.span,
             Self::StructType(x) => &mut x.span,
+            Self::EnumType(x) => &mut x.span,
+            Self::TraitType(x) => &mut x.span,
             Self::FunctionType(x) => &mut x.span,
+            Self::ClosureType(x) => &mut x.span,
             Self::SelfType(x) => &mut x.span,
             Self::Placeholder(x) => &mut x.span,
             Self::Error(x) => &mut x.span,
@@ -184,50 +208,57 @@ impl Ast {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Empty {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ArrayLiteral {
     pub kind: ArrayLiteralKind,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TupleLiteral {
     pub elements: Vec<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ArrayType {
     pub inner: Box<Ast>,
     pub size: Box<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct SliceType {
     pub inner: Box<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct PointerType {
     pub inner: Box<Ast>,
     pub is_mutable: bool,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Return {
     pub expr: Option<Box<Ast>>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+// `defer expr`, which runs `expr` when the enclosing block exits
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Defer {
+    pub expr: Box<Ast>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct If {
     pub condition: Box<Ast>,
     pub then: Box<Ast>,
@@ -235,13 +266,27 @@ pub struct If {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub value: Box<Ast>,
+    pub arms: Vec<MatchArm>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct MatchArm {
+    pub pat: MatchPat,
+    pub expr: Ast,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Block {
     pub statements: Vec<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Binary {
     pub lhs: Box<Ast>,
     pub rhs: Box<Ast>,
@@ -249,21 +294,30 @@ pub struct Binary {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Unary {
     pub op: UnaryOp,
     pub value: Box<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Subscript {
     pub expr: Box<Ast>,
     pub index: Box<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+// An instantiation of a generic binding with more than one argument, i.e: `Foo[i32, 4]`.
+// Instantiations with a single argument are parsed as a `Subscript`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Instantiation {
+    pub expr: Box<Ast>,
+    pub args: Vec<Ast>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Slice {
     pub expr: Box<Ast>,
     pub low: Option<Box<Ast>>,
@@ -271,7 +325,7 @@ pub struct Slice {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct StructType {
     pub name: Ustr,
     pub fields: Vec<StructTypeField>,
@@ -279,34 +333,56 @@ pub struct StructType {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct StructTypeField {
     pub name: Ustr,
     pub ty: Ast,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EnumType {
+    pub name: Ustr,
+    pub variants: Vec<EnumTypeVariant>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EnumTypeVariant {
+    pub name: Ustr,
+    pub payload: Vec<Ast>,
+    pub span: Span,
+}
+
+// A set of functions that a type can implement, i.e: `trait { fn display(self: *Self) -> str }`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TraitType {
+    pub name: Ustr,
+    pub functions: Vec<FunctionSig>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct StructLiteralField {
     pub name: Ustr,
     pub expr: Ast,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Call {
     pub callee: Box<Ast>,
     pub args: Vec<CallArg>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct CallArg {
     pub value: Ast,
     pub spread: bool,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct MemberAccess {
     pub expr: Box<Ast>,
     pub member: Ustr,
@@ -314,32 +390,32 @@ pub struct MemberAccess {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Ident {
     pub name: Ustr,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct StructLiteral {
     pub type_expr: Option<Box<Ast>>,
     pub fields: Vec<StructLiteralField>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum ArrayLiteralKind {
     List(Vec<Ast>),
     Fill { len: Box<Ast>, expr: Box<Ast> },
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Literal {
     pub kind: LiteralKind,
     pub span: Span,
 }
 
-#[derive(strum_macros::IntoStaticStr, Debug, PartialEq, Clone, Copy)]
+#[derive(strum_macros::IntoStaticStr, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum LiteralKind {
     Nil,
     Bool(bool),
@@ -349,58 +425,91 @@ pub enum LiteralKind {
     Char(char),
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Import {
     pub path: PathBuf,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Builtin {
     pub kind: BuiltinKind,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Comptime {
     pub expr: Box<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+// An associated functions block, i.e: `impl Point { fn new(...) = ... }`.
+// Functions which take a `self` parameter first can also be called as methods, i.e: `p.print()`.
+// A trait implementation names its trait, i.e: `impl Display for Point { ... }`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Impl {
+    pub target: NameAndSpan,
+    pub trait_name: Option<NameAndSpan>,
+    pub bindings: Vec<Binding>,
+    pub span: Span,
+}
+
+impl Impl {
+    pub fn find_function(&self, name: Ustr) -> Option<(usize, &Binding)> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .find(|(_, binding)| match &binding.kind {
+                BindingKind::Function {
+                    name: NameAndSpan { name: binding_name, .. },
+                    ..
+                } => *binding_name == name,
+                _ => false,
+            })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum BuiltinKind {
     SizeOf(Box<Ast>),
     AlignOf(Box<Ast>),
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Assign {
     pub lhs: Box<Ast>,
     pub rhs: Box<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Cast {
     pub expr: Box<Ast>,
     pub target_type: Box<Ast>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+// The postfix `?` operator, i.e: `read_file(path)?`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Try {
+    pub expr: Box<Ast>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Loop {
     pub block: Block,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct While {
     pub condition: Box<Ast>,
     pub block: Block,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct For {
     pub iter_binding: NameAndSpan,
     pub index_binding: Option<NameAndSpan>,
@@ -409,20 +518,20 @@ pub struct For {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum ForIter {
     Range(Box<Ast>, Box<Ast>),
     Value(Box<Ast>),
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Function {
     pub sig: FunctionSig,
     pub body: Block,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct FunctionSig {
     pub name: Option<Ustr>,
     pub params: Vec<FunctionParam>,
@@ -438,14 +547,14 @@ impl FunctionSig {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct FunctionVarargs {
     pub name: NamePat,
     pub type_expr: Option<Box<Ast>>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct FunctionParam {
     pub pat: Pat,
     pub type_expr: Option<Box<Ast>>,
@@ -525,7 +634,7 @@ impl ExternLibrary {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Binding {
     pub attrs: Vec<Attr>,
     pub vis: Vis,
@@ -568,7 +677,7 @@ impl Binding {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum BindingKind {
     Let {
         pat: Pat,
@@ -577,6 +686,7 @@ pub enum BindingKind {
     },
     Function {
         name: NameAndSpan,
+        generic_params: Vec<GenericParam>,
         sig: FunctionSig,
         body: Box<Ast>,
     },
@@ -591,10 +701,29 @@ pub enum BindingKind {
     },
     Type {
         name: NameAndSpan,
+        generic_params: Vec<GenericParam>,
         type_expr: Box<Ast>,
     },
 }
 
+impl BindingKind {
+    pub fn generic_params(&self) -> &[GenericParam] {
+        match self {
+            BindingKind::Function { generic_params, .. } | BindingKind::Type { generic_params, .. } => generic_params,
+            BindingKind::Let { .. } | BindingKind::ExternFunction { .. } | BindingKind::ExternVariable { .. } => &[],
+        }
+    }
+}
+
+// A generic parameter, i.e: the `T` and `N` in `fn foo[T, N: uint]`.
+// A parameter with a type annotation is a const parameter, otherwise it is a type parameter
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GenericParam {
+    pub name: NameAndSpan,
+    pub type_expr: Option<Box<Ast>>,
+    pub span: Span,
+}
+
 impl Display for BindingKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -611,7 +740,7 @@ impl Display for BindingKind {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct NameAndSpan {
     pub name: Ustr,
     pub span: Span,
@@ -623,7 +752,7 @@ impl NameAndSpan {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Vis {
     Private,
     Public,
@@ -644,14 +773,14 @@ impl Display for Vis {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Attr {
     pub name: NameAndSpan,
     pub value: Option<Box<Ast>>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum BinaryOp {
     Add,
     Sub,
@@ -818,7 +947,7 @@ impl BinaryOp {
     }
 }
 
-#[derive(strum_macros::IntoStaticStr, Debug, PartialEq, Clone, Copy)]
+#[derive(strum_macros::IntoStaticStr, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum UnaryOp {
     Ref(bool),
     Deref,
@@ -1,11 +1,12 @@
 use crate::{span::Span, workspace::BindingId};
 use enum_as_inner::EnumAsInner;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use ustr::Ustr;
 
-use super::NameAndSpan;
+use super::{Ast, LiteralKind, NameAndSpan};
 
-#[derive(Debug, PartialEq, Clone, EnumAsInner)]
+#[derive(Debug, PartialEq, Clone, EnumAsInner, Serialize, Deserialize)]
 pub enum Pat {
     Name(NamePat),
     Struct(StructPat),
@@ -35,14 +36,14 @@ impl Pat {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct StructPat {
     pub subpats: Vec<StructSubPat>,
     pub span: Span,
     pub glob: Option<GlobPat>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum StructSubPat {
     Name(NamePat),
     NameAndPat(NameAndSpan, Pat),
@@ -71,32 +72,34 @@ impl StructSubPat {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TuplePat {
     pub subpats: Vec<Pat>,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct GlobPat {
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum UnpackPatKind {
     Struct(StructPat),
     Tuple(TuplePat),
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct HybridPat {
     pub name_pat: NamePat,
     pub unpack_pat: UnpackPatKind,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct NamePat {
+    // Assigned when the binding is checked
+    #[serde(skip)]
     pub id: BindingId,
     pub name: Ustr,
     pub span: Span,
@@ -104,6 +107,40 @@ pub struct NamePat {
     pub ignore: bool,
 }
 
+// A pattern that's tested against a value in a `match` arm
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum MatchPat {
+    // `_` or a name - matches any value
+    Name(NamePat),
+    // `Type.variant`, `.variant`, optionally followed by a tuple unpack of its payload
+    Variant(VariantPat),
+    Literal(LiteralPat),
+}
+
+impl MatchPat {
+    pub fn span(&self) -> Span {
+        match self {
+            MatchPat::Name(p) => p.span,
+            MatchPat::Variant(p) => p.span,
+            MatchPat::Literal(p) => p.span,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct VariantPat {
+    pub enum_type: Option<Box<Ast>>,
+    pub name: NameAndSpan,
+    pub payload: Option<TuplePat>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LiteralPat {
+    pub kind: LiteralKind,
+    pub span: Span,
+}
+
 pub struct PatIter<'a> {
     pats: Vec<&'a Pat>,
     positions: Vec<usize>,
@@ -1,6 +1,7 @@
 use crate::{
     ast,
     common::id_cache::IdCache,
+    incremental,
     parse::{spawn_parser, ParserCache, ParserResult},
     workspace::Workspace,
 };
@@ -28,6 +29,12 @@ pub fn generate_ast(workspace: &mut Workspace) -> (Vec<ast::Module>, AstGenerati
         diagnostics: workspace.diagnostics.clone(),
         parsed_files: HashSet::new(),
         total_lines: 0,
+        ast_cache_dir: if workspace.build_options.incremental {
+            Some(incremental::ast_cache_dir(&workspace.build_options))
+        } else {
+            None
+        },
+        source_overrides: workspace.build_options.source_overrides.clone(),
     }));
 
     let thread_pool = ThreadPool::new(num_cpus::get());
@@ -0,0 +1,203 @@
+use super::super::traits::IsAggregateType;
+use super::{align_of, amd64_system_v, size_of, AbiFunction, AbiInfo, AbiTy};
+use inkwell::{
+    attributes::Attribute,
+    types::{AnyType, BasicTypeEnum, FloatType, FunctionType},
+};
+
+// The AAPCS64 calling convention, lowered the same way clang lowers it:
+// - Homogeneous float aggregates (1 to 4 floats of the same type) are passed in float registers
+// - Other aggregates of up to 16 bytes are passed in up to two general purpose registers
+// - Larger aggregates are copied by the caller, and passed through a pointer.
+//   When they are returned, the caller passes the pointer to the result in x8
+pub(super) fn get_fn<'ctx>(info: AbiInfo<'ctx>, fn_ty: FunctionType<'ctx>) -> AbiFunction<'ctx> {
+    AbiFunction {
+        params: get_params(info, fn_ty.get_param_types()),
+        ret: get_return(info, fn_ty.get_return_type().unwrap()),
+        variadic: fn_ty.is_var_arg(),
+    }
+}
+
+pub(super) fn get_params<'ctx>(info: AbiInfo<'ctx>, params: Vec<BasicTypeEnum<'ctx>>) -> Vec<AbiTy<'ctx>> {
+    params
+        .iter()
+        .map(|&param| {
+            if !param.is_aggregate_type() {
+                return amd64_system_v::non_struct(info, param);
+            }
+
+            let size = size_of(param, info.word_size);
+
+            if size == 0 {
+                AbiTy::direct(param)
+            } else if let Some((base, count)) = homogeneous_float_aggregate(param) {
+                *AbiTy::direct(param).with_cast_to(base.array_type(count).into())
+            } else if size <= 16 {
+                *AbiTy::direct(param).with_cast_to(small_aggregate_type(info, param, size))
+            } else {
+                AbiTy::indirect(param)
+            }
+        })
+        .collect()
+}
+
+pub(super) fn get_return<'ctx>(info: AbiInfo<'ctx>, ret: BasicTypeEnum<'ctx>) -> AbiTy<'ctx> {
+    if !ret.is_aggregate_type() {
+        return amd64_system_v::non_struct(info, ret);
+    }
+
+    let size = size_of(ret, info.word_size);
+
+    if size == 0 {
+        AbiTy::direct(info.context.struct_type(&[], false).into())
+    } else if homogeneous_float_aggregate(ret).is_some() {
+        // LLVM returns a struct of floats in the float registers by itself
+        AbiTy::direct(ret)
+    } else if size <= 16 {
+        *AbiTy::direct(ret).with_cast_to(small_aggregate_type(info, ret, size))
+    } else {
+        *AbiTy::indirect(ret).with_attr(
+            info.context
+                .create_type_attribute(Attribute::get_named_enum_kind_id("sret"), ret.as_any_type_enum()),
+        )
+    }
+}
+
+// An aggregate of up to 16 bytes is passed as an integer, which is rounded up to a whole number of registers.
+// A 16 byte aggregate is passed as two 64 bit integers, unless it is aligned to 16 bytes
+fn small_aggregate_type<'ctx>(info: AbiInfo<'ctx>, ty: BasicTypeEnum<'ctx>, size: usize) -> BasicTypeEnum<'ctx> {
+    let size = if size <= 8 { 8 } else { 16 };
+
+    if size == 16 && align_of(ty, info.word_size) < 16 {
+        info.context.i64_type().array_type(2).into()
+    } else {
+        info.context.custom_width_int_type((8 * size) as u32).into()
+    }
+}
+
+// The float type and the number of floats of a homogeneous float aggregate,
+// which is an aggregate of 1 to 4 floats of the same type - directly or through nested aggregates
+fn homogeneous_float_aggregate<'ctx>(ty: BasicTypeEnum<'ctx>) -> Option<(FloatType<'ctx>, u32)> {
+    let mut base = None;
+    let mut count = 0;
+
+    if collect_floats(ty, &mut base, &mut count) && (1..=4).contains(&count) {
+        base.map(|base| (base, count))
+    } else {
+        None
+    }
+}
+
+// Counts the floats of an aggregate into `count`.
+// Returns false if the aggregate contains anything other than floats of the same type
+fn collect_floats<'ctx>(ty: BasicTypeEnum<'ctx>, base: &mut Option<FloatType<'ctx>>, count: &mut u32) -> bool {
+    match ty {
+        BasicTypeEnum::StructType(t) => t
+            .get_field_types()
+            .into_iter()
+            .all(|field| collect_floats(field, base, count)),
+        BasicTypeEnum::ArrayType(t) => {
+            let mut element_count = 0;
+
+            if !collect_floats(t.get_element_type(), base, &mut element_count) {
+                return false;
+            }
+
+            *count += element_count * t.len();
+
+            true
+        }
+        BasicTypeEnum::FloatType(t) => match base {
+            Some(base) if *base != t => false,
+            _ => {
+                *base = Some(t);
+                *count += 1;
+                true
+            }
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Each function is lowered as `T f(T)`, and compared with the signature clang emits for the equivalent C function,
+    // i.e: `clang --target=aarch64-linux-gnu -S -emit-llvm`
+    use super::super::{abi_fn_type, AbiInfo};
+    use super::get_fn;
+    use inkwell::{
+        context::Context,
+        types::{AnyType, BasicType, BasicTypeEnum},
+    };
+
+    fn lower<'ctx>(context: &'ctx Context, ty: BasicTypeEnum<'ctx>) -> String {
+        let fn_ty = ty.fn_type(&[ty.into()], false);
+        let abi_fn = get_fn(AbiInfo { context, word_size: 8 }, fn_ty);
+        abi_fn_type(context, &abi_fn).print_to_string().to_string()
+    }
+
+    #[test]
+    fn homogeneous_float_aggregate() {
+        let context = Context::create();
+        let f32 = context.f32_type();
+
+        // struct { float x, y, z; }
+        let ty = context.struct_type(&[f32.into(), f32.into(), f32.into()], false);
+        assert_eq!(lower(&context, ty.into()), "{ float, float, float } ([3 x float])");
+    }
+
+    #[test]
+    fn nested_homogeneous_float_aggregate() {
+        let context = Context::create();
+        let f64_array = context.f64_type().array_type(2);
+
+        // struct { double a[2]; double b[2]; }
+        let ty = context.struct_type(&[f64_array.into(), f64_array.into()], false);
+        assert_eq!(
+            lower(&context, ty.into()),
+            "{ [2 x double], [2 x double] } ([4 x double])"
+        );
+    }
+
+    #[test]
+    fn too_many_floats_is_not_homogeneous() {
+        let context = Context::create();
+
+        // struct { float a[5]; }
+        let ty = context.struct_type(&[context.f32_type().array_type(5).into()], false);
+        assert_eq!(lower(&context, ty.into()), "void ({ [5 x float] }*, { [5 x float] }*)");
+    }
+
+    #[test]
+    fn aggregate_larger_than_16_bytes() {
+        let context = Context::create();
+        let i64 = context.i64_type();
+
+        // struct { long a, b, c; }
+        let ty = context.struct_type(&[i64.into(), i64.into(), i64.into()], false);
+        assert_eq!(
+            lower(&context, ty.into()),
+            "void ({ i64, i64, i64 }*, { i64, i64, i64 }*)"
+        );
+    }
+
+    #[test]
+    fn mixed_int_and_float_aggregates() {
+        let context = Context::create();
+        let (i32, i64) = (context.i32_type(), context.i64_type());
+        let (f32, f64) = (context.f32_type(), context.f64_type());
+
+        // struct { int a; float b; }
+        let ty = context.struct_type(&[i32.into(), f32.into()], false);
+        assert_eq!(lower(&context, ty.into()), "i64 (i64)");
+
+        // struct { long a; double b; }
+        let ty = context.struct_type(&[i64.into(), f64.into()], false);
+        assert_eq!(lower(&context, ty.into()), "[2 x i64] ([2 x i64])");
+
+        // Floats of different types aren't a homogeneous float aggregate
+        // struct { float a; double b; }
+        let ty = context.struct_type(&[f32.into(), f64.into()], false);
+        assert_eq!(lower(&context, ty.into()), "[2 x i64] ([2 x i64])");
+    }
+}
@@ -1,6 +1,8 @@
+mod aarch64;
 mod amd64_system_v;
 mod amd64_win64;
 mod i386;
+mod wasm;
 
 use crate::common::{
     mem::{bit_width_to_size, calculate_align_from_offset},
@@ -9,7 +11,8 @@ use crate::common::{
 use inkwell::{
     attributes::Attribute,
     context::Context,
-    types::{AnyType, AnyTypeEnum, BasicTypeEnum, FunctionType},
+    types::{AnyType, AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType},
+    AddressSpace,
 };
 
 pub(super) fn get_abi_compliant_fn<'ctx>(
@@ -28,10 +31,51 @@ pub(super) fn get_abi_compliant_fn<'ctx>(
             Os::Linux => amd64_system_v::get_fn(info, fn_ty),
             os => unimplemented!("{}", os.name()),
         },
+        Arch::Arm64 => aarch64::get_fn(info, fn_ty),
+        Arch::Wasm32 | Arch::Wasm64 => wasm::get_fn(info, fn_ty),
         arch => unimplemented!("{}", arch.name()),
     }
 }
 
+// The LLVM type of an ABI compliant function.
+// An indirectly returned value is written through a pointer, which is passed as the first parameter
+pub(super) fn abi_fn_type<'ctx>(context: &'ctx Context, abi_fn: &AbiFunction<'ctx>) -> FunctionType<'ctx> {
+    let ret = match abi_fn.ret.kind {
+        AbiType::Direct => match abi_fn.ret.cast_to {
+            Some(cast_to) => cast_to,
+            None => abi_fn.ret.ty,
+        }
+        .as_any_type_enum(),
+        AbiType::Indirect | AbiType::Ignore => context.void_type().into(),
+    };
+
+    let mut params: Vec<BasicMetadataTypeEnum> = vec![];
+
+    if abi_fn.ret.kind.is_indirect() {
+        params.push(abi_fn.ret.ty.ptr_type(AddressSpace::Generic).into());
+    }
+
+    for param in abi_fn.params.iter() {
+        let ty = match &param.kind {
+            AbiType::Direct => match param.cast_to {
+                Some(cast_to) => cast_to,
+                None => param.ty,
+            },
+            AbiType::Indirect => param.ty.ptr_type(AddressSpace::Generic).into(),
+            AbiType::Ignore => unimplemented!("ignore '{:?}'", param.ty),
+        };
+
+        params.push(ty.into());
+    }
+
+    if ret.is_void_type() {
+        ret.into_void_type().fn_type(&params, abi_fn.variadic)
+    } else {
+        let ret: BasicTypeEnum = ret.try_into().unwrap();
+        ret.fn_type(&params, abi_fn.variadic)
+    }
+}
+
 pub(super) fn size_of<'ctx>(llvm_type: BasicTypeEnum<'ctx>, word_size: usize) -> usize {
     size_of_any(llvm_type.as_any_type_enum(), word_size)
 }
@@ -0,0 +1,73 @@
+use super::super::traits::IsAggregateType;
+use super::{amd64_system_v, size_of, AbiFunction, AbiInfo, AbiTy};
+use inkwell::{
+    attributes::Attribute,
+    types::{AnyType, BasicTypeEnum, FunctionType},
+};
+
+// Based on the basic C ABI of WebAssembly, which clang follows.
+// Aggregates that are empty, wrap a single scalar, or fit in 64 bits (such as fat pointers) are passed directly,
+// and the rest are passed through a pointer. Unlike clang, which only passes the first two kinds directly
+pub(super) fn get_fn<'ctx>(info: AbiInfo<'ctx>, fn_ty: FunctionType<'ctx>) -> AbiFunction<'ctx> {
+    AbiFunction {
+        params: get_params(info, fn_ty.get_param_types()),
+        ret: get_return(info, fn_ty.get_return_type().unwrap()),
+        variadic: fn_ty.is_var_arg(),
+    }
+}
+
+pub(super) fn get_params<'ctx>(info: AbiInfo<'ctx>, params: Vec<BasicTypeEnum<'ctx>>) -> Vec<AbiTy<'ctx>> {
+    params
+        .iter()
+        .map(|&param| {
+            if param.is_aggregate_type() {
+                if size_of(param, info.word_size) == 0 {
+                    AbiTy::direct(param)
+                } else if let Some(scalar) = single_scalar(param) {
+                    *AbiTy::direct(param).with_cast_to(scalar)
+                } else {
+                    match size_of(param, info.word_size) {
+                        size @ (1 | 2 | 4 | 8) => *AbiTy::direct(param)
+                            .with_cast_to(info.context.custom_width_int_type((8 * size) as u32).into()),
+                        _ => AbiTy::indirect_byval(&info.context, param, info.word_size),
+                    }
+                }
+            } else {
+                amd64_system_v::non_struct(info, param)
+            }
+        })
+        .collect()
+}
+
+pub(super) fn get_return<'ctx>(info: AbiInfo<'ctx>, ret: BasicTypeEnum<'ctx>) -> AbiTy<'ctx> {
+    if ret.is_aggregate_type() {
+        if size_of(ret, info.word_size) == 0 {
+            AbiTy::direct(info.context.struct_type(&[], false).into())
+        } else if let Some(scalar) = single_scalar(ret) {
+            *AbiTy::direct(ret).with_cast_to(scalar)
+        } else {
+            match size_of(ret, info.word_size) {
+                size @ (1 | 2 | 4 | 8) => {
+                    *AbiTy::direct(ret).with_cast_to(info.context.custom_width_int_type((8 * size) as u32).into())
+                }
+                _ => *AbiTy::indirect(ret).with_attr(
+                    info.context
+                        .create_type_attribute(Attribute::get_named_enum_kind_id("sret"), ret.as_any_type_enum()),
+                ),
+            }
+        }
+    } else {
+        amd64_system_v::non_struct(info, ret)
+    }
+}
+
+// The scalar that an aggregate wraps, if it has a single field - directly or through nested aggregates.
+// i.e: both `struct { x: i32 }` and `struct { inner: [1]i32 }` are passed as an `i32`
+fn single_scalar<'ctx>(ty: BasicTypeEnum<'ctx>) -> Option<BasicTypeEnum<'ctx>> {
+    match ty {
+        BasicTypeEnum::StructType(t) if t.count_fields() == 1 => single_scalar(t.get_field_type_at_index(0).unwrap()),
+        BasicTypeEnum::ArrayType(t) if t.len() == 1 => single_scalar(t.get_element_type()),
+        BasicTypeEnum::StructType(_) | BasicTypeEnum::ArrayType(_) => None,
+        _ => Some(ty),
+    }
+}
@@ -1,10 +1,11 @@
 use super::{
     abi::{align_of, size_of},
+    debug_info::DebugInfo,
     traits::IsALoadInst,
 };
 use crate::{
     ast::ExternLibrary,
-    common::{build_options, scopes::Scopes, target::TargetMetrics},
+    common::{scopes::Scopes, target::TargetMetrics},
     hir,
     infer::type_ctx::TypeCtx,
     types::*,
@@ -14,11 +15,10 @@ use inkwell::{
     basic_block::BasicBlock,
     builder::Builder,
     context::Context,
+    debug_info::DIScope,
     module::{Linkage, Module},
-    passes::{PassManager, PassManagerBuilder},
     types::{BasicTypeEnum, IntType},
     values::{BasicValue, BasicValueEnum, FunctionValue, GlobalValue, PointerValue},
-    OptimizationLevel,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -85,6 +85,9 @@ pub(super) struct Generator<'g, 'ctx> {
     // This is an Option since it is only initialized after
     // creating the startup function's state
     pub(super) startup_function_state: Option<FunctionState<'ctx>>,
+
+    // This is only initialized in debug builds
+    pub(super) debug_info: Option<DebugInfo<'ctx>>,
 }
 
 #[derive(Clone)]
@@ -94,9 +97,14 @@ pub(super) struct FunctionState<'ctx> {
     pub(super) fn_type: FunctionType,
     pub(super) return_ptr: Option<PointerValue<'ctx>>,
     pub(super) loop_blocks: Vec<LoopBlock<'ctx>>,
+    // The deferred expressions of each scope that is currently generated
+    pub(super) defer_scopes: Vec<Vec<hir::Node>>,
     pub(super) decl_block: BasicBlock<'ctx>,
     pub(super) current_block: BasicBlock<'ctx>,
     pub(super) scopes: Scopes<BindingId, Decl<'ctx>>,
+    // The function's debug info scope, followed by the lexical scopes of the blocks that are currently generated.
+    // This is empty when the function has no debug info
+    pub(super) debug_scopes: Vec<DIScope<'ctx>>,
 }
 
 impl<'ctx> FunctionState<'ctx> {
@@ -114,9 +122,11 @@ impl<'ctx> FunctionState<'ctx> {
             fn_type,
             return_ptr,
             loop_blocks: vec![],
+            defer_scopes: vec![],
             decl_block,
             current_block: entry_block,
             scopes: Scopes::default(),
+            debug_scopes: vec![],
         }
     }
 
@@ -133,33 +143,14 @@ impl<'ctx> FunctionState<'ctx> {
 pub(super) struct LoopBlock<'ctx> {
     pub(super) head: BasicBlock<'ctx>,
     pub(super) exit: BasicBlock<'ctx>,
+    // The number of defer scopes outside of the loop
+    pub(super) defer_depth: usize,
 }
 
 impl<'g, 'ctx> Generator<'g, 'ctx> {
     pub(super) fn start(&mut self) {
         self.gen_start_function();
-    }
-
-    pub(super) fn optimize(&mut self) {
-        let pass_manager_builder = PassManagerBuilder::create();
-
-        let optimization_level: OptimizationLevel = self.workspace.build_options.optimization_level.into();
-
-        let size_level: u32 = match self.workspace.build_options.optimization_level {
-            build_options::OptimizationLevel::Debug => 1,
-            build_options::OptimizationLevel::Release => 2,
-        };
-
-        pass_manager_builder.set_optimization_level(optimization_level);
-        pass_manager_builder.set_size_level(size_level);
-
-        let pass_manager = PassManager::create(());
-        pass_manager_builder.populate_module_pass_manager(&pass_manager);
-        pass_manager.run_on(&self.module);
-
-        let link_time_optimizations = PassManager::create(());
-        pass_manager_builder.populate_lto_pass_manager(&link_time_optimizations, false, true);
-        link_time_optimizations.run_on(&self.module);
+        self.gen_exported_functions();
     }
 
     pub(super) fn gen_top_level_binding(&mut self, id: BindingId) -> Decl<'ctx> {
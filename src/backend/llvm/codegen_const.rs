@@ -94,6 +94,18 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
                 // self.context.const_struct(&values, false).into();
                 ty.llvm_type(self).into_struct_type().const_named_struct(&values).into()
             }
+            ConstValue::Enum(e) => {
+                let llvm_type = ty.llvm_type(self).into_struct_type();
+                let tag = self.context.i32_type().const_int(e.variant_index as u64, false);
+
+                // Constant variants don't have a payload, so it is left zeroed
+                let values = match llvm_type.get_field_type_at_index(1) {
+                    Some(payload_type) => vec![tag.into(), payload_type.const_zero()],
+                    None => vec![tag.into()],
+                };
+
+                llvm_type.const_named_struct(&values).into()
+            }
             ConstValue::Function(function) => {
                 let prev_block = if let Some(state) = state {
                     Some(state.current_block)
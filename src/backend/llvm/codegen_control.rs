@@ -9,15 +9,41 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Control {
             hir::Control::While(x) => x.codegen(generator, state),
             hir::Control::Return(x) => x.codegen(generator, state),
             hir::Control::Break(_) => {
-                let exit_block = state.loop_blocks.last().unwrap().exit;
-                generator.builder.build_unconditional_branch(exit_block);
+                let loop_block = *state.loop_blocks.last().unwrap();
+                generator.gen_deferred_from(state, loop_block.defer_depth);
+                generator.builder.build_unconditional_branch(loop_block.exit);
                 generator.const_unit()
             }
             hir::Control::Continue(_) => {
-                let head_block = state.loop_blocks.last().unwrap().head;
-                generator.builder.build_unconditional_branch(head_block);
+                let loop_block = *state.loop_blocks.last().unwrap();
+                generator.gen_deferred_from(state, loop_block.defer_depth);
+                generator.builder.build_unconditional_branch(loop_block.head);
                 generator.const_unit()
             }
+            hir::Control::Defer(defer) => {
+                // The expression is generated at each of the scope's exits
+                state.defer_scopes.last_mut().unwrap().push((*defer.expr).clone());
+                generator.const_unit()
+            }
+        }
+    }
+}
+
+impl<'g, 'ctx> Generator<'g, 'ctx> {
+    // Generates the deferred expressions of all scopes starting from `depth`, from the innermost scope to the outermost
+    pub(super) fn gen_deferred_from(&mut self, state: &mut FunctionState<'ctx>, depth: usize) {
+        let defers = state.defer_scopes[depth..].concat();
+        self.gen_deferred(state, &defers);
+    }
+
+    // Generates deferred expressions in reverse order. Nothing is generated if the current block already exited
+    pub(super) fn gen_deferred(&mut self, state: &mut FunctionState<'ctx>, defers: &[hir::Node]) {
+        for defer in defers.iter().rev() {
+            if self.current_block().get_terminator().is_some() {
+                break;
+            }
+
+            defer.codegen(self, state);
         }
     }
 }
@@ -60,6 +86,7 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::While {
         state.loop_blocks.push(LoopBlock {
             head: loop_head,
             exit: loop_exit,
+            defer_depth: state.defer_scopes.len(),
         });
 
         self.body.codegen(generator, state);
@@ -79,6 +106,7 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::While {
 impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Return {
     fn codegen(&self, generator: &mut Generator<'g, 'ctx>, state: &mut FunctionState<'ctx>) -> BasicValueEnum<'ctx> {
         let value = self.value.codegen(generator, state);
+        generator.gen_deferred_from(state, 0);
         generator.gen_return(state, Some(value));
         generator.const_unit()
     }
@@ -17,6 +17,8 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Literal {
             hir::Literal::Tuple(x) => x.codegen(generator, state),
             hir::Literal::Array(x) => x.codegen(generator, state),
             hir::Literal::ArrayFill(x) => x.codegen(generator, state),
+            hir::Literal::Enum(x) => x.codegen(generator, state),
+            hir::Literal::Closure(x) => x.codegen(generator, state),
         }
     }
 }
@@ -185,3 +187,66 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::ArrayFillLiteral {
         array_ptr.into()
     }
 }
+
+impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::EnumLiteral {
+    fn codegen(&self, generator: &mut Generator<'g, 'ctx>, state: &mut FunctionState<'ctx>) -> BasicValueEnum<'ctx> {
+        let ty = self.ty.normalize(generator.tcx);
+        let llvm_type = ty.llvm_type(generator);
+
+        let payload = self.payload.codegen(generator, state);
+
+        let enum_ptr = generator.build_alloca(state, llvm_type);
+
+        let tag_ptr = generator.builder.build_struct_gep(enum_ptr, 0, ENUM_TAG_FIELD).unwrap();
+
+        let tag = generator.context.i32_type().const_int(self.variant_index as _, false);
+        generator.build_store(tag_ptr, tag.into());
+
+        let payload_ptr = generator
+            .builder
+            .build_struct_gep(enum_ptr, 1, ENUM_PAYLOAD_FIELD)
+            .unwrap();
+
+        let payload_ptr =
+            generator
+                .builder
+                .build_pointer_cast(payload_ptr, payload.get_type().ptr_type(AddressSpace::Generic), "");
+
+        generator.build_store(payload_ptr, payload);
+
+        generator.build_load(enum_ptr, "load_enum")
+    }
+}
+
+impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::ClosureLiteral {
+    fn codegen(&self, generator: &mut Generator<'g, 'ctx>, state: &mut FunctionState<'ctx>) -> BasicValueEnum<'ctx> {
+        let raw_pointer_type = generator.raw_pointer_type();
+
+        let function = self.function.codegen(generator, state).into_pointer_value();
+        let function = generator
+            .builder
+            .build_pointer_cast(function, raw_pointer_type, "closure_fn");
+
+        // The environment is moved to the heap, so it outlives the function that created the closure
+        let env = match &self.env {
+            Some(env) => {
+                let env_type = env.ty().normalize(generator.tcx).llvm_type(generator);
+                let env_value = env.codegen(generator, state);
+
+                let env_ptr = generator.builder.build_malloc(env_type, "closure_env_alloc").unwrap();
+
+                generator.build_store(env_ptr, env_value);
+
+                generator
+                    .builder
+                    .build_pointer_cast(env_ptr, raw_pointer_type, "closure_env")
+            }
+            None => raw_pointer_type.const_null(),
+        };
+
+        let closure_type = generator.closure_type();
+        let closure_ptr = generator.build_struct(state, closure_type.into(), &[function.into(), env.into()]);
+
+        generator.build_load(closure_ptr, "load_closure")
+    }
+}
@@ -21,7 +21,11 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Node {
             return generator.const_unit();
         }
 
-        match self {
+        // The node's instructions are mapped to its span, and the parent's instructions that follow it to the parent's
+        let prev_debug_location = generator.current_debug_location();
+        generator.set_debug_location(state, self.span());
+
+        let value = match self {
             hir::Node::Const(x) => x.codegen(generator, state),
             hir::Node::Binding(x) => x.codegen(generator, state),
             hir::Node::Id(x) => x.codegen(generator, state),
@@ -33,7 +37,11 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Node {
             hir::Node::Control(x) => x.codegen(generator, state),
             hir::Node::Builtin(x) => x.codegen(generator, state),
             hir::Node::Literal(x) => x.codegen(generator, state),
-        }
+        };
+
+        generator.restore_debug_location(prev_debug_location);
+
+        value
     }
 }
 
@@ -101,7 +109,8 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Binding {
             }
             _ => {
                 let value = self.value.codegen(generator, state);
-                generator.gen_local(state, self.id, value);
+                let ptr = generator.gen_local(state, self.id, value);
+                generator.declare_debug_variable(state, self.id, ptr, None);
             }
         }
 
@@ -148,18 +157,37 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Assign {
 impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::MemberAccess {
     fn codegen(&self, generator: &mut Generator<'g, 'ctx>, state: &mut FunctionState<'ctx>) -> BasicValueEnum<'ctx> {
         let value = self.value.codegen(generator, state);
-        generator.gep_struct(
-            value,
-            self.member_index,
-            &self.member_name,
-            self.value.ty().normalize(generator.tcx).is_fat_pointer(),
-        )
+        let value_type = self.value.ty().normalize(generator.tcx);
+
+        match &value_type {
+            // index 0 is an enum's tag, index k is the payload of variant k-1,
+            // which is read by reinterpreting the enum's payload storage
+            Type::Enum(enum_type) if self.member_index > 0 => {
+                let variant = &enum_type.variants[self.member_index as usize - 1];
+                let payload_type = variant.payload_type().llvm_type(generator);
+
+                let enum_ptr = generator.build_alloca_or_load_addr(state, value);
+
+                let payload_ptr = generator
+                    .builder
+                    .build_struct_gep(enum_ptr, 1, ENUM_PAYLOAD_FIELD)
+                    .unwrap();
+
+                let payload_ptr =
+                    generator
+                        .builder
+                        .build_pointer_cast(payload_ptr, payload_type.ptr_type(AddressSpace::Generic), "");
+
+                generator.build_load(payload_ptr, &self.member_name)
+            }
+            _ => generator.gep_struct(value, self.member_index, &self.member_name, value_type.is_fat_pointer()),
+        }
     }
 }
 
 impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Call {
     fn codegen(&self, generator: &mut Generator<'g, 'ctx>, state: &mut FunctionState<'ctx>) -> BasicValueEnum<'ctx> {
-        let callee_ty = self.callee.ty().normalize(generator.tcx).into_function();
+        let callee_ty = self.callee.ty().normalize(generator.tcx);
 
         let mut args = vec![];
 
@@ -172,6 +200,11 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Call {
 
         let args: Vec<BasicValueEnum> = args.iter().map(|(a, _)| *a).collect();
 
+        if let Type::Closure(closure_ty) = &callee_ty {
+            let closure = self.callee.codegen(generator, state).into_struct_value();
+            return generator.gen_closure_call(state, closure, closure_ty, args, &self.ty.normalize(generator.tcx));
+        }
+
         let callee_ptr = self.callee.codegen(generator, state).into_pointer_value();
 
         // println!("callee: {:#?}", callee_ptr.get_type());
@@ -185,7 +218,7 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Call {
         generator.gen_function_call(
             state,
             callable_value,
-            &callee_ty,
+            &callee_ty.into_function(),
             args,
             &self.ty.normalize(generator.tcx),
         )
@@ -240,6 +273,11 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Cast {
                 .build_float_cast(value.into_float_value(), cast_type.into_float_type(), INST_NAME)
                 .into(),
 
+            // Fat pointers that are cast to each other share the same layout, so their value is kept as is
+            (Type::Pointer(..), Type::Pointer(..)) if from_type.is_fat_pointer() && target_type.is_fat_pointer() => {
+                value
+            }
+
             (Type::Pointer(_, _), Type::Pointer(_, _)) => generator
                 .builder
                 .build_pointer_cast(value.into_pointer_value(), cast_type.into_pointer_type(), INST_NAME)
@@ -272,6 +310,8 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Sequence {
 
         if self.is_scope {
             state.push_scope();
+            state.defer_scopes.push(vec![]);
+            generator.push_debug_scope(state, self.span);
         }
 
         for (i, statement) in self.statements.iter().enumerate() {
@@ -282,6 +322,10 @@ impl<'g, 'ctx> Codegen<'g, 'ctx> for hir::Sequence {
         }
 
         if self.is_scope {
+            let defers = state.defer_scopes.pop().unwrap();
+            generator.gen_deferred(state, &defers);
+
+            generator.pop_debug_scope(state);
             state.pop_scope();
         }
 
@@ -5,6 +5,7 @@ use inkwell::values::GlobalValue;
 impl<'g, 'ctx> Generator<'g, 'ctx> {
     pub(super) fn initialize_static(&mut self, global_value: GlobalValue<'ctx>, value: &hir::Node) {
         let prev_block = self.builder.get_insert_block();
+        let prev_debug_location = self.current_debug_location();
 
         let mut state = self.startup_function_state.clone().unwrap();
 
@@ -19,5 +20,7 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
         if let Some(prev_block) = prev_block {
             self.builder.position_at_end(prev_block);
         }
+
+        self.restore_debug_location(prev_debug_location);
     }
 }
@@ -0,0 +1,689 @@
+use super::codegen::{FunctionState, Generator};
+use crate::{
+    hir,
+    infer::{display::DisplayType, normalize::Normalize},
+    span::Span,
+    types::{align_of::AlignOf, offset_of::OffsetOf, size_of::SizeOf, *},
+    workspace::{BindingId, ModuleId},
+};
+use inkwell::{
+    debug_info::{
+        AsDIScope, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DILocation, DIScope, DIType, DWARFEmissionKind,
+        DWARFSourceLanguage, DebugInfoBuilder,
+    },
+    module::FlagBehavior,
+    values::{FunctionValue, PointerValue},
+    AddressSpace,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+// DWARF base type encodings
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x06;
+
+const DWARF_VERSION: u64 = 4;
+
+// DWARF debug info, which is generated for debug builds.
+// Each module gets its own compile unit, and each function gets a subprogram in its module's compile unit.
+// While a function is generated, every node sets the builder's debug location to its span,
+// so the instructions it emits are mapped back to it.
+pub(super) struct DebugInfo<'ctx> {
+    compile_units: HashMap<ModuleId, CompileUnit<'ctx>>,
+    // Named structs and enums are described once, and are referred to by their binding
+    types: HashMap<BindingId, DIType<'ctx>>,
+    // Named types that are currently described, which a pointer in one of their fields can refer to
+    types_in_progress: HashSet<BindingId>,
+}
+
+struct CompileUnit<'ctx> {
+    builder: DebugInfoBuilder<'ctx>,
+    unit: DICompileUnit<'ctx>,
+}
+
+impl<'ctx> CompileUnit<'ctx> {
+    fn file(&self) -> DIFile<'ctx> {
+        self.unit.get_file()
+    }
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+    pub(super) fn new() -> Self {
+        Self {
+            compile_units: HashMap::default(),
+            types: HashMap::default(),
+            types_in_progress: HashSet::default(),
+        }
+    }
+}
+
+impl<'g, 'ctx> Generator<'g, 'ctx> {
+    pub(super) fn init_debug_info(&mut self) {
+        if !self.workspace.build_options.optimization_level.is_debug() {
+            return;
+        }
+
+        self.module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            self.context
+                .i32_type()
+                .const_int(inkwell::debug_info::debug_metadata_version() as u64, false),
+        );
+
+        self.module.add_basic_value_flag(
+            "Dwarf Version",
+            FlagBehavior::Warning,
+            self.context.i32_type().const_int(DWARF_VERSION, false),
+        );
+
+        self.debug_info = Some(DebugInfo::new());
+
+        // Types are created with the root module's builder, so its compile unit always exists
+        self.ensure_compile_unit(self.workspace.root_module_id);
+    }
+
+    pub(super) fn finalize_debug_info(&self) {
+        if let Some(debug_info) = &self.debug_info {
+            for compile_unit in debug_info.compile_units.values() {
+                compile_unit.builder.finalize();
+            }
+        }
+    }
+
+    // Creates the subprogram of `function`, and points the builder at its first line.
+    // Returns the function's scope, or `None` when debug info isn't generated
+    pub(super) fn gen_function_debug_info(
+        &mut self,
+        function: &hir::Function,
+        function_type: &FunctionType,
+        function_value: FunctionValue<'ctx>,
+    ) -> Option<DIScope<'ctx>> {
+        self.debug_info.as_ref()?;
+
+        let return_type = if function_type.return_type.is_unit() || function_type.return_type.is_never() {
+            None
+        } else {
+            Some(self.debug_type(&function_type.return_type))
+        };
+
+        let param_types: Vec<DIType<'ctx>> = function_type
+            .params
+            .iter()
+            .map(|param| self.debug_type(&param.ty))
+            .collect();
+
+        self.ensure_compile_unit(function.module_id);
+
+        let compile_unit = self.compile_unit(function.module_id);
+        let file = compile_unit.file();
+
+        let subroutine_type =
+            compile_unit
+                .builder
+                .create_subroutine_type(file, return_type, &param_types, DIFlags::PROTOTYPED);
+
+        let line = span_line(function.span);
+
+        let subprogram = compile_unit.builder.create_function(
+            compile_unit.unit.as_debug_info_scope(),
+            &function.name,
+            Some(function.qualified_name.as_str()),
+            file,
+            line,
+            subroutine_type,
+            true,
+            true,
+            line,
+            DIFlags::PROTOTYPED,
+            false,
+        );
+
+        function_value.set_subprogram(subprogram);
+
+        let scope = subprogram.as_debug_info_scope();
+
+        let location = compile_unit
+            .builder
+            .create_debug_location(self.context, line, 0, scope, None);
+
+        self.builder.set_current_debug_location(self.context, location);
+
+        Some(scope)
+    }
+
+    // Points the builder at `span`. Code that is generated outside of a function with debug info,
+    // such as the startup function and static initializers, doesn't get a debug location
+    pub(super) fn set_debug_location(&self, state: &FunctionState<'ctx>, span: Span) {
+        let scope = match state.debug_scopes.last() {
+            Some(scope) => *scope,
+            None => {
+                if self.debug_info.is_some() {
+                    self.builder.unset_current_debug_location();
+                }
+
+                return;
+            }
+        };
+
+        // Spans that come from other files, like default arguments, are attributed to the code that uses them
+        if span.is_unknown() || span.file_id != state.module_info.file_id {
+            return;
+        }
+
+        let location = self.compile_unit(state.module_info.id).builder.create_debug_location(
+            self.context,
+            span.start.line,
+            span.start.column,
+            scope,
+            None,
+        );
+
+        self.builder.set_current_debug_location(self.context, location);
+    }
+
+    pub(super) fn current_debug_location(&self) -> Option<DILocation<'ctx>> {
+        self.debug_info.as_ref()?;
+        self.builder.get_current_debug_location()
+    }
+
+    pub(super) fn restore_debug_location(&self, location: Option<DILocation<'ctx>>) {
+        if self.debug_info.is_none() {
+            return;
+        }
+
+        match location {
+            Some(location) => self.builder.set_current_debug_location(self.context, location),
+            None => self.builder.unset_current_debug_location(),
+        }
+    }
+
+    // Nested blocks get their own lexical scope, so that shadowed variables are told apart
+    pub(super) fn push_debug_scope(&self, state: &mut FunctionState<'ctx>, span: Span) {
+        let parent = match state.debug_scopes.last() {
+            Some(parent) => *parent,
+            None => return,
+        };
+
+        let compile_unit = self.compile_unit(state.module_info.id);
+
+        let block =
+            compile_unit
+                .builder
+                .create_lexical_block(parent, compile_unit.file(), span_line(span), span.start.column);
+
+        state.debug_scopes.push(block.as_debug_info_scope());
+    }
+
+    pub(super) fn pop_debug_scope(&self, state: &mut FunctionState<'ctx>) {
+        // The function's own scope is never popped
+        if state.debug_scopes.len() > 1 {
+            state.debug_scopes.pop();
+        }
+    }
+
+    // Describes a local variable, or a parameter when `arg_no` is set, which is stored in `ptr`
+    pub(super) fn declare_debug_variable(
+        &mut self,
+        state: &FunctionState<'ctx>,
+        id: BindingId,
+        ptr: PointerValue<'ctx>,
+        arg_no: Option<u32>,
+    ) {
+        let scope = match state.debug_scopes.last() {
+            Some(scope) => *scope,
+            None => return,
+        };
+
+        let binding_info = self.workspace.binding_infos.get(id).unwrap();
+        let name = binding_info.name;
+        let span = binding_info.span;
+        let ty = binding_info.ty.normalize(self.tcx);
+
+        let ty = self.debug_type(&ty);
+
+        let compile_unit = self.compile_unit(state.module_info.id);
+        let file = compile_unit.file();
+        let line = span_line(span);
+
+        let variable = match arg_no {
+            Some(arg_no) => compile_unit.builder.create_parameter_variable(
+                scope,
+                &name,
+                arg_no,
+                file,
+                line,
+                ty,
+                true,
+                DIFlags::ZERO,
+            ),
+            None => compile_unit
+                .builder
+                .create_auto_variable(scope, &name, file, line, ty, true, DIFlags::ZERO, 0),
+        };
+
+        let location = compile_unit
+            .builder
+            .create_debug_location(self.context, line, span.start.column, scope, None);
+
+        compile_unit
+            .builder
+            .insert_declare_at_end(ptr, Some(variable), None, location, state.decl_block);
+    }
+
+    fn debug_type(&mut self, ty: &Type) -> DIType<'ctx> {
+        let word_size = self.target_metrics.word_size;
+
+        match ty {
+            Type::Var(_) | Type::Infer(..) => match ty.normalize(self.tcx) {
+                Type::Var(_) => self.debug_type(&Type::Unit),
+                ty => self.debug_type(&ty),
+            },
+            Type::Bool => self.debug_basic_type(ty, DW_ATE_BOOLEAN),
+            Type::Int(_) => self.debug_basic_type(ty, DW_ATE_SIGNED),
+            Type::Uint(_) => self.debug_basic_type(ty, DW_ATE_UNSIGNED),
+            Type::Float(_) => self.debug_basic_type(ty, DW_ATE_FLOAT),
+            Type::Pointer(inner, _) => match inner.as_ref() {
+                Type::Slice(elem) | Type::Str(elem) => self.debug_fat_pointer_type(ty, elem),
+                _ => {
+                    let pointee = self.debug_type(inner);
+                    self.debug_pointer_type(ty, pointee)
+                }
+            },
+            Type::Slice(elem) | Type::Str(elem) => self.debug_fat_pointer_type(ty, elem),
+            // Function pointers are described as pointers to unit, since their signature is already known
+            // from the subprograms of the functions they point to
+            Type::Function(_) => {
+                let pointee = self.debug_type(&Type::Unit);
+                self.debug_pointer_type(ty, pointee)
+            }
+            // A closure's function and environment are type-erased, so they are described as raw pointers
+            Type::Closure(_) => {
+                let raw_pointer = Type::u8().pointer_type(false);
+
+                self.debug_struct_type(
+                    ty,
+                    None,
+                    &[("fn", raw_pointer.clone()), ("env", raw_pointer)],
+                    StructTypeKind::Struct,
+                )
+            }
+            Type::Array(inner, len) => {
+                let inner_type = self.debug_type(inner);
+
+                self.types_builder()
+                    .create_array_type(
+                        inner_type,
+                        bits(ty.size_of(word_size)),
+                        bits(ty.align_of(word_size)) as u32,
+                        &[0..*len as i64],
+                    )
+                    .as_type()
+            }
+            Type::Tuple(elements) => {
+                let fields: Vec<(String, Type)> = elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, element)| (index.to_string(), element.clone()))
+                    .collect();
+
+                let fields: Vec<(&str, Type)> = fields.iter().map(|(name, ty)| (name.as_str(), ty.clone())).collect();
+
+                self.debug_struct_type(ty, None, &fields, StructTypeKind::Struct)
+            }
+            Type::Struct(struct_type) => {
+                let fields: Vec<(&str, Type)> = struct_type
+                    .fields
+                    .iter()
+                    .map(|field| (field.name.as_str(), field.ty.clone()))
+                    .collect();
+
+                self.debug_struct_type(ty, struct_type.id, &fields, struct_type.kind)
+            }
+            Type::Enum(enum_type) => self.debug_enum_type(ty, enum_type),
+            Type::Never | Type::Unit | Type::Type(_) | Type::Module(_) | Type::Trait(_) | Type::AnyType => {
+                self.debug_struct_type(ty, None, &[], StructTypeKind::Struct)
+            }
+            Type::Param(_) => unreachable!("type parameters are substituted before codegen"),
+        }
+    }
+
+    fn debug_basic_type(&self, ty: &Type, encoding: u32) -> DIType<'ctx> {
+        let size = ty.size_of(self.target_metrics.word_size);
+
+        self.types_builder()
+            .create_basic_type(&ty.display(self.tcx), bits(size), encoding, DIFlags::ZERO)
+            .unwrap()
+            .as_type()
+    }
+
+    fn debug_pointer_type(&self, ty: &Type, pointee: DIType<'ctx>) -> DIType<'ctx> {
+        let word_size = self.target_metrics.word_size;
+
+        self.types_builder()
+            .create_pointer_type(
+                &ty.display(self.tcx),
+                pointee,
+                bits(word_size),
+                bits(word_size) as u32,
+                AddressSpace::Generic,
+            )
+            .as_type()
+    }
+
+    // `*[]T` and `*str` are a pair of a pointer to their first element, and their length
+    fn debug_fat_pointer_type(&mut self, ty: &Type, elem: &Type) -> DIType<'ctx> {
+        self.debug_struct_type(
+            ty,
+            None,
+            &[("ptr", elem.pointer_type(false)), ("len", Type::uint())],
+            StructTypeKind::Struct,
+        )
+    }
+
+    fn debug_struct_type(
+        &mut self,
+        ty: &Type,
+        id: Option<BindingId>,
+        fields: &[(&str, Type)],
+        kind: StructTypeKind,
+    ) -> DIType<'ctx> {
+        if let Some(id) = id {
+            let debug_info = self.debug_info.as_ref().unwrap();
+
+            if let Some(debug_type) = debug_info.types.get(&id) {
+                return *debug_type;
+            }
+
+            // A type that refers back to itself through a pointer refers to a forward declaration of it,
+            // which debuggers resolve by its name
+            if debug_info.types_in_progress.contains(&id) {
+                let name = ty.display(self.tcx);
+
+                return self
+                    .types_builder()
+                    .create_struct_type(
+                        self.types_file().as_debug_info_scope(),
+                        &name,
+                        self.types_file(),
+                        0,
+                        0,
+                        0,
+                        DIFlags::FWD_DECL,
+                        None,
+                        &[],
+                        0,
+                        None,
+                        &name,
+                    )
+                    .as_type();
+            }
+
+            self.debug_info.as_mut().unwrap().types_in_progress.insert(id);
+        }
+
+        let word_size = self.target_metrics.word_size;
+
+        let layout = StructType {
+            name: ustr::ustr(""),
+            id: None,
+            fields: fields
+                .iter()
+                .map(|(_, ty)| StructTypeField::temp(ty.normalize(self.tcx)))
+                .collect(),
+            kind,
+        };
+
+        let (file, line) = self.type_declaration(id);
+
+        let mut members = vec![];
+
+        for (index, (name, field)) in fields.iter().enumerate() {
+            let field = field.normalize(self.tcx);
+            let field_type = self.debug_type(&field);
+
+            let member = self.types_builder().create_member_type(
+                file.as_debug_info_scope(),
+                name,
+                file,
+                line,
+                bits(field.size_of(word_size)),
+                bits(field.align_of(word_size)) as u32,
+                bits(layout.offset_of(index, word_size)),
+                DIFlags::ZERO,
+                field_type,
+            );
+
+            members.push(member.as_type());
+        }
+
+        let name = ty.display(self.tcx);
+        let size = bits(layout.size_of(word_size));
+        let align = bits(layout.align_of(word_size)) as u32;
+
+        let debug_type = if layout.is_union() {
+            self.types_builder()
+                .create_union_type(
+                    file.as_debug_info_scope(),
+                    &name,
+                    file,
+                    line,
+                    size,
+                    align,
+                    DIFlags::ZERO,
+                    &members,
+                    0,
+                    "",
+                )
+                .as_type()
+        } else {
+            self.types_builder()
+                .create_struct_type(
+                    file.as_debug_info_scope(),
+                    &name,
+                    file,
+                    line,
+                    size,
+                    align,
+                    DIFlags::ZERO,
+                    None,
+                    &members,
+                    0,
+                    None,
+                    "",
+                )
+                .as_type()
+        };
+
+        if let Some(id) = id {
+            let debug_info = self.debug_info.as_mut().unwrap();
+            debug_info.types_in_progress.remove(&id);
+            debug_info.types.insert(id, debug_type);
+        }
+
+        debug_type
+    }
+
+    // An enum is described as its tag, followed by a union of its variants' payloads
+    fn debug_enum_type(&mut self, ty: &Type, enum_type: &EnumType) -> DIType<'ctx> {
+        let word_size = self.target_metrics.word_size;
+        let layout = enum_type.layout(word_size);
+
+        if !enum_type.has_payloads() {
+            return self.debug_struct_type(ty, enum_type.id, &[(ENUM_TAG_FIELD, Type::u32())], layout.kind);
+        }
+
+        let payloads: Vec<(&str, Type)> = enum_type
+            .variants
+            .iter()
+            .filter(|variant| !variant.payload.is_empty())
+            .map(|variant| (variant.name.as_str(), variant.payload_type()))
+            .collect();
+
+        let payload_type = Type::Struct(StructType {
+            name: ustr::ustr(ENUM_PAYLOAD_FIELD),
+            id: None,
+            fields: payloads
+                .iter()
+                .map(|(name, ty)| StructTypeField {
+                    name: ustr::ustr(name),
+                    ty: ty.clone(),
+                    span: Span::unknown(),
+                })
+                .collect(),
+            kind: StructTypeKind::Union,
+        });
+
+        self.debug_struct_type(
+            ty,
+            enum_type.id,
+            &[(ENUM_TAG_FIELD, Type::u32()), (ENUM_PAYLOAD_FIELD, payload_type)],
+            layout.kind,
+        )
+    }
+
+    // The file and line where a named type is declared. Anonymous types are attributed to the root module
+    fn type_declaration(&mut self, id: Option<BindingId>) -> (DIFile<'ctx>, u32) {
+        match id.and_then(|id| self.workspace.binding_infos.get(id)) {
+            Some(binding_info) => {
+                let module_id = binding_info.module_id;
+                let line = span_line(binding_info.span);
+
+                self.ensure_compile_unit(module_id);
+
+                (self.compile_unit(module_id).file(), line)
+            }
+            None => (self.types_file(), 0),
+        }
+    }
+
+    fn ensure_compile_unit(&mut self, module_id: ModuleId) {
+        if self.debug_info.as_ref().unwrap().compile_units.contains_key(&module_id) {
+            return;
+        }
+
+        let module_info = self.workspace.module_infos.get(module_id).unwrap();
+        let path = Path::new(module_info.file_path.as_str());
+
+        let file_name = path.file_name().map_or("", |name| name.to_str().unwrap());
+        let directory = path.parent().map_or("", |dir| dir.to_str().unwrap());
+
+        let (builder, unit) = self.module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            file_name,
+            directory,
+            "chili",
+            false,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+
+        self.debug_info
+            .as_mut()
+            .unwrap()
+            .compile_units
+            .insert(module_id, CompileUnit { builder, unit });
+    }
+
+    fn compile_unit(&self, module_id: ModuleId) -> &CompileUnit<'ctx> {
+        self.debug_info.as_ref().unwrap().compile_units.get(&module_id).unwrap()
+    }
+
+    // Types don't belong to a compile unit, so they are all created with the root module's builder
+    fn types_builder(&self) -> &DebugInfoBuilder<'ctx> {
+        &self.root_compile_unit().builder
+    }
+
+    fn types_file(&self) -> DIFile<'ctx> {
+        self.root_compile_unit().file()
+    }
+
+    fn root_compile_unit(&self) -> &CompileUnit<'ctx> {
+        self.compile_unit(self.workspace.root_module_id)
+    }
+}
+
+fn span_line(span: Span) -> u32 {
+    if span.is_unknown() {
+        0
+    } else {
+        span.start.line
+    }
+}
+
+fn bits(bytes: usize) -> u64 {
+    bytes as u64 * 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{create_target_machine, gen_module};
+    use crate::test_utils::*;
+    use inkwell::context::Context;
+
+    #[test]
+    fn debug_info_is_valid() {
+        // `run` is exported, so it's generated without an entry point, along with `geometry.dot`, which it calls
+        let source_file = write_sources(&[
+            (
+                "main.chl",
+                r#"
+use geometry
+
+@export
+fn run(n: int) -> int = {
+    let p = geometry.Point { x: n, y: 2 }
+    let pair = (p.x, p.y)
+    let numbers = [1, 2, 3]
+    let slice = &numbers[..]
+    let name = "point"
+    let mut total = geometry.dot(p, p)
+
+    if n > 0 {
+        let doubled = total * 2
+        total = doubled
+    }
+
+    total + pair.0 + slice.len as int + name.len as int
+}
+"#,
+            ),
+            (
+                "geometry.chl",
+                "@copy\npub type Point = struct { x: int, y: int }\n\npub fn dot(a: Point, b: Point) -> int = a.x * b.x + a.y * b.y\n",
+            ),
+        ]);
+
+        let result = check_file(&source_file, false);
+        assert!(errors(&result).is_empty());
+
+        let workspace = &result.workspace;
+        assert!(workspace.build_options.optimization_level.is_debug());
+
+        let context = Context::create();
+        let target_machine = create_target_machine(workspace, &workspace.build_options.target_platform.metrics());
+        let (module, _) = gen_module(
+            workspace,
+            result.tcx.as_ref().unwrap(),
+            result.cache.as_ref().unwrap(),
+            &context,
+            &target_machine,
+        );
+
+        if let Err(e) = module.verify() {
+            panic!("{}", e);
+        }
+    }
+}
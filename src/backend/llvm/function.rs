@@ -1,15 +1,19 @@
 use super::{
     abi::AbiType,
     codegen::{FunctionState, Generator},
+    is_libc,
     ty::IntoLlvmType,
     CallingConv,
 };
-use crate::{backend::llvm::codegen::Codegen, hir, infer::normalize::Normalize, types::*};
+use crate::{ast, backend::llvm::codegen::Codegen, hir, infer::normalize::Normalize, types::*};
 use inkwell::{
     attributes::{Attribute, AttributeLoc},
     module::Linkage,
     types::{AnyType, BasicTypeEnum},
-    values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue, PointerValue},
+    values::{
+        BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue, PointerValue, StructValue,
+    },
+    AddressSpace,
 };
 
 impl<'g, 'ctx> Generator<'g, 'ctx> {
@@ -33,6 +37,8 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
                             self.builder.get_insert_block()
                         };
 
+                        let prev_debug_location = self.current_debug_location();
+
                         let function_value =
                             self.declare_fn_sig(&function_type, function.qualified_name, Some(Linkage::Private));
 
@@ -64,6 +70,10 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
                             state.scopes = prev_state.scopes;
                         }
 
+                        if let Some(scope) = self.gen_function_debug_info(function, &function_type, function_value) {
+                            state.debug_scopes.push(scope);
+                        }
+
                         self.start_block(&mut state, entry_block);
 
                         state.push_scope();
@@ -93,7 +103,8 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
 
                             let transmuted_value = self.build_transmute(&state, value, llvm_param_ty);
 
-                            self.gen_local(&mut state, param.id, transmuted_value);
+                            let ptr = self.gen_local(&mut state, param.id, transmuted_value);
+                            self.declare_debug_variable(&state, param.id, ptr, Some(index as u32 + 1));
                         }
 
                         let return_value = body.as_ref().unwrap().codegen(self, &mut state);
@@ -111,6 +122,8 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
                             self.builder.position_at_end(prev_block);
                         }
 
+                        self.restore_debug_location(prev_debug_location);
+
                         function_value
                     }
                     hir::FunctionKind::Extern { lib, dylib, link_name } => {
@@ -124,6 +137,36 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
                                 let function_type = self.fn_type(&function_type);
                                 let function_value = self.get_or_add_function(link_name, function_type, None);
 
+                                // LLVM must know that `setjmp` returns twice, or it may break the state it saves
+                                if matches!(link_name.as_str(), "setjmp" | "_setjmp") {
+                                    function_value.add_attribute(
+                                        AttributeLoc::Function,
+                                        self.context.create_enum_attribute(
+                                            Attribute::get_named_enum_kind_id("returns_twice"),
+                                            0,
+                                        ),
+                                    );
+                                }
+
+                                // On wasm, the functions of a system library are imported from the host module of the same name,
+                                // i.e: `@lib(wasi)` where `wasi` is "wasi_snapshot_preview1"
+                                if self.target_metrics.arch.is_wasm() {
+                                    if let Some(ast::ExternLibrary::System(lib_name)) = lib.as_ref().or(dylib.as_ref())
+                                    {
+                                        if !is_libc(lib_name) {
+                                            function_value.add_attribute(
+                                                AttributeLoc::Function,
+                                                self.context.create_string_attribute("wasm-import-module", lib_name),
+                                            );
+
+                                            function_value.add_attribute(
+                                                AttributeLoc::Function,
+                                                self.context.create_string_attribute("wasm-import-name", link_name),
+                                            );
+                                        }
+                                    }
+                                }
+
                                 self.extern_functions.insert(function.qualified_name, function_value);
 
                                 function_value
@@ -276,6 +319,62 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
         value
     }
 
+    // Calls a closure through its type-erased function pointer.
+    // A closure with a null environment is a plain function, so it is called without passing the environment
+    pub(super) fn gen_closure_call(
+        &mut self,
+        state: &mut FunctionState<'ctx>,
+        closure: StructValue<'ctx>,
+        callee_ty: &FunctionType,
+        args: Vec<BasicValueEnum<'ctx>>,
+        result_ty: &Type,
+    ) -> BasicValueEnum<'ctx> {
+        let function_ptr = self
+            .builder
+            .build_extract_value(closure, 0, "closure_fn")
+            .unwrap()
+            .into_pointer_value();
+
+        let env_ptr = self
+            .builder
+            .build_extract_value(closure, 1, "closure_env")
+            .unwrap()
+            .into_pointer_value();
+
+        let callee_ty_with_env = callee_ty.with_closure_env_param();
+        let args_without_env = args.clone();
+
+        self.gen_conditional(
+            state,
+            |generator: &mut Generator<'g, 'ctx>, _: &mut FunctionState<'ctx>| {
+                generator.builder.build_is_null(env_ptr, "closure_env_is_null")
+            },
+            move |generator: &mut Generator<'g, 'ctx>, state: &mut FunctionState<'ctx>| {
+                let fn_ptr_type = generator
+                    .abi_compliant_fn_type(callee_ty)
+                    .ptr_type(AddressSpace::Generic);
+                let function_ptr = generator.builder.build_pointer_cast(function_ptr, fn_ptr_type, "");
+                let callable: CallableValue = function_ptr.try_into().unwrap();
+
+                generator.gen_function_call(state, callable, callee_ty, args_without_env, result_ty)
+            },
+            Some(
+                move |generator: &mut Generator<'g, 'ctx>, state: &mut FunctionState<'ctx>| {
+                    let fn_ptr_type = generator
+                        .abi_compliant_fn_type(&callee_ty_with_env)
+                        .ptr_type(AddressSpace::Generic);
+                    let function_ptr = generator.builder.build_pointer_cast(function_ptr, fn_ptr_type, "");
+                    let callable: CallableValue = function_ptr.try_into().unwrap();
+
+                    let mut args_with_env = vec![env_ptr.into()];
+                    args_with_env.extend(args);
+
+                    generator.gen_function_call(state, callable, &callee_ty_with_env, args_with_env, result_ty)
+                },
+            ),
+        )
+    }
+
     fn gen_function_call_inner(
         &mut self,
         callee: impl Into<CallableValue<'ctx>>,
@@ -1,9 +1,9 @@
 use super::codegen::Generator;
-use crate::{hir, types::FunctionType};
-use inkwell::{
-    module::Linkage,
-    values::{BasicValue, FunctionValue},
+use crate::{
+    hir,
+    types::{FunctionType, ENUM_TAG_FIELD},
 };
+use inkwell::{module::Linkage, values::FunctionValue};
 
 impl<'g, 'ctx> Generator<'g, 'ctx> {
     pub(super) fn gen_intrinsic(
@@ -23,13 +23,22 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
 
                 let return_ptr = function.get_first_param().unwrap().into_pointer_value();
 
-                let str = generator.const_str_slice("", "");
+                // Workspaces can only be built at compile-time, so a compiled build always fails with `none`
+                let none_index = function_type
+                    .return_type
+                    .as_enum()
+                    .variants
+                    .iter()
+                    .position(|variant| variant.name == "none")
+                    .unwrap();
 
-                let return_value = generator
-                    .const_struct(&[str.into(), generator.const_bool(false).into()])
-                    .as_basic_value_enum();
+                let tag_ptr = generator
+                    .builder
+                    .build_struct_gep(return_ptr, 0, ENUM_TAG_FIELD)
+                    .unwrap();
 
-                generator.build_store(return_ptr, return_value);
+                let tag = generator.context.i32_type().const_int(none_index as _, false);
+                generator.build_store(tag_ptr, tag.into());
 
                 generator.builder.build_return(None);
 
@@ -38,7 +47,9 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
             hir::Intrinsic::Location
             | hir::Intrinsic::CallerLocation
             | hir::Intrinsic::CompilerError
-            | hir::Intrinsic::CompilerWarning => panic!(
+            | hir::Intrinsic::CompilerWarning
+            | hir::Intrinsic::Print
+            | hir::Intrinsic::Println => panic!(
                 "intrinsic function '{}' should have been evaluated at compile-time",
                 intrinsic
             ),
@@ -51,8 +62,15 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
         F: FnOnce(&mut Generator<'g, 'ctx>) -> FunctionValue<'ctx>,
     {
         self.intrinsics.get(intrinsic).cloned().unwrap_or_else(|| {
+            // Intrinsic functions have no debug info, so they don't get the caller's debug location
+            let debug_location = self.current_debug_location();
+            self.restore_debug_location(None);
+
             let function = create_fn(self);
             self.intrinsics.insert(*intrinsic, function);
+
+            self.restore_debug_location(debug_location);
+
             function
         })
     }
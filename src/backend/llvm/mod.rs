@@ -7,6 +7,7 @@ mod codegen_literal;
 mod codegen_node;
 mod codegen_static;
 mod conditional;
+mod debug_info;
 mod function;
 mod intrinsics;
 mod panic;
@@ -19,7 +20,7 @@ mod util;
 use crate::{
     ast,
     common::{
-        build_options::{self, BuildOptions},
+        build_options::{self, BuildOptions, OutputKind},
         target::{Arch, Os, TargetMetrics},
     },
     hir,
@@ -28,10 +29,12 @@ use crate::{
     workspace::Workspace,
 };
 use codegen::Generator;
+use colored::Colorize;
 use execute::Execute;
 use inkwell::{
     context::Context,
     module::Module,
+    passes::{PassManager, PassManagerBuilder},
     targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple},
     OptimizationLevel,
 };
@@ -48,19 +51,31 @@ use super::microsoft_craziness;
 
 pub fn codegen<'w>(workspace: &Workspace, tcx: &TypeCtx, cache: &hir::Cache) -> PathBuf {
     let context = Context::create();
-    let module = context.create_module(
-        workspace
-            .build_options
-            .source_file
-            .file_stem()
-            .unwrap()
-            .to_str()
-            .unwrap(),
-    );
-    let builder = context.create_builder();
 
     let target_metrics = workspace.build_options.target_platform.metrics();
+    let target_machine = create_target_machine(workspace, &target_metrics);
+
+    let (module, extern_libraries) = gen_module(workspace, tcx, cache, &context, &target_machine);
+
+    if let Err(e) = module.verify() {
+        module.print_to_file("fail.ll").unwrap();
+        panic!("{}", e);
+    }
+
+    time! { workspace.build_options.emit_times, "llvm opt", {
+        optimize(&module, workspace.build_options.optimization_level);
+    }};
 
+    build_output(
+        &workspace.build_options,
+        &target_machine,
+        &target_metrics,
+        &module,
+        &extern_libraries,
+    )
+}
+
+fn create_target_machine(workspace: &Workspace, target_metrics: &TargetMetrics) -> TargetMachine {
     match &target_metrics.arch {
         Arch::Amd64 | Arch::_386 => Target::initialize_x86(&InitializationConfig::default()),
         Arch::Arm64 => Target::initialize_aarch64(&InitializationConfig::default()),
@@ -69,63 +84,117 @@ pub fn codegen<'w>(workspace: &Workspace, tcx: &TypeCtx, cache: &hir::Cache) ->
 
     let triple = TargetTriple::create(target_metrics.target_triplet);
     let target = Target::from_triple(&triple).unwrap();
-    let host_cpu = TargetMachine::get_host_cpu_name();
-    let features = TargetMachine::get_host_cpu_features();
 
-    let target_machine = target
+    // The host's cpu and features are only used when building for the host, since the target may not have them.
+    // On wasm, bulk memory instructions are used to copy memory, so that it doesn't depend on libc's `memcpy`
+    let (cpu, features) = if target_metrics.arch.is_wasm() {
+        ("generic".to_string(), "+bulk-memory".to_string())
+    } else if workspace.build_options.is_cross_compiling() {
+        ("generic".to_string(), "".to_string())
+    } else {
+        (
+            TargetMachine::get_host_cpu_name().to_str().unwrap().to_string(),
+            TargetMachine::get_host_cpu_features().to_str().unwrap().to_string(),
+        )
+    };
+
+    // Libraries are linked into programs that may be position independent, so they must be too
+    let reloc_mode = if workspace.build_options.output_kind.is_library() {
+        RelocMode::PIC
+    } else {
+        RelocMode::Default
+    };
+
+    target
         .create_target_machine(
             &triple,
-            host_cpu.to_str().unwrap(),
-            features.to_str().unwrap(),
+            &cpu,
+            &features,
             workspace.build_options.optimization_level.into(),
-            RelocMode::Default,
+            reloc_mode,
             CodeModel::Default,
         )
-        .unwrap();
+        .unwrap()
+}
+
+// Generates the workspace's module, along with the libraries its extern functions and variables are linked from.
+// The module isn't verified or optimized yet
+fn gen_module<'ctx>(
+    workspace: &Workspace,
+    tcx: &TypeCtx,
+    cache: &hir::Cache,
+    context: &'ctx Context,
+    target_machine: &TargetMachine,
+) -> (Module<'ctx>, HashSet<ast::ExternLibrary>) {
+    let module = context.create_module(
+        workspace
+            .build_options
+            .source_file
+            .file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap(),
+    );
+    let builder = context.create_builder();
 
     module.set_data_layout(&target_machine.get_target_data().get_data_layout());
     module.set_triple(&target_machine.get_triple());
 
-    let mut cg = Generator {
-        workspace,
-        tcx,
-        cache,
-        target_metrics: target_metrics.clone(),
-        context: &context,
-        module: &module,
-        builder: &builder,
-        ptr_sized_int_type: context.ptr_sized_int_type(&target_machine.get_target_data(), None),
-        global_decls: HashMap::default(),
-        types: HashMap::default(),
-        static_strs: UstrMap::default(),
-        functions: HashMap::default(),
-        extern_functions: UstrMap::default(),
-        extern_variables: UstrMap::default(),
-        extern_libraries: HashSet::default(),
-        intrinsics: HashMap::default(),
-        startup_function_state: None,
+    // The generator borrows the module, so it's dropped before the module is returned
+    let extern_libraries = {
+        let mut cg = Generator {
+            workspace,
+            tcx,
+            cache,
+            target_metrics: workspace.build_options.target_platform.metrics(),
+            context,
+            module: &module,
+            builder: &builder,
+            ptr_sized_int_type: context.ptr_sized_int_type(&target_machine.get_target_data(), None),
+            global_decls: HashMap::default(),
+            types: HashMap::default(),
+            static_strs: UstrMap::default(),
+            functions: HashMap::default(),
+            extern_functions: UstrMap::default(),
+            extern_variables: UstrMap::default(),
+            extern_libraries: HashSet::default(),
+            intrinsics: HashMap::default(),
+            startup_function_state: None,
+            debug_info: None,
+        };
+
+        cg.init_debug_info();
+
+        time! { workspace.build_options.emit_times, "llvm", {
+            cg.start();
+        }};
+
+        cg.finalize_debug_info();
+
+        cg.extern_libraries
     };
 
-    time! { workspace.build_options.emit_times, "llvm", {
-        cg.start();
-    }};
+    (module, extern_libraries)
+}
 
-    if let Err(e) = cg.module.verify() {
-        cg.module.print_to_file("fail.ll").unwrap();
-        panic!("{}", e);
-    }
+fn optimize(module: &Module, optimization_level: build_options::OptimizationLevel) {
+    let pass_manager_builder = PassManagerBuilder::create();
 
-    time! { workspace.build_options.emit_times, "llvm opt", {
-        cg.optimize();
-    }};
+    let size_level: u32 = match optimization_level {
+        build_options::OptimizationLevel::Debug => 1,
+        build_options::OptimizationLevel::Release => 2,
+    };
 
-    build_executable(
-        &workspace.build_options,
-        &target_machine,
-        &target_metrics,
-        &module,
-        &cg.extern_libraries,
-    )
+    pass_manager_builder.set_optimization_level(optimization_level.into());
+    pass_manager_builder.set_size_level(size_level);
+
+    let pass_manager = PassManager::create(());
+    pass_manager_builder.populate_module_pass_manager(&pass_manager);
+    pass_manager.run_on(module);
+
+    let link_time_optimizations = PassManager::create(());
+    pass_manager_builder.populate_lto_pass_manager(&link_time_optimizations, false, true);
+    link_time_optimizations.run_on(module);
 }
 
 impl From<build_options::OptimizationLevel> for OptimizationLevel {
@@ -137,7 +206,7 @@ impl From<build_options::OptimizationLevel> for OptimizationLevel {
     }
 }
 
-fn build_executable(
+fn build_output(
     build_options: &BuildOptions,
     target_machine: &TargetMachine,
     target_metrics: &TargetMetrics,
@@ -163,10 +232,19 @@ fn build_executable(
         output_path.with_extension("o")
     };
 
-    let output_file = if target_metrics.os == Os::Windows {
-        output_path.with_extension("exe")
-    } else {
-        output_path.with_extension("")
+    let output_file = match build_options.output_kind {
+        OutputKind::Executable if target_metrics.os == Os::Windows => output_path.with_extension("exe"),
+        OutputKind::Executable | OutputKind::SharedLibrary if target_metrics.arch.is_wasm() => {
+            output_path.with_extension("wasm")
+        }
+        OutputKind::Executable => output_path.with_extension(""),
+        OutputKind::StaticLibrary if target_metrics.os == Os::Windows => output_path.with_extension("lib"),
+        OutputKind::StaticLibrary => output_path.with_extension("a"),
+        OutputKind::SharedLibrary => match target_metrics.os {
+            Os::Windows => output_path.with_extension("dll"),
+            Os::Darwin => output_path.with_extension("dylib"),
+            _ => output_path.with_extension("so"),
+        },
     };
 
     time! { build_options.emit_times, "write obj",
@@ -175,8 +253,45 @@ fn build_executable(
             .unwrap()
     };
 
+    // A static library is an archive of the object file, so it isn't linked
+    if build_options.output_kind == OutputKind::StaticLibrary {
+        time! { build_options.emit_times, "archive",
+            archive(target_metrics, &output_file, &object_file)
+        }
+
+        let _ = std::fs::remove_file(object_file);
+
+        return output_file.absolutize().unwrap().to_path_buf();
+    }
+
+    // wasm modules are linked with `wasm-ld`, which can link them on any platform.
+    // Other platforms are only linked by default when building for the current platform
+    let linker = match &build_options.linker {
+        Some(linker) => Some(linker.to_str().unwrap()),
+        None if target_metrics.arch.is_wasm() => is_available("wasm-ld").then(|| "wasm-ld"),
+        None if build_options.is_cross_compiling() => None,
+        None if target_metrics.os == Os::Windows => Some("lld-link"),
+        None => Some("clang"),
+    };
+
+    // Without a linker, the object file is the build's output
+    let linker = match linker {
+        Some(linker) => linker,
+        None => {
+            // Written to stderr, so that it isn't mixed with the output of tools which read stdout
+            eprintln!(
+                "{} no linker is available for `{}`, so `{}` wasn't linked. A linker can be given with `--linker`",
+                "note:".cyan().bold(),
+                build_options.target_platform.name(),
+                object_file.display()
+            );
+
+            return object_file.absolutize().unwrap().to_path_buf();
+        }
+    };
+
     time! { build_options.emit_times, "link",
-        link(target_metrics, &output_file, &object_file, &extern_libraries)
+        link(linker, target_metrics, build_options.output_kind, &output_file, &object_file, &extern_libraries)
     }
 
     let _ = std::fs::remove_file(object_file);
@@ -185,7 +300,9 @@ fn build_executable(
 }
 
 fn link(
+    linker: &str,
     target_metrics: &TargetMetrics,
+    output_kind: OutputKind,
     executable_file: &PathBuf,
     object_file: &PathBuf,
     extern_libraries: &HashSet<ast::ExternLibrary>,
@@ -211,10 +328,11 @@ fn link(
             let mut link_flags = vec!["--allow-undefined"];
 
             if matches!(target_metrics.arch, Arch::Wasm64) {
-                link_flags.push("-mwas64");
+                link_flags.push("-mwasm64");
             }
 
-            if matches!(target_metrics.os, Os::Freestanding) {
+            // Modules without an entry point are only used through their exports
+            if matches!(target_metrics.os, Os::Freestanding | Os::Js) || output_kind.is_library() {
                 link_flags.push("--no-entry");
             }
 
@@ -228,7 +346,8 @@ fn link(
     for lib in extern_libraries.iter() {
         match lib {
             ast::ExternLibrary::System(lib_name) => {
-                if !is_libc(lib_name) {
+                // On wasm, system libraries are imported from the host instead of being linked
+                if !is_libc(lib_name) && !target_metrics.arch.is_wasm() {
                     libs.push(lib_name.clone())
                 }
             }
@@ -239,58 +358,106 @@ fn link(
         }
     }
 
-    #[cfg(windows)]
-    {
-        let find_result = unsafe { microsoft_craziness::find_visual_studio_and_windows_sdk() };
+    if target_metrics.os == Os::Windows {
+        #[cfg(windows)]
+        {
+            let find_result = unsafe { microsoft_craziness::find_visual_studio_and_windows_sdk() };
 
-        if let Some(path) = &find_result.windows_sdk_ucrt_library_path {
-            lib_paths.push(path.to_string().unwrap());
-        }
+            if let Some(path) = &find_result.windows_sdk_ucrt_library_path {
+                lib_paths.push(path.to_string().unwrap());
+            }
 
-        if let Some(path) = &find_result.windows_sdk_um_library_path {
-            lib_paths.push(path.to_string().unwrap());
-        }
+            if let Some(path) = &find_result.windows_sdk_um_library_path {
+                lib_paths.push(path.to_string().unwrap());
+            }
 
-        if let Some(path) = &find_result.vs_library_path {
-            lib_paths.push(path.to_string().unwrap());
+            if let Some(path) = &find_result.vs_library_path {
+                lib_paths.push(path.to_string().unwrap());
+            }
         }
 
-        Command::new("lld-link")
+        // A DLL's entry point is the CRT's default one, which initializes the CRT when the DLL is loaded
+        let output_kind_flags = if output_kind == OutputKind::SharedLibrary {
+            vec!["/dll"]
+        } else {
+            vec!["/entry:mainCRTStartup", "/subsystem:CONSOLE"]
+        };
+
+        Command::new(linker)
             .arg(format!("/out:{}", executable_file.to_str().unwrap()))
-            .arg("/entry:mainCRTStartup")
+            .args(output_kind_flags)
             .arg("/defaultlib:libcmt")
             .arg("/nologo")
             .arg("/incremental:no")
             .arg("/opt:ref")
             .arg("/threads:8")
-            .arg("/subsystem:CONSOLE")
             .args(lib_paths.iter().map(|path| format!("/libpath:{}", path)))
             .arg(object_file.to_str().unwrap())
             .args(libs)
             .args(link_flags)
             .execute_output()
             .unwrap();
+    } else if target_metrics.arch.is_wasm() {
+        Command::new(linker)
+            .arg(object_file.to_str().unwrap())
+            .arg("-o")
+            .arg(executable_file.to_str().unwrap())
+            .args(lib_paths.iter().map(|path| format!("-L{}", path)))
+            .args(libs.iter().map(|path| format!("-l:{}", path)))
+            .args(link_flags)
+            .execute_output()
+            .unwrap();
+    } else {
+        Command::new(linker)
+            .arg("-Wno-unused-command-line-argument")
+            .arg(object_file.to_str().unwrap())
+            .arg(format!("-o{}", executable_file.to_str().unwrap()))
+            .args(lib_paths.iter().map(|path| format!("-L{}", path)))
+            .arg("-lc")
+            .arg("-lm")
+            .args(libs.iter().map(|path| format!("-l:{}", path)))
+            .arg(if output_kind == OutputKind::SharedLibrary {
+                "-shared"
+            } else {
+                "-no-pie"
+            })
+            .args(link_flags)
+            .execute_output()
+            .unwrap();
     }
+}
 
-    #[cfg(not(windows))]
-    Command::new("clang")
-        .arg("-Wno-unused-command-line-argument")
-        .arg(object_file.to_str().unwrap())
-        .arg(format!("-o{}", executable_file.to_str().unwrap()))
-        .args(lib_paths.iter().map(|path| format!("-L{}", path)))
-        .arg("-lc")
-        .arg("-lm")
-        .args(libs.iter().map(|path| format!("-l:{}", path)))
-        .arg("-no-pie")
-        .args(link_flags)
-        .execute_output()
-        .unwrap();
+// Archives the object file into a static library, with the archiver that matches the target's linker
+fn archive(target_metrics: &TargetMetrics, library_file: &PathBuf, object_file: &PathBuf) {
+    if target_metrics.os == Os::Windows {
+        Command::new("lld-link")
+            .arg("/lib")
+            .arg("/nologo")
+            .arg(format!("/out:{}", library_file.to_str().unwrap()))
+            .arg(object_file.to_str().unwrap())
+            .execute_output()
+            .unwrap();
+    } else {
+        // `ar` adds the object file to an existing archive, instead of replacing it
+        let _ = std::fs::remove_file(library_file);
+
+        Command::new(if is_available("llvm-ar") { "llvm-ar" } else { "ar" })
+            .arg("rcs")
+            .arg(library_file.to_str().unwrap())
+            .arg(object_file.to_str().unwrap())
+            .execute_output()
+            .unwrap();
+    }
 }
 
 fn is_libc(lib: &str) -> bool {
     lib.eq_ignore_ascii_case("c")
 }
 
+fn is_available(program: &str) -> bool {
+    Command::new(program).arg("--version").output().is_ok()
+}
+
 #[allow(dead_code)]
 #[repr(u32)]
 pub enum CallingConv {
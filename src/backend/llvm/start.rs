@@ -1,11 +1,16 @@
 use super::codegen::{FunctionState, Generator};
-use crate::{infer::normalize::Normalize, types::*};
-use inkwell::{module::Linkage, values::BasicValue, AddressSpace};
+use crate::{common::target::Os, infer::normalize::Normalize, types::*};
+use inkwell::{
+    attributes::AttributeLoc,
+    module::Linkage,
+    values::{BasicValue, FunctionValue},
+    AddressSpace, DLLStorageClass,
+};
 use ustr::ustr;
 
 impl<'g, 'ctx> Generator<'g, 'ctx> {
     pub(super) fn gen_start_function(&mut self) {
-        let name = self.workspace.build_options.start_function_name().unwrap();
+        let name = self.workspace.build_options.start_function_name();
 
         let linkage = Some(Linkage::External);
 
@@ -26,59 +31,78 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
         //         ret_type.fn_type(&[], false),
         //         linkage,
         //     )
-        // } else if (is_arch_wasm()) {
-        //     self.module.add_function(
-        //         "_start",
-        //         ret_type.fn_type(&[], false),
-        //         linkage,
-        //     )
-        // } else {
-        //     self.module.add_function(
-        //         "main",
-        //         ret_type.fn_type(&[
-        //             self.context.i32_type(),
-        //
-        // self.context.i8_type().ptr_type(AddressSpace::Generic).
-        // ptr_type(AddressSpace::Generic)         ], false),
-        //         linkage,
-        //     )
-        // };
-        let startup_fn_type = FunctionType {
-            params: vec![
-                FunctionTypeParam {
-                    name: ustr("argc"),
-                    ty: Type::u32(),
-                    default_value: None,
-                },
-                FunctionTypeParam {
-                    name: ustr("argv"),
-                    ty: Type::u8().pointer_type(false).pointer_type(false),
-                    default_value: None,
-                },
-            ],
-            return_type: Box::new(Type::u32()),
-            varargs: None,
-            kind: FunctionTypeKind::Orphan,
+        // }
+
+        // On wasm, the start function takes no arguments and returns nothing.
+        // Its exit code is 0, unless the program exits by calling `proc_exit`.
+        // A library's start function only initializes static variables, so it doesn't either
+        let is_wasm = self.target_metrics.arch.is_wasm();
+        let is_library = self.workspace.build_options.output_kind.is_library();
+        let returns_exit_code = !is_wasm && !is_library;
+
+        let startup_fn_type = if !returns_exit_code {
+            FunctionType {
+                params: vec![],
+                return_type: Box::new(Type::Unit),
+                varargs: None,
+                kind: FunctionTypeKind::Orphan,
+            }
+        } else {
+            FunctionType {
+                params: vec![
+                    FunctionTypeParam {
+                        name: ustr("argc"),
+                        ty: Type::u32(),
+                        default_value: None,
+                    },
+                    FunctionTypeParam {
+                        name: ustr("argv"),
+                        ty: Type::u8().pointer_type(false).pointer_type(false),
+                        default_value: None,
+                    },
+                ],
+                return_type: Box::new(Type::u32()),
+                varargs: None,
+                kind: FunctionTypeKind::Orphan,
+            }
         };
 
-        let function = self.module.add_function(
-            name,
-            self.context.i32_type().fn_type(
-                &[
-                    self.context.i32_type().into(),
-                    self.context
-                        .i8_type()
-                        .ptr_type(AddressSpace::Generic)
-                        .ptr_type(AddressSpace::Generic)
-                        .into(),
-                ],
-                false,
-            ),
-            linkage,
-        );
+        let function = if !returns_exit_code {
+            self.module
+                .add_function(name, self.context.void_type().fn_type(&[], false), linkage)
+        } else {
+            let function = self.module.add_function(
+                name,
+                self.context.i32_type().fn_type(
+                    &[
+                        self.context.i32_type().into(),
+                        self.context
+                            .i8_type()
+                            .ptr_type(AddressSpace::Generic)
+                            .ptr_type(AddressSpace::Generic)
+                            .into(),
+                    ],
+                    false,
+                ),
+                linkage,
+            );
+
+            function.get_nth_param(0).unwrap().set_name("argc");
+            function.get_nth_param(1).unwrap().set_name("argv");
+
+            function
+        };
 
-        function.get_nth_param(0).unwrap().set_name("argc");
-        function.get_nth_param(1).unwrap().set_name("argv");
+        // A start function that isn't the target's entry, such as `_initialize`, is exported to the wasm host.
+        // Elsewhere, the library's static variables are initialized by the loader, before any of its functions are called
+        if !self.workspace.build_options.need_entry_point_function() {
+            if is_wasm {
+                self.export_function(function, name);
+            } else {
+                function.set_linkage(Linkage::Private);
+                self.add_global_constructor(function);
+            }
+        }
 
         let decl_block = self.context.append_basic_block(function, "decls");
         let entry_block = self.context.append_basic_block(function, "entry");
@@ -100,28 +124,32 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
 
         self.startup_function_state = Some(state.clone());
 
-        // Codegen the entry point function
-        let entry_point_function = self.cache.entry_point_function().unwrap();
+        // Codegen the entry point function. Targets without one only initialize static variables
+        if let Some(entry_point_function) = self.cache.entry_point_function() {
+            self.gen_function(entry_point_function.id, None);
 
-        self.gen_function(entry_point_function.id, None);
+            // Call the entry point function
+            let entry_point_function_value = *self.functions.get(&entry_point_function.id).unwrap();
 
-        // Call the entry point function
-        let entry_point_function_value = *self.functions.get(&entry_point_function.id).unwrap();
+            let entry_point_function_type = entry_point_function.ty.normalize(self.tcx).into_function();
 
-        let entry_point_function_type = entry_point_function.ty.normalize(self.tcx).into_function();
-
-        self.gen_function_call(
-            &mut state,
-            entry_point_function_value,
-            &entry_point_function_type,
-            vec![],
-            &entry_point_function_type.return_type,
-        );
+            self.gen_function_call(
+                &mut state,
+                entry_point_function_value,
+                &entry_point_function_type,
+                vec![],
+                &entry_point_function_type.return_type,
+            );
+        }
 
         // TODO: if this is DLL Main, return 1 instead of 0
 
         if self.current_block().get_terminator().is_none() {
-            self.builder.build_return(Some(&self.context.i32_type().const_zero()));
+            if !returns_exit_code {
+                self.builder.build_return(None);
+            } else {
+                self.builder.build_return(Some(&self.context.i32_type().const_zero()));
+            }
         }
 
         self.start_block(&mut state, decl_block);
@@ -130,4 +158,57 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
 
         self.builder.build_unconditional_branch(entry_block);
     }
+
+    // Functions marked with `@export` are given external linkage, under their link name
+    pub(super) fn gen_exported_functions(&mut self) {
+        for &(id, link_name) in self.cache.exported_functions.iter() {
+            let function = self.gen_function(id, None);
+            self.export_function(function, &link_name);
+        }
+    }
+
+    pub(super) fn export_function(&self, function: FunctionValue<'ctx>, name: &str) {
+        function.as_global_value().set_name(name);
+        function.set_linkage(Linkage::External);
+
+        if self.target_metrics.arch.is_wasm() {
+            function.add_attribute(
+                AttributeLoc::Function,
+                self.context.create_string_attribute("wasm-export-name", name),
+            );
+        } else if self.target_metrics.os == Os::Windows {
+            function
+                .as_global_value()
+                .set_dll_storage_class(DLLStorageClass::Export);
+        }
+    }
+
+    // Adds `function` to `llvm.global_ctors`, so that it is called when the program or library is loaded
+    fn add_global_constructor(&self, function: FunctionValue<'ctx>) {
+        let i32_type = self.context.i32_type();
+        let data_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+        let constructor_type = self.context.struct_type(
+            &[
+                i32_type.into(),
+                function.get_type().ptr_type(AddressSpace::Generic).into(),
+                data_type.into(),
+            ],
+            false,
+        );
+
+        let constructor = constructor_type.const_named_struct(&[
+            // The lowest priority, which is the one that C++ constructors get by default
+            i32_type.const_int(65535, false).into(),
+            function.as_global_value().as_pointer_value().into(),
+            data_type.const_null().into(),
+        ]);
+
+        let constructors = self
+            .module
+            .add_global(constructor_type.array_type(1), None, "llvm.global_ctors");
+
+        constructors.set_linkage(Linkage::Appending);
+        constructors.set_initializer(&constructor_type.const_array(&[constructor]));
+    }
 }
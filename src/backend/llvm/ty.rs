@@ -1,5 +1,5 @@
 use super::{
-    abi::{self, AbiFunction, AbiType},
+    abi::{self, AbiFunction},
     codegen::Generator,
 };
 use crate::{
@@ -7,7 +7,7 @@ use crate::{
     types::{size_of::SizeOf, *},
 };
 use inkwell::{
-    types::{AnyType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, PointerType},
+    types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, PointerType},
     AddressSpace,
 };
 use std::cmp::Ordering;
@@ -54,12 +54,14 @@ impl<'g, 'ctx> IntoLlvmType<'g, 'ctx> for Type {
             },
             Type::Pointer(inner, _) => inner.llvm_type(generator).ptr_type(AddressSpace::Generic).into(),
             Type::Slice(inner) | Type::Str(inner) => generator.slice_type(inner).into(),
-            Type::Type(_) | Type::Unit | Type::Module { .. } => generator.unit_type(),
+            Type::Type(_) | Type::Unit | Type::Module { .. } | Type::Trait(_) => generator.unit_type(),
+            Type::Param(_) => unreachable!("type parameters are substituted before codegen"),
             Type::Never => generator.never_type(),
             Type::Function(func) => generator
                 .abi_compliant_fn_type(func)
                 .ptr_type(AddressSpace::Generic)
                 .into(),
+            Type::Closure(_) => generator.closure_type().into(),
             Type::Array(inner, size) => inner.llvm_type(generator).array_type(*size as u32).into(),
             Type::Tuple(tys) => generator
                 .context
@@ -79,6 +81,16 @@ impl<'g, 'ctx> IntoLlvmType<'g, 'ctx> for Type {
 
                 struct_type.into()
             }
+            Type::Enum(enum_type) => {
+                // Named enums are lowered to named structs, which makes the generated IR easier to read
+                let layout = StructType {
+                    name: enum_type.name,
+                    id: enum_type.id,
+                    ..enum_type.layout(generator.target_metrics.word_size)
+                };
+
+                Type::Struct(layout).llvm_type(generator)
+            }
             _ => {
                 panic!("bug: type `{}` in llvm codegen", self.display(generator.tcx))
             }
@@ -99,6 +111,13 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
         self.context.i8_type().ptr_type(AddressSpace::Generic)
     }
 
+    // A closure is lowered to a pair of its type-erased function and environment pointers
+    pub(super) fn closure_type(&self) -> inkwell::types::StructType<'ctx> {
+        let raw_pointer_type = self.raw_pointer_type();
+        self.context
+            .struct_type(&[raw_pointer_type.into(), raw_pointer_type.into()], false)
+    }
+
     pub(super) fn slice_type(&mut self, elem_type: &Type) -> inkwell::types::StructType<'ctx> {
         self.fat_pointer_type(elem_type, &Type::uint())
     }
@@ -144,40 +163,7 @@ impl<'g, 'ctx> Generator<'g, 'ctx> {
     }
 
     pub(super) fn abi_fn_to_type(&mut self, abi_fn: &AbiFunction<'ctx>) -> inkwell::types::FunctionType<'ctx> {
-        let ret = match abi_fn.ret.kind {
-            AbiType::Direct => match abi_fn.ret.cast_to {
-                Some(cast_to) => cast_to,
-                None => abi_fn.ret.ty,
-            }
-            .as_any_type_enum(),
-            AbiType::Indirect | AbiType::Ignore => self.context.void_type().into(),
-        };
-
-        let mut params: Vec<BasicMetadataTypeEnum> = vec![];
-
-        if abi_fn.ret.kind.is_indirect() {
-            params.push(abi_fn.ret.ty.ptr_type(AddressSpace::Generic).into());
-        }
-
-        for param in abi_fn.params.iter() {
-            let ty = match &param.kind {
-                AbiType::Direct => match param.cast_to {
-                    Some(cast_to) => cast_to,
-                    None => param.ty,
-                },
-                AbiType::Indirect => param.ty.ptr_type(AddressSpace::Generic).into(),
-                AbiType::Ignore => unimplemented!("ignore '{:?}'", param.ty),
-            };
-
-            params.push(ty.into());
-        }
-
-        if ret.is_void_type() {
-            ret.into_void_type().fn_type(&params, abi_fn.variadic)
-        } else {
-            let ret: BasicTypeEnum = ret.try_into().unwrap();
-            ret.fn_type(&params, abi_fn.variadic)
-        }
+        abi::abi_fn_type(self.context, abi_fn)
     }
 
     fn get_or_create_named_struct_type(&mut self, struct_type: &StructType) -> inkwell::types::StructType<'ctx> {
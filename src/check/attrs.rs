@@ -10,8 +10,13 @@ use crate::{
         attrs::{Attr, AttrKind, Attrs},
         const_value::ConstValue,
     },
-    infer::{display::OrReportErr, unify::UnifyType},
-    types::TypeId,
+    infer::{
+        display::{DisplayType, OrReportErr},
+        normalize::Normalize,
+        unify::UnifyType,
+    },
+    types::{StructType, Type, TypeId},
+    workspace::BindingInfoFlags,
 };
 
 impl<'s> CheckSess<'s> {
@@ -78,7 +83,9 @@ impl<'s> CheckSess<'s> {
 
     fn get_attr_expected_type(&self, kind: AttrKind) -> TypeId {
         match kind {
-            AttrKind::Intrinsic | AttrKind::TrackCaller => self.tcx.common_types.unit,
+            AttrKind::Intrinsic | AttrKind::TrackCaller | AttrKind::Test | AttrKind::Copy | AttrKind::Export => {
+                self.tcx.common_types.unit
+            }
             AttrKind::Lib | AttrKind::Dylib | AttrKind::LinkName => self.tcx.common_types.str_pointer,
         }
     }
@@ -100,7 +107,7 @@ impl<'s> CheckSess<'s> {
                     ast::BindingKind::ExternFunction { .. } | ast::BindingKind::ExternVariable { .. } => (),
                     _ => return Err(invalid_attr_use(attr, "can only be used on extern functions")),
                 },
-                AttrKind::Lib | AttrKind::Dylib | AttrKind::LinkName => match &binding.kind {
+                AttrKind::Lib | AttrKind::Dylib => match &binding.kind {
                     ast::BindingKind::ExternFunction { .. } | ast::BindingKind::ExternVariable { .. } => (),
                     _ => {
                         return Err(invalid_attr_use(
@@ -109,16 +116,53 @@ impl<'s> CheckSess<'s> {
                         ))
                     }
                 },
-                AttrKind::TrackCaller => match &binding.kind {
+                // An exported function is exported under its `link_name`, if it has one
+                AttrKind::LinkName => match &binding.kind {
+                    ast::BindingKind::ExternFunction { .. } | ast::BindingKind::ExternVariable { .. } => (),
+                    ast::BindingKind::Function { .. } if attrs.has(AttrKind::Export) => (),
+                    _ => {
+                        return Err(invalid_attr_use(
+                            attr,
+                            "can only be used on extern variables, extern functions and exported functions",
+                        ))
+                    }
+                },
+                AttrKind::TrackCaller | AttrKind::Test | AttrKind::Export => match &binding.kind {
                     ast::BindingKind::Function { .. } => (),
                     _ => return Err(invalid_attr_use(attr, "can only be used on functions")),
                 },
+                AttrKind::Copy => match &binding.kind {
+                    ast::BindingKind::Type { .. } => (),
+                    _ => return Err(invalid_attr_use(attr, "can only be used on struct types")),
+                },
             }
         }
 
         Ok(())
     }
 
+    // A struct type marked with `@copy` is copied when it is used by value, instead of being moved
+    pub(super) fn apply_copy_attr(&mut self, attrs: &Attrs, ty: TypeId) -> DiagnosticResult<()> {
+        let attr = match attrs.get(AttrKind::Copy) {
+            Some(attr) => attr,
+            None => return Ok(()),
+        };
+
+        match ty.normalize(&self.tcx) {
+            Type::Struct(StructType { id: Some(id), .. }) => {
+                self.workspace.binding_infos.get_mut(id).unwrap().flags |= BindingInfoFlags::COPY;
+                Ok(())
+            }
+            ty => Err(Diagnostic::error()
+                .with_message(format!(
+                    "the `{}` attribute can only be used on struct types",
+                    attr.kind
+                ))
+                .with_label(Label::primary(attr.span, "invalid attribute use"))
+                .with_note(format!("found type `{}`", ty.display(&self.tcx)))),
+        }
+    }
+
     pub(super) fn maybe_get_extern_lib_attr(
         &self,
         env: &Env,
@@ -0,0 +1,280 @@
+use super::{env::Env, CheckResult, CheckSess};
+use crate::{
+    ast,
+    error::diagnostic::{Diagnostic, Label},
+    hir::{
+        self,
+        const_value::{ConstFunction, ConstValue},
+    },
+    infer::normalize::Normalize,
+    span::Span,
+    sym,
+    types::{FunctionType, FunctionTypeParam, StructType, StructTypeField, StructTypeKind, Type, TypeId},
+    workspace::{BindingId, BindingInfoFlags, BindingInfoKind, ScopeLevel},
+};
+use ustr::{ustr, Ustr};
+
+// Every function that is declared inside of another function gets a closure frame.
+// If the function refers to a local binding of an outer function, that binding is captured,
+// and the function becomes a closure.
+#[derive(Debug, Clone)]
+pub struct ClosureFrame {
+    pub function_id: hir::FunctionId,
+    // The closure's environment parameter, along with the type of the environment it points to.
+    // This is only created once the first binding is captured
+    pub env: Option<(BindingId, TypeId)>,
+    pub captures: Vec<Capture>,
+    // Set when the function refers to itself by name, which a closure cannot do
+    pub self_reference_span: Option<Span>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Capture {
+    pub id: BindingId,
+    pub name: Ustr,
+    pub ty: TypeId,
+    pub span: Span,
+}
+
+impl ClosureFrame {
+    pub fn new(function_id: hir::FunctionId) -> Self {
+        Self {
+            function_id,
+            env: None,
+            captures: vec![],
+            self_reference_span: None,
+        }
+    }
+}
+
+impl<'s> CheckSess<'s> {
+    // Refers to a local binding from the current function.
+    // If the binding belongs to an outer function, it is captured by the current closure
+    pub(super) fn capture_or_id(&mut self, env: &mut Env, id: BindingId, span: Span) -> CheckResult {
+        let function_scope = self.function_frame().map_or(ScopeLevel::Global, |f| f.scope_level);
+        let binding_info = self.workspace.binding_infos.get(id).unwrap();
+
+        if !binding_info.scope_level.is_global() && binding_info.scope_level < function_scope {
+            self.capture_binding(env, id, span)
+        } else {
+            Ok(self.id_or_const_by_id(id, span))
+        }
+    }
+
+    // Captures `id` by value into the current closure's environment,
+    // and returns an access to the captured value through the environment parameter
+    pub(super) fn capture_binding(&mut self, env: &mut Env, id: BindingId, span: Span) -> CheckResult {
+        let binding_info = self.workspace.binding_infos.get(id).unwrap();
+        let (name, ty) = (binding_info.name, binding_info.ty);
+
+        let (env_param_id, env_type) = match self.closure_frames.last().unwrap().env {
+            Some(closure_env) => closure_env,
+            None => {
+                let env_type = self.tcx.var(span);
+                let env_param_type = self.tcx.bound(Type::Pointer(Box::new(env_type.as_kind()), false), span);
+
+                let (env_param_id, _) = self.bind_name(
+                    env,
+                    ustr(sym::CLOSURE_ENV_PARAM),
+                    ast::Vis::Private,
+                    env_param_type,
+                    None,
+                    false,
+                    BindingInfoKind::LetConst,
+                    span,
+                    BindingInfoFlags::empty(),
+                )?;
+
+                self.closure_frames.last_mut().unwrap().env = Some((env_param_id, env_type));
+
+                (env_param_id, env_type)
+            }
+        };
+
+        let frame = self.closure_frames.last_mut().unwrap();
+
+        let member_index = match frame.captures.iter().position(|capture| capture.id == id) {
+            Some(index) => index,
+            None => {
+                frame.captures.push(Capture { id, name, ty, span });
+                frame.captures.len() - 1
+            }
+        };
+
+        let env_param_type = self.workspace.binding_infos.get(env_param_id).unwrap().ty;
+
+        Ok(hir::Node::MemberAccess(hir::MemberAccess {
+            value: Box::new(hir::Node::Builtin(hir::Builtin::Deref(hir::Unary {
+                value: Box::new(hir::Node::Id(hir::Id {
+                    id: env_param_id,
+                    ty: env_param_type,
+                    span,
+                })),
+                ty: env_type,
+                span,
+            }))),
+            member_name: name,
+            member_index: member_index as _,
+            ty,
+            span,
+        }))
+    }
+
+    // Called when a function is referred to by its name. Closures can't refer to themselves,
+    // since their environment isn't available through their name
+    pub(super) fn record_closure_self_reference(&mut self, function_id: hir::FunctionId, span: Span) {
+        if let Some(frame) = self
+            .closure_frames
+            .iter_mut()
+            .find(|frame| frame.function_id == function_id)
+        {
+            frame.self_reference_span.get_or_insert(span);
+        }
+    }
+}
+
+// Turns a function that captured its environment into a closure:
+// - The environment parameter is prepended to the function's parameters
+// - The captured values are stored in an environment struct
+// - The function is paired with its environment, which codegen moves to the heap,
+//   so that the closure can outlive the function that created it.
+//   The environment is never freed, since nothing tracks when the last copy of the closure goes away
+pub(super) fn build_closure(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    frame: ClosureFrame,
+    function_type: &FunctionType,
+    span: Span,
+) -> CheckResult {
+    let (env_param_id, env_type) = frame.env.unwrap();
+
+    if let Some(self_reference_span) = frame.self_reference_span {
+        let function = sess.cache.functions.get(frame.function_id).unwrap();
+
+        return Err(Diagnostic::error()
+            .with_message(format!("closure `{}` cannot refer to itself", function.name))
+            .with_label(Label::primary(self_reference_span, "refers to itself"))
+            .with_label(Label::secondary(
+                frame.captures.first().unwrap().span,
+                "the function becomes a closure because it captures this",
+            ))
+            .with_note("a closure's environment isn't available through its name"));
+    }
+
+    let env_struct_type = Type::Struct(StructType {
+        name: ustr(""),
+        id: None,
+        fields: frame
+            .captures
+            .iter()
+            .map(|capture| StructTypeField {
+                name: capture.name,
+                ty: capture.ty.normalize(&sess.tcx),
+                span: capture.span,
+            })
+            .collect(),
+        kind: StructTypeKind::Struct,
+    });
+
+    sess.tcx.bind_ty(env_type, env_struct_type);
+
+    let env_param_type = sess.workspace.binding_infos.get(env_param_id).unwrap().ty;
+
+    // The function receives its environment as its first parameter
+    let underlying_function_type = {
+        let mut params = vec![FunctionTypeParam {
+            name: ustr(sym::CLOSURE_ENV_PARAM),
+            ty: env_param_type.as_kind(),
+            default_value: None,
+        }];
+
+        params.extend(function_type.params.iter().cloned());
+
+        Type::Function(FunctionType {
+            params,
+            ..function_type.clone()
+        })
+    };
+
+    let underlying_function_type = sess.tcx.bound(underlying_function_type, span);
+
+    let function = sess.cache.functions.get_mut(frame.function_id).unwrap();
+
+    function.ty = underlying_function_type;
+
+    if let hir::FunctionKind::Orphan { params, .. } = &mut function.kind {
+        params.insert(
+            0,
+            hir::FunctionParam {
+                id: env_param_id,
+                ty: env_param_type,
+                span,
+            },
+        );
+    }
+
+    let function_node = hir::Node::Const(hir::Const {
+        value: ConstValue::Function(ConstFunction {
+            id: frame.function_id,
+            name: function.name,
+        }),
+        ty: underlying_function_type,
+        span,
+    });
+
+    // The captured values are resolved in the enclosing function, which may capture them itself
+    let mut fields = vec![];
+
+    for capture in frame.captures.iter() {
+        let value = sess.capture_or_id(env, capture.id, capture.span)?;
+
+        fields.push(hir::StructLiteralField {
+            name: capture.name,
+            value: Box::new(value),
+            ty: capture.ty,
+            span: capture.span,
+        });
+    }
+
+    let env_literal = hir::Node::Literal(hir::Literal::Struct(hir::StructLiteral {
+        fields,
+        ty: env_type,
+        span,
+    }));
+
+    let closure_type = sess.tcx.bound(Type::Closure(function_type.clone()), span);
+
+    Ok(hir::Node::Literal(hir::Literal::Closure(hir::ClosureLiteral {
+        function: Box::new(function_node),
+        env: Some(Box::new(env_literal)),
+        ty: closure_type,
+        span,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hir::const_value::ConstValue, test_utils::*};
+
+    #[test]
+    fn returned_closure_outlives_its_creator() {
+        // `make_adder`'s second call reuses the stack of its first call,
+        // which would overwrite the first closure's environment if it lived there
+        let result = check_source(
+            r#"
+fn make_adder(n: int) -> closure(x: int) -> int = fn(x) { x + n }
+
+let result = comptime {
+    let add_40 = make_adder(40)
+    let _ = make_adder(100)
+    add_40(2)
+}
+
+fn main() = ()
+"#,
+        );
+
+        assert!(errors(&result).is_empty());
+        assert_eq!(global_const(&result, "result"), Some(ConstValue::Int(42)));
+    }
+}
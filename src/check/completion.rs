@@ -0,0 +1,71 @@
+use super::{env::Env, CheckSess};
+use crate::{
+    hir::{CompletionSite, CompletionSiteKind},
+    infer::normalize::Normalize,
+    span::Span,
+    types::{Type, TypeId},
+    workspace::BindingId,
+};
+use ustr::UstrSet;
+
+impl<'s> CheckSess<'s> {
+    // Records the local bindings visible from `env`, if `span` is at the completion offset
+    pub(super) fn record_scope_completion_site(&mut self, env: &Env, span: Span) {
+        if !self.is_completion_site(env, span) {
+            return;
+        }
+
+        let mut seen_names = UstrSet::default();
+        let mut bindings: Vec<BindingId> = vec![];
+
+        // Inner scopes shadow outer scopes, so they are visited first
+        for scope in env.scopes().iter().rev() {
+            for (&name, &id) in scope.bindings.iter() {
+                if seen_names.insert(name) {
+                    bindings.push(id);
+                }
+            }
+        }
+
+        self.cache.completion_site = Some(CompletionSite {
+            kind: CompletionSiteKind::Scope(bindings),
+            span,
+        });
+    }
+
+    // Records the type of an accessed value, if the accessed member's span is at the completion offset
+    pub(super) fn record_member_completion_site(&mut self, env: &Env, ty: TypeId, member_span: Span) {
+        if !self.is_completion_site(env, member_span) {
+            return;
+        }
+
+        self.cache.completion_site = Some(CompletionSite {
+            kind: CompletionSiteKind::Member(ty),
+            span: member_span,
+        });
+
+        // Associated functions are checked on demand, so all of the type's functions are checked to complete them
+        let struct_type = match ty.normalize(&self.tcx) {
+            Type::Type(inner) => *inner,
+            ty => ty.maybe_deref_once(),
+        };
+
+        if let Type::Struct(struct_type) = struct_type {
+            if let Err(diagnostic) = self.check_associated_functions_of(&struct_type) {
+                self.workspace.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    fn is_completion_site(&self, env: &Env, span: Span) -> bool {
+        match self.workspace.build_options.completion_offset {
+            // Note that the offset is inclusive on both ends, since the cursor is usually placed right after the name
+            Some(offset) => {
+                env.module_id() == self.workspace.root_module_id
+                    && span.start.index <= offset
+                    && offset <= span.end.index
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use super::{env::Env, Check, CheckResult, CheckSess};
+use crate::{ast, hir, types::TypeId};
+
+// `defer expr` attaches `expr` to its enclosing block, and is run when the block exits.
+// Control flow can't escape a deferred expression, so `return`, `?`, and `break`/`continue`
+// to a loop outside of it are rejected.
+impl Check for ast::Defer {
+    fn check(&self, sess: &mut CheckSess, env: &mut Env, _expected_type: Option<TypeId>) -> CheckResult {
+        let loop_depth = std::mem::replace(&mut sess.loop_depth, 0);
+
+        let defer_span = sess
+            .function_frames
+            .last_mut()
+            .map(|frame| std::mem::replace(&mut frame.defer_span, Some(self.span)));
+
+        let expr = self.expr.check(sess, env, None);
+
+        sess.loop_depth = loop_depth;
+
+        if let (Some(frame), Some(defer_span)) = (sess.function_frames.last_mut(), defer_span) {
+            frame.defer_span = defer_span;
+        }
+
+        Ok(hir::Node::Control(hir::Control::Defer(hir::Defer {
+            expr: Box::new(expr?),
+            ty: sess.tcx.common_types.unit,
+            span: self.span,
+        })))
+    }
+}
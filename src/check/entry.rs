@@ -1,13 +1,26 @@
-use super::CheckSess;
+use super::{env::Env, CheckResult, CheckSess};
 use crate::{
     error::diagnostic::{Diagnostic, Label},
+    hir::{
+        self,
+        attrs::Attr,
+        const_value::{ConstFunction, ConstValue},
+    },
     infer::{display::DisplayType, normalize::Normalize},
     span::Span,
+    types::{FunctionType, FunctionTypeKind, Type},
+    workspace::{ModuleId, ScopeLevel},
 };
+use ustr::ustr;
 
 impl<'s> CheckSess<'s> {
     pub(super) fn check_entry_point_function_exists(&mut self) {
-        if self.workspace.build_options.need_entry_point_function() {
+        if self.workspace.build_options.test_mode {
+            // In test mode, the entry point runs all test functions, so `main` isn't required
+            if let Err(diag) = self.build_test_entry_point() {
+                self.workspace.diagnostics.push(diag);
+            }
+        } else if self.workspace.build_options.need_entry_point_function() {
             if let Some(function) = self.cache.entry_point_function() {
                 let ty = function.ty.normalize(&self.tcx).into_function();
 
@@ -39,4 +52,155 @@ impl<'s> CheckSess<'s> {
             }
         }
     }
+
+    pub(super) fn validate_test_function_binding(&self, env: &Env, attr: &Attr, is_generic: bool) -> CheckResult<()> {
+        if !env.scope_level().is_global() {
+            Err(Diagnostic::error()
+                .with_message("test functions must be declared at the top-level")
+                .with_label(Label::primary(attr.span, "test function is declared in local scope")))
+        } else if is_generic {
+            Err(Diagnostic::error()
+                .with_message("test functions cannot be generic")
+                .with_label(Label::primary(attr.span, "test function is generic")))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Collects a checked `@test` function, to be run by the entry point generated in test mode
+    pub(super) fn collect_test_function(&mut self, node: &hir::Node) -> CheckResult<()> {
+        let function_id = match node.as_const_value() {
+            Some(ConstValue::Function(f)) => f.id,
+            _ => unreachable!(),
+        };
+
+        let function = self.cache.functions.get(function_id).unwrap();
+        let ty = function.ty.normalize(&self.tcx).into_function();
+
+        if !ty.return_type.is_unit() || !ty.params.is_empty() || ty.has_c_varargs() {
+            return Err(Diagnostic::error()
+                .with_message(format!(
+                    "test function `{}` has type `{}`, expected `fn() -> ()`",
+                    function.name,
+                    ty.display(&self.tcx)
+                ))
+                .with_label(Label::primary(function.span, "invalid test function type")));
+        }
+
+        self.cache.test_functions.push(function_id);
+
+        Ok(())
+    }
+
+    // Generates the entry point of a test runner, which runs each test function
+    // through `std.testing.run_test`, and reports the results with `std.testing.report`
+    fn build_test_entry_point(&mut self) -> CheckResult<()> {
+        let root_module_id = self.workspace.root_module_id;
+        let span = Span::initial(self.workspace.get_root_module_info().file_id);
+
+        let unit = self.tcx.common_types.unit;
+
+        let run_test = self.get_function_by_name("std.testing", "run_test", span)?;
+        let report = self.get_function_by_name("std.testing", "report", span)?;
+
+        let mut statements = vec![];
+
+        for &function_id in self.cache.test_functions.iter() {
+            let function = self.cache.functions.get(function_id).unwrap();
+
+            let name = hir::Node::Const(hir::Const {
+                value: ConstValue::Str(function.qualified_name),
+                ty: self.tcx.common_types.str_pointer,
+                span,
+            });
+
+            let test = hir::Node::Const(hir::Const {
+                value: ConstValue::Function(ConstFunction {
+                    id: function_id,
+                    name: function.qualified_name,
+                }),
+                ty: function.ty,
+                span,
+            });
+
+            statements.push(hir::Node::Call(hir::Call {
+                callee: Box::new(run_test.clone()),
+                args: vec![name, test],
+                ty: unit,
+                span,
+            }));
+        }
+
+        statements.push(hir::Node::Call(hir::Call {
+            callee: Box::new(report),
+            args: vec![],
+            ty: unit,
+            span,
+        }));
+
+        let ty = self.tcx.bound(
+            Type::Function(FunctionType {
+                params: vec![],
+                return_type: Box::new(Type::Unit),
+                varargs: None,
+                kind: FunctionTypeKind::Orphan,
+            }),
+            span,
+        );
+
+        let name = ustr("test_main");
+
+        let function_id = self.cache.functions.insert_with_id(hir::Function {
+            id: hir::FunctionId::unknown(),
+            module_id: root_module_id,
+            name,
+            qualified_name: name,
+            kind: hir::FunctionKind::Orphan {
+                params: vec![],
+                inferred_return_type_span: None,
+                body: Some(hir::Sequence {
+                    statements,
+                    ty: unit,
+                    span,
+                    is_scope: true,
+                }),
+            },
+            ty,
+            span,
+        });
+
+        self.cache.entry_point_function_id = Some(function_id);
+
+        Ok(())
+    }
+
+    fn get_function_by_name(&mut self, module_name: &str, name: &str, span: Span) -> CheckResult {
+        let module_id = self
+            .workspace
+            .module_infos
+            .iter()
+            .find(|(_, m)| m.qualified_name == module_name)
+            .map(|(id, _)| ModuleId::from(id))
+            .ok_or_else(|| {
+                Diagnostic::error()
+                    .with_message(format!("couldn't find module `{}`", module_name))
+                    .with_label(Label::primary(span, "required by the test runner"))
+            })?;
+
+        self.check_module_by_id(module_id)?;
+
+        let binding_info = self
+            .workspace
+            .binding_infos
+            .iter()
+            .map(|(_, b)| b)
+            .find(|b| b.module_id == module_id && b.scope_level == ScopeLevel::Global && b.name == name)
+            .ok_or_else(|| {
+                Diagnostic::error()
+                    .with_message(format!("couldn't find function `{}` in module `{}`", name, module_name))
+                    .with_label(Label::primary(span, "required by the test runner"))
+            })?;
+
+        Ok(self.id_or_const(binding_info, span))
+    }
 }
@@ -0,0 +1,227 @@
+use super::{env::Env, env::ScopeKind, Check, CheckResult, CheckSess};
+use crate::{
+    ast,
+    error::{
+        diagnostic::{Diagnostic, Label},
+        SyntaxError,
+    },
+    hir::{
+        self,
+        const_value::{ConstEnum, ConstValue},
+    },
+    infer::{
+        coerce::OrCoerceIntoTy,
+        display::{DisplayType, OrReportErr},
+        normalize::Normalize,
+        unify::UnifyType,
+    },
+    span::Span,
+    types::{is_sized::IsSized, EnumType, EnumVariant, Type, TypeId},
+    workspace::{BindingInfoFlags, BindingInfoKind},
+};
+use ustr::UstrMap;
+
+impl Check for ast::EnumType {
+    fn check(&self, sess: &mut CheckSess, env: &mut Env, _expected_type: Option<TypeId>) -> CheckResult {
+        let is_named = !self.name.is_empty();
+
+        // the enum's main type variable
+        let enum_type_var = sess.tcx.bound(
+            Type::Enum(EnumType {
+                name: self.name,
+                id: None,
+                variants: vec![],
+            }),
+            self.span,
+        );
+
+        // the enum's main type variable, in its `type` variation
+        let enum_type_type_var = sess.tcx.bound(enum_type_var.as_kind().create_type(), self.span);
+
+        env.push_scope(ScopeKind::Block);
+
+        // named enums are nominal - their identity is the binding of their name
+        let id = if is_named {
+            let (binding_id, _) = sess.bind_name(
+                env,
+                self.name,
+                ast::Vis::Private,
+                enum_type_type_var,
+                Some(hir::Node::Const(hir::Const {
+                    value: ConstValue::Type(enum_type_var),
+                    ty: sess.tcx.common_types.anytype,
+                    span: self.span,
+                })),
+                false,
+                BindingInfoKind::LetConst,
+                self.span,
+                BindingInfoFlags::empty(),
+            )?;
+
+            Some(binding_id)
+        } else {
+            None
+        };
+
+        let mut variant_map = UstrMap::<Span>::default();
+        let mut variants = vec![];
+
+        for variant in self.variants.iter() {
+            if let Some(defined_span) = variant_map.insert(variant.name, variant.span) {
+                return Err(SyntaxError::duplicate_enum_variant(
+                    defined_span,
+                    variant.span,
+                    variant.name.to_string(),
+                ));
+            }
+
+            let mut payload = vec![];
+
+            for ty in variant.payload.iter() {
+                let node = ty.check(sess, env, Some(sess.tcx.common_types.anytype))?;
+                let ty_id = sess.require_const_type(&node)?;
+                let payload_type = ty_id.normalize(&sess.tcx);
+
+                if payload_type.is_unsized() {
+                    return Err(Diagnostic::error()
+                        .with_message(format!(
+                            "the size of `{}`s payload type `{}` cannot be known at compile-time",
+                            variant.name,
+                            payload_type.display(&sess.tcx)
+                        ))
+                        .with_label(Label::primary(ty.span(), "doesn't have a size known at compile-time"))
+                        .with_note("enum payload sizes must be known at compile-time"));
+                }
+
+                payload.push(ty_id.into());
+            }
+
+            variants.push(EnumVariant {
+                name: variant.name,
+                payload,
+                span: variant.span,
+            });
+        }
+
+        env.pop_scope();
+
+        let enum_type = Type::Enum(EnumType {
+            name: self.name,
+            id,
+            variants,
+        });
+
+        sess.tcx.bind_ty(enum_type_var, enum_type.clone());
+
+        Ok(hir::Node::Const(hir::Const {
+            ty: sess.tcx.bound(enum_type.create_type(), self.span),
+            span: self.span,
+            value: ConstValue::Type(enum_type_var),
+        }))
+    }
+}
+
+// Checks `Enum.variant`, or `Enum.variant(a, b)` when `args` are supplied
+pub(super) fn check_enum_variant(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    enum_type: &EnumType,
+    access: &ast::MemberAccess,
+    args: Option<&[ast::CallArg]>,
+    span: Span,
+) -> CheckResult {
+    let (index, variant) = match enum_type.variant_and_position(access.member) {
+        Some(variant) => variant,
+        None => {
+            return Err(Diagnostic::error()
+                .with_message(format!(
+                    "enum `{}` has no variant `{}`",
+                    enum_type.display(&sess.tcx),
+                    access.member
+                ))
+                .with_label(Label::primary(access.member_span, "unknown variant")))
+        }
+    };
+
+    let ty = sess.tcx.bound(Type::Enum(enum_type.clone()), span);
+
+    match args {
+        None if variant.has_payload() => Err(Diagnostic::error()
+            .with_message(format!(
+                "variant `{}` expects a payload of type `{}`",
+                variant.name,
+                variant.payload_type().display(&sess.tcx)
+            ))
+            .with_label(Label::primary(access.member_span, "missing payload"))
+            .with_note(format!("construct it by calling it, i.e: `{}(...)`", variant.name))),
+        None => Ok(hir::Node::Const(hir::Const {
+            value: ConstValue::Enum(ConstEnum {
+                variant_index: index,
+                variant_name: variant.name,
+            }),
+            ty,
+            span,
+        })),
+        Some(_) if !variant.has_payload() => Err(Diagnostic::error()
+            .with_message(format!("variant `{}` doesn't have a payload", variant.name))
+            .with_label(Label::primary(span, "cannot be called"))),
+        Some(args) => {
+            if args.len() != variant.payload.len() {
+                let expected = variant.payload.len();
+                let actual = args.len();
+
+                return Err(Diagnostic::error()
+                    .with_message(format!(
+                        "variant `{}` expects {} value{}, but {} {} supplied",
+                        variant.name,
+                        expected,
+                        if expected > 1 { "s" } else { "" },
+                        actual,
+                        if actual == 0 || actual > 1 { "were" } else { "was" },
+                    ))
+                    .with_label(Label::primary(
+                        span,
+                        format!("expected {} values, got {}", expected, actual),
+                    )));
+            }
+
+            let mut elements = vec![];
+
+            for (arg, payload_type) in args.iter().zip(variant.payload.iter()) {
+                if arg.spread {
+                    return Err(Diagnostic::error()
+                        .with_message("cannot spread values into a variant's payload")
+                        .with_label(Label::primary(arg.value.span(), "cannot spread")));
+                }
+
+                let expected_type = sess.tcx.bound(payload_type.clone(), arg.value.span());
+
+                let mut node = arg.value.check(sess, env, Some(expected_type))?;
+
+                node.ty()
+                    .unify(&expected_type, &mut sess.tcx)
+                    .or_coerce_into_ty(&mut node, &expected_type, &mut sess.tcx, sess.target_metrics.word_size)
+                    .or_report_err(
+                        &sess.tcx,
+                        &expected_type,
+                        Some(variant.span),
+                        &node.ty(),
+                        arg.value.span(),
+                    )?;
+
+                elements.push(node);
+            }
+
+            Ok(hir::Node::Literal(hir::Literal::Enum(hir::EnumLiteral {
+                variant_index: index as _,
+                payload: Box::new(hir::Node::Literal(hir::Literal::Tuple(hir::TupleLiteral {
+                    elements,
+                    ty: sess.tcx.bound(variant.payload_type(), span),
+                    span,
+                }))),
+                ty,
+                span,
+            })))
+        }
+    }
+}
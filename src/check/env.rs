@@ -1,7 +1,8 @@
 use crate::{
     hir,
-    workspace::{BindingId, ModuleId, ModuleInfo, ScopeLevel},
+    workspace::{BindingId, ModuleId, ModuleInfo, ScopeId, ScopeLevel},
 };
+use std::sync::atomic::{AtomicUsize, Ordering};
 use ustr::{ustr, Ustr, UstrMap};
 
 pub struct Env {
@@ -25,7 +26,6 @@ impl Env {
         self.module_id
     }
 
-    #[allow(unused)]
     pub fn module_info(&self) -> ModuleInfo {
         self.module_info
     }
@@ -65,6 +65,11 @@ impl Env {
         }
     }
 
+    // The ids of the current scopes, which tell apart scopes of the same level and name
+    pub fn scope_path(&self) -> Vec<ScopeId> {
+        self.scopes.iter().map(|s| s.id).collect()
+    }
+
     pub fn push_scope(&mut self, kind: ScopeKind) {
         self.push_named_scope("_", kind);
     }
@@ -126,6 +131,7 @@ impl Env {
 
 #[derive(Debug, Clone)]
 pub struct Scope {
+    pub id: ScopeId,
     pub kind: ScopeKind,
     pub name: String,
     pub bindings: UstrMap<BindingId>,
@@ -134,7 +140,11 @@ pub struct Scope {
 
 impl Scope {
     pub fn new(name: impl ToString, kind: ScopeKind) -> Self {
+        // Each scope that is entered gets a new id, so that scope ids are unique across checked modules
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
         Self {
+            id: ScopeId::from(NEXT_ID.fetch_add(1, Ordering::Relaxed)),
             kind,
             name: name.to_string(),
             bindings: UstrMap::default(),
@@ -0,0 +1,88 @@
+use super::{env::Env, CheckResult, CheckSess};
+use crate::{
+    error::diagnostic::{Diagnostic, Label},
+    hir::{self, attrs::Attr, const_value::ConstValue},
+};
+use ustr::Ustr;
+
+impl<'s> CheckSess<'s> {
+    pub(super) fn validate_exported_function_binding(
+        &self,
+        env: &Env,
+        attr: &Attr,
+        is_generic: bool,
+        track_caller_attr: Option<&Attr>,
+    ) -> CheckResult<()> {
+        if !env.scope_level().is_global() {
+            Err(Diagnostic::error()
+                .with_message("exported functions must be declared at the top-level")
+                .with_label(Label::primary(
+                    attr.span,
+                    "exported function is declared in local scope",
+                )))
+        } else if is_generic {
+            Err(Diagnostic::error()
+                .with_message("exported functions cannot be generic")
+                .with_label(Label::primary(attr.span, "exported function is generic")))
+        } else if let Some(track_caller_attr) = track_caller_attr {
+            // The caller's location is passed as a hidden parameter, which callers from outside of the program don't pass
+            Err(Diagnostic::error()
+                .with_message("exported functions cannot track their caller")
+                .with_label(Label::primary(
+                    track_caller_attr.span,
+                    "exported function is annotated with `@track_caller`",
+                ))
+                .with_label(Label::secondary(attr.span, "function is exported here")))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Collects a checked `@export` function, which is given external linkage under `link_name` by codegen.
+    // Two functions can't be exported under the same name, since the linker can't tell their symbols apart
+    pub(super) fn collect_exported_function(&mut self, node: &hir::Node, link_name: Ustr) -> CheckResult<()> {
+        let function_id = match node.as_const_value() {
+            Some(ConstValue::Function(f)) => f.id,
+            _ => unreachable!(),
+        };
+
+        let function = self.cache.functions.get(function_id).unwrap();
+
+        if let Some(&(other_id, _)) = self
+            .cache
+            .exported_functions
+            .iter()
+            .find(|(_, other_link_name)| *other_link_name == link_name)
+        {
+            let other = self.cache.functions.get(other_id).unwrap();
+
+            return Err(Diagnostic::error()
+                .with_message(format!("a function is already exported as `{}`", link_name))
+                .with_label(Label::primary(
+                    function.span,
+                    format!("`{}` exported again here", link_name),
+                ))
+                .with_label(Label::secondary(
+                    other.span,
+                    format!("`{}` first exported here", link_name),
+                ))
+                .with_note("give one of the functions a different name with `@link_name`"));
+        }
+
+        self.cache.exported_functions.push((function_id, link_name));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[test]
+    fn exported_function_cannot_track_caller() {
+        let result = check_source("@export @track_caller\nfn add(a: i32, b: i32) -> i32 = a + b\n\nfn main() = ()\n");
+
+        assert!(has_error(&result, "exported functions cannot track their caller"));
+    }
+}
@@ -0,0 +1,395 @@
+use super::{
+    env::{Env, ScopeKind},
+    top_level::CallerInfo,
+    CheckResult, CheckSess,
+};
+use crate::{
+    ast,
+    error::{
+        diagnostic::{Diagnostic, Label},
+        DiagnosticResult,
+    },
+    hir::{self, const_value::ConstValue},
+    infer::{display::DisplayType, normalize::Normalize},
+    span::Span,
+    types::{InferType, Type, TypeId},
+    workspace::{BindingInfoFlags, BindingInfoKind, ModuleId},
+};
+use ustr::ustr;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Piece {
+    Str(String),
+    Placeholder,
+}
+
+// `print("{} + {} = {}", a, b, c)` is desugared into a sequence of calls to the writer functions of `std.fmt`:
+// > write_int(a); write_str(" + "); write_int(b); write_str(" = "); write_int(c)
+// Since the format string is known at compile-time, it is checked against the arguments' types here,
+// and structs and tuples are written field by field.
+impl<'s> CheckSess<'s> {
+    pub(super) fn check_format_call(
+        &mut self,
+        env: &mut Env,
+        module_id: ModuleId,
+        args: &[hir::Node],
+        newline: bool,
+        span: Span,
+    ) -> CheckResult {
+        let (fmt_node, args) = args.split_first().unwrap();
+
+        let fmt = match fmt_node.as_const_value() {
+            Some(ConstValue::Str(fmt)) => *fmt,
+            _ => {
+                return Err(Diagnostic::error()
+                    .with_message("format string must be a string literal")
+                    .with_label(Label::primary(fmt_node.span(), "not a string literal")))
+            }
+        };
+
+        let pieces = parse_format_string(&fmt, fmt_node.span())?;
+        let placeholder_count = pieces.iter().filter(|piece| **piece == Piece::Placeholder).count();
+
+        if placeholder_count != args.len() {
+            let mut diagnostic = Diagnostic::error()
+                .with_message(format!(
+                    "format string has {} placeholder{}, but {} argument{} supplied",
+                    placeholder_count,
+                    if placeholder_count == 1 { "" } else { "s" },
+                    args.len(),
+                    if args.len() == 1 { " was" } else { "s were" },
+                ))
+                .with_label(Label::primary(
+                    fmt_node.span(),
+                    format!(
+                        "expected {} argument{}",
+                        placeholder_count,
+                        if placeholder_count == 1 { "" } else { "s" }
+                    ),
+                ));
+
+            for arg in args.iter().skip(placeholder_count) {
+                diagnostic = diagnostic.with_label(Label::secondary(arg.span(), "argument never used"));
+            }
+
+            return Err(diagnostic);
+        }
+
+        let mut writer = FormatWriter {
+            module_id,
+            statements: vec![],
+            pending: String::new(),
+            span,
+        };
+
+        env.push_scope(ScopeKind::Block);
+
+        // All arguments are evaluated before anything is written
+        let mut values = vec![];
+
+        for arg in args.iter() {
+            values.push(self.bind_format_arg(env, &mut writer, arg.clone())?);
+        }
+
+        let mut values = values.into_iter().zip(args.iter());
+
+        for piece in pieces {
+            match piece {
+                Piece::Str(s) => writer.pending.push_str(&s),
+                Piece::Placeholder => {
+                    let (value, arg) = values.next().unwrap();
+                    self.write_value(&mut writer, value, arg)?;
+                }
+            }
+        }
+
+        if newline {
+            writer.pending.push('\n');
+        }
+
+        writer.flush(self)?;
+
+        env.pop_scope();
+
+        let unit_type = self.tcx.common_types.unit;
+
+        if writer.statements.is_empty() {
+            writer.statements.push(hir::Node::Const(hir::Const {
+                value: ConstValue::Unit(()),
+                ty: unit_type,
+                span,
+            }));
+        }
+
+        Ok(hir::Node::Sequence(hir::Sequence {
+            statements: writer.statements,
+            ty: unit_type,
+            span,
+            is_scope: true,
+        }))
+    }
+
+    // An argument that isn't a place is bound to a local, so it is evaluated once, even if it is a struct
+    // or a tuple that is accessed once for each of its fields. Places aren't bound, so they aren't moved
+    fn bind_format_arg(
+        &mut self,
+        env: &mut Env,
+        writer: &mut FormatWriter,
+        arg: hir::Node,
+    ) -> DiagnosticResult<hir::Node> {
+        match &arg {
+            hir::Node::Const(_) if !matches!(arg.ty().normalize(&self.tcx), Type::Struct(_) | Type::Tuple(_)) => {
+                return Ok(arg)
+            }
+            _ if is_place(&arg) => return Ok(arg),
+            _ => (),
+        }
+
+        let ty = arg.ty();
+        let span = arg.span();
+        let name = self.generate_name("fmt_arg");
+
+        let (id, binding) = self.bind_name(
+            env,
+            name,
+            ast::Vis::Private,
+            ty,
+            Some(arg),
+            false,
+            BindingInfoKind::LetConst,
+            span,
+            BindingInfoFlags::NO_CONST_FOLD,
+        )?;
+
+        writer.statements.push(binding);
+
+        Ok(hir::Node::Id(hir::Id { id, ty, span }))
+    }
+
+    fn write_value(&mut self, writer: &mut FormatWriter, value: hir::Node, arg: &hir::Node) -> DiagnosticResult<()> {
+        let ty = value.ty().normalize(&self.tcx);
+        let span = value.span();
+
+        match &ty {
+            Type::Int(_) | Type::Infer(_, InferType::AnyInt) => {
+                let value = cast(value, self.tcx.common_types.i64);
+                writer.call(self, "write_int", value)
+            }
+            Type::Uint(_) => {
+                let value = cast(value, self.tcx.common_types.u64);
+                writer.call(self, "write_uint", value)
+            }
+            Type::Float(_) | Type::Infer(_, InferType::AnyFloat) => {
+                let value = cast(value, self.tcx.common_types.f64);
+                writer.call(self, "write_float", value)
+            }
+            Type::Bool => writer.call(self, "write_bool", value),
+            Type::Unit => {
+                writer.pending.push_str("()");
+                Ok(())
+            }
+            Type::Pointer(inner, _) if matches!(inner.as_ref(), Type::Str(_)) => {
+                let value = cast(value, self.tcx.common_types.str_pointer);
+                writer.call(self, "write_str", value)
+            }
+            Type::Pointer(..) if !ty.is_fat_pointer() => {
+                let value = cast(value, self.tcx.common_types.uint);
+                writer.call(self, "write_ptr", value)
+            }
+            Type::Tuple(elements) => {
+                writer.pending.push('(');
+
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        writer.pending.push_str(", ");
+                    }
+
+                    let element = hir::Node::MemberAccess(hir::MemberAccess {
+                        value: Box::new(value.clone()),
+                        member_name: ustr(&index.to_string()),
+                        member_index: index as _,
+                        ty: self.tcx.bound(element.clone(), span),
+                        span,
+                    });
+
+                    self.write_value(writer, element, arg)?;
+                }
+
+                // A tuple with a single element is written as `(x,)`, to distinguish it from a parenthesized value
+                if elements.len() == 1 {
+                    writer.pending.push(',');
+                }
+
+                writer.pending.push(')');
+
+                Ok(())
+            }
+            Type::Struct(struct_type) if !struct_type.is_union() => {
+                if !struct_type.name.is_empty() {
+                    writer.pending.push_str(&struct_type.name);
+                    writer.pending.push(' ');
+                }
+
+                if struct_type.fields.is_empty() {
+                    writer.pending.push_str("{}");
+                    return Ok(());
+                }
+
+                writer.pending.push_str("{ ");
+
+                for (index, field) in struct_type.fields.iter().enumerate() {
+                    if index > 0 {
+                        writer.pending.push_str(", ");
+                    }
+
+                    writer.pending.push_str(&field.name);
+                    writer.pending.push_str(": ");
+
+                    let field_value = hir::Node::MemberAccess(hir::MemberAccess {
+                        value: Box::new(value.clone()),
+                        member_name: field.name,
+                        member_index: index as _,
+                        ty: self.tcx.bound(field.ty.clone(), span),
+                        span,
+                    });
+
+                    self.write_value(writer, field_value, arg)?;
+                }
+
+                writer.pending.push_str(" }");
+
+                Ok(())
+            }
+            _ => {
+                let arg_ty = arg.ty().normalize(&self.tcx);
+
+                let mut diagnostic = Diagnostic::error()
+                    .with_message(format!("cannot format a value of type `{}`", ty.display(&self.tcx)))
+                    .with_label(Label::primary(arg.span(), "cannot be formatted"));
+
+                if arg_ty != ty {
+                    diagnostic = diagnostic.with_note(format!(
+                        "`{}` contains a value of type `{}`",
+                        arg_ty.display(&self.tcx),
+                        ty.display(&self.tcx)
+                    ));
+                }
+
+                Err(diagnostic
+                    .with_note("only integers, floats, bools, strings, pointers, structs and tuples can be formatted"))
+            }
+        }
+    }
+}
+
+struct FormatWriter {
+    // The module that defines the writer functions
+    module_id: ModuleId,
+    statements: Vec<hir::Node>,
+    // Literal text that wasn't written yet, which is merged into a single `write_str` call
+    pending: String,
+    span: Span,
+}
+
+impl FormatWriter {
+    fn flush(&mut self, sess: &mut CheckSess) -> DiagnosticResult<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let value = hir::Node::Const(hir::Const {
+            value: ConstValue::Str(ustr(&std::mem::take(&mut self.pending))),
+            ty: sess.tcx.common_types.str_pointer,
+            span: self.span,
+        });
+
+        self.call(sess, "write_str", value)
+    }
+
+    fn call(&mut self, sess: &mut CheckSess, function_name: &str, value: hir::Node) -> DiagnosticResult<()> {
+        // Literal text that precedes this value is written first
+        self.flush(sess)?;
+
+        let callee = sess.check_top_level_name(
+            ustr(function_name),
+            self.module_id,
+            CallerInfo {
+                module_id: self.module_id,
+                span: self.span,
+            },
+            true,
+        )?;
+
+        self.statements.push(hir::Node::Call(hir::Call {
+            callee: Box::new(callee),
+            args: vec![value],
+            ty: sess.tcx.common_types.unit,
+            span: self.span,
+        }));
+
+        Ok(())
+    }
+}
+
+fn is_place(node: &hir::Node) -> bool {
+    match node {
+        hir::Node::Id(_) => true,
+        hir::Node::MemberAccess(access) => is_place(&access.value),
+        hir::Node::Builtin(hir::Builtin::Deref(unary)) => is_place(&unary.value),
+        _ => false,
+    }
+}
+
+fn cast(value: hir::Node, ty: TypeId) -> hir::Node {
+    let span = value.span();
+
+    hir::Node::Cast(hir::Cast {
+        value: Box::new(value),
+        ty,
+        span,
+    })
+}
+
+// Splits a format string into literal text and `{}` placeholders. `{{` and `}}` are escaped braces
+fn parse_format_string(fmt: &str, span: Span) -> DiagnosticResult<Vec<Piece>> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut chars = fmt.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' => match chars.next() {
+                Some('{') => text.push('{'),
+                Some('}') => {
+                    if !text.is_empty() {
+                        pieces.push(Piece::Str(std::mem::take(&mut text)));
+                    }
+
+                    pieces.push(Piece::Placeholder);
+                }
+                _ => {
+                    return Err(Diagnostic::error()
+                        .with_message("invalid format string: expected `}` after `{`")
+                        .with_label(Label::primary(span, "invalid format string"))
+                        .with_note("if you intended to write a `{`, escape it with `{{`"))
+                }
+            },
+            '}' => match chars.next() {
+                Some('}') => text.push('}'),
+                _ => {
+                    return Err(Diagnostic::error()
+                        .with_message("invalid format string: unmatched `}` found")
+                        .with_label(Label::primary(span, "invalid format string"))
+                        .with_note("if you intended to write a `}`, escape it with `}}`"))
+                }
+            },
+            ch => text.push(ch),
+        }
+    }
+
+    if !text.is_empty() {
+        pieces.push(Piece::Str(text));
+    }
+
+    Ok(pieces)
+}
@@ -0,0 +1,789 @@
+use super::{
+    check_function, check_function_sig_has_type_annotations, check_optional_type_expr, check_type_expr,
+    env::{Env, ScopeKind},
+    pat::get_qualified_name,
+    Check, CheckResult, CheckSess, TrackCaller,
+};
+use crate::{
+    ast,
+    error::{
+        diagnostic::{Diagnostic, Label},
+        SyntaxError,
+    },
+    hir::{
+        self,
+        const_value::{ConstFunction, ConstValue},
+    },
+    infer::{
+        coerce::OrCoerceIntoTy,
+        display::{DisplayType, OrReportErr},
+        misc::IsConcrete,
+        normalize::{Concrete, Normalize},
+        unify::UnifyType,
+    },
+    span::Span,
+    types::{EnumType, ParamType, StructType, TraitType, Type, TypeId},
+    workspace::{BindingId, BindingInfoFlags, BindingInfoKind, ModuleId},
+};
+use ustr::{ustr, Ustr, UstrMap};
+
+// A generic function or type. A generic function's body is checked once where it is declared,
+// against opaque type parameters and placeholder const values, so its errors are reported even if it's never instantiated.
+// It is then checked again for every distinct list of generic arguments it is instantiated with.
+// Instances are ordinary functions and types, so later stages never see generic code.
+pub(super) struct GenericBinding {
+    pub module_id: ModuleId,
+    pub name: Ustr,
+    pub span: Span,
+    pub binding: ast::Binding,
+    pub params: Vec<GenericParam>,
+    pub track_caller: TrackCaller,
+    pub instances: Vec<GenericInstance>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct GenericParam {
+    pub name: Ustr,
+    pub kind: GenericParamKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum GenericParamKind {
+    Type(Option<TraitBound>),
+    Const(TypeId),
+}
+
+// A type parameter that is annotated with a trait, i.e: `T: Display`.
+// The type parameter's arguments must implement the trait
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TraitBound {
+    pub id: BindingId,
+    pub name: Ustr,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(super) enum GenericArg {
+    Type(Type),
+    Const(ConstValue),
+}
+
+pub(super) struct GenericInstance {
+    pub args: Vec<GenericArg>,
+    pub node: hir::Node,
+}
+
+impl<'s> CheckSess<'s> {
+    // Runs `f` in a context where a generic binding can be referred to without instantiating it,
+    // such as the callee of a call, or the accessed expression of an instantiation
+    pub(super) fn with_instantiation_context<T, F: FnOnce(&mut Self) -> T>(&mut self, f: F) -> T {
+        self.in_instantiation_context = true;
+        let result = f(self);
+        self.in_instantiation_context = false;
+        result
+    }
+
+    pub(super) fn is_checking_generic_body(&self) -> bool {
+        self.generic_body_functions.is_some()
+    }
+
+    pub(super) fn record_generic_body_function(&mut self, id: hir::FunctionId) {
+        if let Some(functions) = &mut self.generic_body_functions {
+            functions.push(id);
+        }
+    }
+
+    // Same as `with_clean_env`, for checking a generic body against opaque parameters.
+    // The functions declared by the body refer to opaque types, so they are removed once it's checked
+    fn with_generic_body_env<T, F: FnMut(&mut Self, Env) -> T>(&mut self, module_id: ModuleId, mut f: F) -> T {
+        self.with_clean_env(module_id, |sess, env| {
+            sess.generic_body_functions = Some(vec![]);
+
+            let result = f(sess, env);
+
+            for id in sess.generic_body_functions.take().unwrap() {
+                sess.cache.functions.remove(id);
+            }
+
+            result
+        })
+    }
+
+    pub(super) fn generic_binding_id(&self, node: &hir::Node) -> Option<BindingId> {
+        match node {
+            hir::Node::Id(id) if self.generic_bindings.contains_key(&id.id) => Some(id.id),
+            _ => None,
+        }
+    }
+
+    // Generic bindings don't have a value of their own, so they must be instantiated before they are used
+    pub(super) fn validate_generic_binding_use(&self, node: hir::Node) -> CheckResult {
+        match self.generic_binding_id(&node) {
+            Some(id) if !self.in_instantiation_context => {
+                let generic = self.generic_bindings.get(&id).unwrap();
+
+                Err(Diagnostic::error()
+                    .with_message(format!(
+                        "generic {} `{}` must be instantiated before it is used",
+                        generic.binding.kind, generic.name
+                    ))
+                    .with_label(Label::primary(node.span(), "missing generic arguments"))
+                    .with_label(Label::secondary(
+                        generic.span,
+                        format!("`{}` is defined here", generic.name),
+                    ))
+                    .with_note(format!(
+                        "supply its generic arguments, i.e: `{}[{}]`",
+                        generic.name,
+                        generic
+                            .params
+                            .iter()
+                            .map(|p| p.name.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    )))
+            }
+            _ => Ok(node),
+        }
+    }
+}
+
+pub(super) fn check_generic_binding(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    binding: &ast::Binding,
+    name: Ustr,
+    span: Span,
+    track_caller: TrackCaller,
+) -> CheckResult {
+    if !env.scope_level().is_global() {
+        return Err(Diagnostic::error()
+            .with_message(format!(
+                "generic {} `{}` must be declared at the top level",
+                binding.kind, name
+            ))
+            .with_label(Label::primary(span, "declared in a local scope")));
+    }
+
+    let params = check_generic_params(sess, env, binding.kind.generic_params())?;
+
+    let unit_type = sess.tcx.common_types.unit;
+
+    let (id, bound_node) = sess.bind_name(
+        env,
+        name,
+        binding.vis,
+        unit_type,
+        Some(hir::Node::Const(hir::Const {
+            value: ConstValue::Unit(()),
+            ty: unit_type,
+            span,
+        })),
+        false,
+        match &binding.kind {
+            ast::BindingKind::Type { .. } => BindingInfoKind::Type,
+            _ => BindingInfoKind::Function,
+        },
+        span,
+        BindingInfoFlags::IS_USER_DEFINED | BindingInfoFlags::NO_CONST_FOLD,
+    )?;
+
+    sess.generic_bindings.insert(
+        id,
+        GenericBinding {
+            module_id: env.module_id(),
+            name,
+            span,
+            binding: binding.clone(),
+            params,
+            track_caller,
+            instances: vec![],
+        },
+    );
+
+    if let ast::BindingKind::Function { .. } = &binding.kind {
+        check_generic_body(sess, id)?;
+    }
+
+    Ok(bound_node)
+}
+
+// Checks a generic function's body with its type parameters bound to opaque types, and its const parameters bound to placeholder values.
+// An opaque type only supports the functions of its trait bound, so the body must be valid for any of the generic's arguments
+fn check_generic_body(sess: &mut CheckSess, id: BindingId) -> CheckResult<()> {
+    let generic = sess.generic_bindings.get(&id).unwrap();
+
+    let (module_id, name, binding, params, track_caller) = (
+        generic.module_id,
+        generic.name,
+        generic.binding.clone(),
+        generic.params.clone(),
+        generic.track_caller,
+    );
+
+    let args: Vec<GenericArg> = params
+        .iter()
+        .enumerate()
+        .map(|(index, param)| match param.kind {
+            GenericParamKind::Type(bound) => GenericArg::Type(Type::Param(ParamType {
+                name: param.name,
+                generic_id: id,
+                index,
+                bound: bound.map(|bound| TraitType {
+                    name: bound.name,
+                    id: bound.id,
+                }),
+            })),
+            GenericParamKind::Const(ty) => GenericArg::Const(if ty.normalize(&sess.tcx).is_bool() {
+                ConstValue::Bool(false)
+            } else {
+                // Not zero, so that dividing by the parameter isn't a compile-time error
+                ConstValue::Int(1)
+            }),
+        })
+        .collect();
+
+    // Recursive calls of the body instantiate it with its own parameters, which is a cycle like any other
+    sess.instantiation_stack.push((id, args.clone()));
+
+    let result = sess.with_generic_body_env(module_id, |sess, mut env| {
+        check_instance(sess, &mut env, &binding, &params, &args, name, track_caller)
+    });
+
+    sess.instantiation_stack.pop();
+
+    result.map(|_| ())
+}
+
+fn check_generic_params(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    generic_params: &[ast::GenericParam],
+) -> CheckResult<Vec<GenericParam>> {
+    let mut defined_params = UstrMap::<Span>::default();
+    let mut params = vec![];
+
+    for param in generic_params.iter() {
+        let (name, span) = (param.name.name, param.name.span);
+
+        if let Some(already_defined_span) = defined_params.insert(name, span) {
+            return Err(SyntaxError::duplicate_binding(name, span, already_defined_span));
+        }
+
+        let kind = match &param.type_expr {
+            Some(type_expr) => {
+                let ty = check_type_expr(type_expr, sess, env)?;
+                let param_type = ty.normalize(&sess.tcx);
+
+                if let Type::Trait(trait_type) = &param_type {
+                    params.push(GenericParam {
+                        name,
+                        kind: GenericParamKind::Type(Some(TraitBound {
+                            id: trait_type.id,
+                            name: trait_type.name,
+                            span: type_expr.span(),
+                        })),
+                        span: param.span,
+                    });
+
+                    continue;
+                }
+
+                if !param_type.is_any_integer() && !param_type.is_bool() {
+                    return Err(Diagnostic::error()
+                        .with_message(format!(
+                            "const parameter `{}` cannot be of type `{}`",
+                            name,
+                            param_type.display(&sess.tcx)
+                        ))
+                        .with_label(Label::primary(type_expr.span(), "invalid const parameter type"))
+                        .with_note("const parameters must be of an integer or boolean type, and type parameters can only be bound by traits"));
+                }
+
+                GenericParamKind::Const(ty)
+            }
+            None => GenericParamKind::Type(None),
+        };
+
+        params.push(GenericParam {
+            name,
+            kind,
+            span: param.span,
+        });
+    }
+
+    Ok(params)
+}
+
+impl Check for ast::Instantiation {
+    fn check(&self, sess: &mut CheckSess, env: &mut Env, _expected_type: Option<TypeId>) -> CheckResult {
+        let node = sess.with_instantiation_context(|sess| self.expr.check(sess, env, None))?;
+
+        match sess.generic_binding_id(&node) {
+            Some(id) => instantiate_with_args(sess, env, id, &self.args, self.span),
+            None => Err(Diagnostic::error()
+                .with_message(format!(
+                    "expected a generic function or type, found a value of type `{}`",
+                    node.ty().display(&sess.tcx)
+                ))
+                .with_label(Label::primary(self.expr.span(), "not generic"))),
+        }
+    }
+}
+
+// Instantiates a generic binding with explicitly supplied arguments, i.e: `Foo[i32, 4]`
+pub(super) fn instantiate_with_args(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    id: BindingId,
+    args: &[ast::Ast],
+    span: Span,
+) -> CheckResult {
+    let (name, def_span, params) = {
+        let generic = sess.generic_bindings.get(&id).unwrap();
+        (generic.name, generic.span, generic.params.clone())
+    };
+
+    if args.len() != params.len() {
+        let expected = params.len();
+        let actual = args.len();
+
+        return Err(Diagnostic::error()
+            .with_message(format!(
+                "`{}` expects {} generic argument{}, but {} {} supplied",
+                name,
+                expected,
+                if expected > 1 { "s" } else { "" },
+                actual,
+                if actual > 1 { "were" } else { "was" },
+            ))
+            .with_label(Label::primary(
+                span,
+                format!("expected {} generic arguments, got {}", expected, actual),
+            ))
+            .with_label(Label::secondary(def_span, format!("`{}` is defined here", name))));
+    }
+
+    let mut generic_args = vec![];
+
+    for (param, arg) in params.iter().zip(args.iter()) {
+        let generic_arg = match param.kind {
+            GenericParamKind::Type(_) => {
+                let ty = check_type_expr(arg, sess, env)?;
+                GenericArg::Type(ty.concrete(&sess.tcx))
+            }
+            GenericParamKind::Const(param_type) => {
+                let mut node = arg.check(sess, env, Some(param_type))?;
+
+                node.ty()
+                    .unify(&param_type, &mut sess.tcx)
+                    .or_coerce_into_ty(&mut node, &param_type, &mut sess.tcx, sess.target_metrics.word_size)
+                    .or_report_err(&sess.tcx, &param_type, Some(param.span), &node.ty(), arg.span())?;
+
+                match node.into_const_value() {
+                    Some(value) => GenericArg::Const(value),
+                    None => {
+                        return Err(Diagnostic::error()
+                            .with_message(format!(
+                                "the value of const parameter `{}` must be known at compile-time",
+                                param.name
+                            ))
+                            .with_label(Label::primary(arg.span(), "not a compile-time known value")))
+                    }
+                }
+            }
+        };
+
+        generic_args.push(generic_arg);
+    }
+
+    instantiate_generic_binding(sess, id, generic_args, span)
+}
+
+// Infers the generic arguments of a call to a generic function from the call's arguments, i.e: `max(1, 2)`.
+// Only type parameters can be inferred, const parameters must always be supplied explicitly.
+pub(super) fn infer_generic_args(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    id: BindingId,
+    call: &ast::Call,
+) -> CheckResult<Vec<GenericArg>> {
+    let generic = sess.generic_bindings.get(&id).unwrap();
+    let (module_id, name, def_span, params) = (generic.module_id, generic.name, generic.span, generic.params.clone());
+
+    let sig = match &generic.binding.kind {
+        ast::BindingKind::Function { sig, .. } => sig.clone(),
+        _ => {
+            return Err(Diagnostic::error()
+                .with_message(format!(
+                    "generic type `{}` must be instantiated before it is used",
+                    name
+                ))
+                .with_label(Label::primary(call.callee.span(), "missing generic arguments"))
+                .with_label(Label::secondary(def_span, format!("`{}` is defined here", name))))
+        }
+    };
+
+    if let Some(param) = params.iter().find(|p| matches!(p.kind, GenericParamKind::Const(_))) {
+        return Err(Diagnostic::error()
+            .with_message(format!("cannot infer the value of const parameter `{}`", param.name))
+            .with_label(Label::primary(call.callee.span(), "cannot infer generic arguments"))
+            .with_label(Label::secondary(param.span, "const parameter declared here"))
+            .with_note(format!(
+                "supply the generic arguments explicitly, i.e: `{}[...](...)`",
+                name
+            )));
+    }
+
+    // Each type parameter is bound to a type variable, which is resolved by unifying
+    // the function's parameter types with the types of the call's arguments
+    let type_vars: Vec<TypeId> = params.iter().map(|param| sess.tcx.var(param.span)).collect();
+
+    let param_types = sess.with_env(module_id, |sess, mut env| {
+        env.push_scope(ScopeKind::Block);
+
+        let result = bind_generic_params(sess, &mut env, &params, |index| {
+            GenericArg::Type(type_vars[index].as_kind())
+        })
+        .and_then(|_| {
+            sig.params
+                .iter()
+                .map(|param| check_optional_type_expr(&param.type_expr, sess, &mut env, param.pat.span()))
+                .collect::<CheckResult<Vec<TypeId>>>()
+        });
+
+        env.pop_scope();
+
+        result
+    })?;
+
+    for (arg, param_type) in call.args.iter().zip(param_types.iter()) {
+        if arg.spread {
+            continue;
+        }
+
+        let node = arg.value.check(sess, env, Some(*param_type))?;
+
+        // A mismatch is reported when the call is checked against the instantiated function
+        let _ = node.ty().unify(param_type, &mut sess.tcx);
+    }
+
+    params
+        .iter()
+        .zip(type_vars.iter())
+        .map(|(param, type_var)| {
+            let ty = type_var.concrete(&sess.tcx);
+
+            match ty.is_concrete(&sess.tcx) {
+                Ok(_) => Ok(GenericArg::Type(ty)),
+                Err(_) => Err(Diagnostic::error()
+                    .with_message(format!("cannot infer the type of type parameter `{}`", param.name))
+                    .with_label(Label::primary(call.callee.span(), "cannot infer generic arguments"))
+                    .with_label(Label::secondary(param.span, "type parameter declared here"))
+                    .with_note(format!(
+                        "supply the generic arguments explicitly, i.e: `{}[...](...)`",
+                        name
+                    ))),
+            }
+        })
+        .collect()
+}
+
+pub(super) fn instantiate_generic_binding(
+    sess: &mut CheckSess,
+    id: BindingId,
+    args: Vec<GenericArg>,
+    span: Span,
+) -> CheckResult {
+    let generic = sess.generic_bindings.get(&id).unwrap();
+
+    if let Some(instance) = generic.instances.iter().find(|instance| instance.args == args) {
+        return Ok(with_span(instance.node.clone(), span));
+    }
+
+    let params = generic.params.clone();
+    check_trait_bounds(sess, &params, &args, span)?;
+
+    let generic = sess.generic_bindings.get(&id).unwrap();
+
+    let instance_name = ustr(&format!(
+        "{}[{}]",
+        generic.name,
+        args.iter()
+            .map(|arg| match arg {
+                GenericArg::Type(ty) => ty.display(&sess.tcx),
+                GenericArg::Const(value) => value.display(&sess.tcx),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    ));
+
+    if sess
+        .instantiation_stack
+        .iter()
+        .any(|(other_id, other_args)| *other_id == id && *other_args == args)
+    {
+        return Err(Diagnostic::error()
+            .with_message(format!("cycle detected while instantiating `{}`", instance_name))
+            .with_label(Label::primary(span, format!("`{}` requires itself", instance_name)))
+            .with_label(Label::secondary(
+                generic.span,
+                format!("`{}` is defined here", generic.name),
+            )));
+    }
+
+    let module_id = generic.module_id;
+    let binding = generic.binding.clone();
+    let track_caller = generic.track_caller;
+
+    sess.instantiation_stack.push((id, args.clone()));
+
+    // Instances requested by a generic body may have opaque arguments, and are only used to check that body
+    let is_in_generic_body = sess.is_checking_generic_body();
+
+    // The instance is checked as if it was declared in the generic binding's module
+    let check = |sess: &mut CheckSess, mut env: Env| {
+        check_instance(sess, &mut env, &binding, &params, &args, instance_name, track_caller)
+    };
+
+    let result = if is_in_generic_body {
+        sess.with_generic_body_env(module_id, check)
+    } else {
+        sess.with_clean_env(module_id, check)
+    };
+
+    sess.instantiation_stack.pop();
+
+    let node = result.map_err(|diagnostic| {
+        diagnostic.with_label(Label::secondary(
+            span,
+            format!("in the instantiation of `{}` requested here", instance_name),
+        ))
+    })?;
+
+    if !is_in_generic_body {
+        sess.generic_bindings
+            .get_mut(&id)
+            .unwrap()
+            .instances
+            .push(GenericInstance {
+                args,
+                node: node.clone(),
+            });
+    }
+
+    Ok(with_span(node, span))
+}
+
+fn check_instance(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    binding: &ast::Binding,
+    params: &[GenericParam],
+    args: &[GenericArg],
+    instance_name: Ustr,
+    track_caller: TrackCaller,
+) -> CheckResult {
+    env.push_named_scope(instance_name, ScopeKind::Block);
+
+    bind_generic_params(sess, env, params, |index| args[index].clone())?;
+
+    let node = match &binding.kind {
+        ast::BindingKind::Function { name, sig, body, .. } => {
+            check_function_sig_has_type_annotations(sess, sig)?;
+
+            let node = check_function(sess, env, sig, body, name.span, None, track_caller)?;
+
+            let (function_id, ty) = match &node {
+                hir::Node::Const(hir::Const {
+                    value: ConstValue::Function(f),
+                    ty,
+                    ..
+                }) => (f.id, *ty),
+                _ => unreachable!(),
+            };
+
+            let module_name = env.module_info().qualified_name;
+
+            let function = sess.cache.functions.get_mut(function_id).unwrap();
+            function.name = instance_name;
+            function.qualified_name = get_qualified_name(module_name, instance_name);
+
+            hir::Node::Const(hir::Const {
+                value: ConstValue::Function(ConstFunction {
+                    id: function_id,
+                    name: instance_name,
+                }),
+                ty,
+                span: name.span,
+            })
+        }
+        ast::BindingKind::Type { name, type_expr, .. } => {
+            let type_node = type_expr.check(sess, env, Some(sess.tcx.common_types.anytype))?;
+
+            let ty = match type_node.as_const_value() {
+                Some(ConstValue::Type(ty)) => *ty,
+                _ => {
+                    return Err(Diagnostic::error()
+                        .with_message(format!(
+                            "expected a type, got a value of type `{}`",
+                            type_node.ty().display(&sess.tcx)
+                        ))
+                        .with_label(Label::primary(type_expr.span(), "expected a type")))
+                }
+            };
+
+            let attrs = sess.check_attrs(&binding.attrs, env)?;
+            sess.apply_copy_attr(&attrs, ty)?;
+
+            // Name the instance after its generic arguments, so that instances can be told apart in diagnostics
+            let ty = match ty.normalize(&sess.tcx) {
+                Type::Struct(struct_type) => sess.tcx.bound(
+                    Type::Struct(StructType {
+                        name: instance_name,
+                        ..struct_type
+                    }),
+                    name.span,
+                ),
+                Type::Enum(enum_type) => sess.tcx.bound(
+                    Type::Enum(EnumType {
+                        name: instance_name,
+                        ..enum_type
+                    }),
+                    name.span,
+                ),
+                _ => ty,
+            };
+
+            hir::Node::Const(hir::Const {
+                value: ConstValue::Type(ty),
+                ty: sess.tcx.bound(ty.as_kind().create_type(), name.span),
+                span: name.span,
+            })
+        }
+        ast::BindingKind::Let { .. }
+        | ast::BindingKind::ExternFunction { .. }
+        | ast::BindingKind::ExternVariable { .. } => {
+            unreachable!()
+        }
+    };
+
+    env.pop_scope();
+
+    Ok(node)
+}
+
+// Checks that the arguments of bound type parameters implement their traits
+fn check_trait_bounds(
+    sess: &mut CheckSess,
+    params: &[GenericParam],
+    args: &[GenericArg],
+    span: Span,
+) -> CheckResult<()> {
+    for (param, arg) in params.iter().zip(args.iter()) {
+        let (bound, ty) = match (param.kind, arg) {
+            (GenericParamKind::Type(Some(bound)), GenericArg::Type(ty)) => (bound, ty),
+            _ => continue,
+        };
+
+        if !sess.implements_trait(bound.id, ty)? {
+            let ty = ty.display(&sess.tcx);
+
+            return Err(Diagnostic::error()
+                .with_message(format!("`{}` doesn't implement trait `{}`", ty, bound.name))
+                .with_label(Label::primary(
+                    span,
+                    format!("`{}` is required to implement `{}`", ty, bound.name),
+                ))
+                .with_label(Label::secondary(
+                    bound.span,
+                    format!("required by this bound on `{}`", param.name),
+                ))
+                .with_note(format!("implement it with `impl {} for {} {{ ... }}`", bound.name, ty)));
+        }
+    }
+
+    Ok(())
+}
+
+// Binds each generic parameter to its argument, in the current scope
+fn bind_generic_params(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    params: &[GenericParam],
+    get_arg: impl Fn(usize) -> GenericArg,
+) -> CheckResult<()> {
+    for (index, param) in params.iter().enumerate() {
+        let (ty, value, kind) = match (get_arg(index), param.kind) {
+            (GenericArg::Type(arg), _) => {
+                let arg_type = sess.tcx.bound(arg.clone(), param.span);
+                let ty = sess.tcx.bound(arg.create_type(), param.span);
+                (ty, ConstValue::Type(arg_type), BindingInfoKind::Type)
+            }
+            (GenericArg::Const(value), GenericParamKind::Const(ty)) => (ty, value, BindingInfoKind::LetConst),
+            (GenericArg::Const(_), GenericParamKind::Type(_)) => unreachable!(),
+        };
+
+        sess.bind_name(
+            env,
+            param.name,
+            ast::Vis::Private,
+            ty,
+            Some(hir::Node::Const(hir::Const {
+                value,
+                ty,
+                span: param.span,
+            })),
+            false,
+            kind,
+            param.span,
+            BindingInfoFlags::empty(),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn with_span(node: hir::Node, span: Span) -> hir::Node {
+    match node {
+        hir::Node::Const(constant) => hir::Node::Const(hir::Const { span, ..constant }),
+        node => node,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[test]
+    fn uninstantiated_generic_body_is_checked() {
+        // `first` is never instantiated, and a `T` isn't an `int` for any `T`
+        let result = check_source("fn main() = ()\n\nfn first[T](a: T, b: T) -> int = a\n");
+
+        assert!(has_error(&result, "mismatched types - expected int, found T"));
+    }
+
+    const DESCRIBE: &str = "type Describe = trait {\n    fn describe(self: *Self) -> int\n}\n\nimpl Describe for int {\n    fn describe(self: *Self) -> int = 1\n}\n\n";
+
+    #[test]
+    fn bound_functions_are_methods_of_type_param() {
+        let result = check_source(&format!(
+            "{}fn main() = {{\n    let _ = twice(41)\n}}\n\nfn twice[T: Describe](value: T) -> int = value.describe() + value.describe()\n",
+            DESCRIBE
+        ));
+
+        assert!(errors(&result).is_empty());
+    }
+
+    #[test]
+    fn method_outside_of_bound() {
+        // `size` isn't a function of `Describe`, even though no instance of `size_of_value` is ever checked
+        let result = check_source(&format!(
+            "{}fn main() = ()\n\nfn size_of_value[T: Describe](value: T) -> int = value.size()\n",
+            DESCRIBE
+        ));
+
+        assert!(has_error(&result, "type parameter `T` has no member `size`"));
+    }
+}
@@ -0,0 +1,920 @@
+use super::{
+    env::{Env, ScopeKind},
+    pat::get_qualified_name,
+    top_level::CallerInfo,
+    Check, CheckResult, CheckSess,
+};
+use crate::{
+    ast,
+    error::{
+        diagnostic::{Diagnostic, Label},
+        SyntaxError,
+    },
+    hir::{
+        self,
+        const_value::{ConstFunction, ConstValue},
+    },
+    infer::{display::DisplayType, normalize::Normalize, substitute::substitute_node, type_ctx::TypeCtx},
+    span::Span,
+    types::{EnumType, ParamType, StructType, Type, TypeId},
+    workspace::{BindingId, BindingInfoFlags},
+};
+use ustr::{Ustr, UstrMap};
+
+impl<'s> CheckSess<'s> {
+    // Checks all of the associated functions declared in an impl block
+    pub(super) fn check_impl(&mut self, module: &ast::Module, impl_index: usize) -> CheckResult<()> {
+        let ty = self.check_impl_type(module, impl_index)?;
+
+        for function_index in 0..module.impls[impl_index].bindings.len() {
+            if self
+                .queued_modules
+                .get_mut(&module.id)
+                .unwrap()
+                .queued_associated_functions
+                .insert((impl_index, function_index))
+            {
+                self.check_associated_function(module, impl_index, function_index, ty)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Checks all of the associated functions of a struct type, in all of its impl blocks
+    pub(super) fn check_associated_functions_of(&mut self, struct_type: &StructType) -> CheckResult<()> {
+        let type_id = match struct_type.id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        let module_id = self.workspace.binding_infos.get(type_id).unwrap().module_id;
+        let modules = self.modules;
+        let module = modules.iter().find(|m| m.id == module_id).unwrap();
+
+        for impl_index in 0..module.impls.len() {
+            let ty = self.check_impl_type(module, impl_index)?;
+
+            if struct_type_id(&self.tcx, ty) == Some(type_id) {
+                self.check_impl(module, impl_index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Finds the associated function `name` of a named struct type, and checks it if it wasn't checked yet.
+    // Impl blocks can only be declared in their type's module, so only that module is searched.
+    pub(super) fn find_associated_function(
+        &mut self,
+        env: &Env,
+        struct_type: &StructType,
+        name: Ustr,
+        caller_info: CallerInfo,
+    ) -> CheckResult<Option<hir::Node>> {
+        let type_id = match struct_type.id {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        if let Some(&id) = self
+            .cache
+            .associated_functions
+            .get(&type_id)
+            .and_then(|functions| functions.get(&name))
+        {
+            return self.associated_function_node(id, caller_info).map(Some);
+        }
+
+        let module_id = self.workspace.binding_infos.get(type_id).unwrap().module_id;
+        let modules = self.modules;
+        let module = modules.iter().find(|m| m.id == module_id).unwrap();
+
+        for (impl_index, imp) in module.impls.iter().enumerate() {
+            let function_index = match imp.find_function(name) {
+                Some((index, _)) => index,
+                None => continue,
+            };
+
+            if imp.trait_name.is_some() {
+                continue;
+            }
+
+            let ty = self.check_impl_type(module, impl_index)?;
+
+            if struct_type_id(&self.tcx, ty) != Some(type_id) {
+                continue;
+            }
+
+            return self
+                .impl_function_node(env, module, impl_index, function_index, name, ty, caller_info)
+                .map(Some);
+        }
+
+        Ok(None)
+    }
+
+    // Finds the function `name` of a trait implemented for `ty`, and checks it if it wasn't checked yet.
+    // Trait impls can be declared in either the trait's module or the type's module, so all modules are searched,
+    // including modules that weren't checked yet.
+    pub(super) fn find_trait_function(
+        &mut self,
+        env: &Env,
+        ty: &Type,
+        name: Ustr,
+        caller_info: CallerInfo,
+    ) -> CheckResult<Option<hir::Node>> {
+        let modules = self.modules;
+        let mut candidates = vec![];
+
+        for module in modules.iter() {
+            self.queue_module(module);
+
+            for (impl_index, imp) in module.impls.iter().enumerate() {
+                if imp.trait_name.is_none() {
+                    continue;
+                }
+
+                let function_index = match imp.find_function(name) {
+                    Some((index, _)) => index,
+                    None => continue,
+                };
+
+                let impl_type = self.check_impl_type(module, impl_index)?;
+
+                if is_same_type(&impl_type.normalize(&self.tcx), ty) {
+                    candidates.push((module, impl_index, function_index, impl_type));
+                }
+            }
+        }
+
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [(module, impl_index, function_index, impl_type)] => self
+                .impl_function_node(env, module, *impl_index, *function_index, name, *impl_type, caller_info)
+                .map(Some),
+            candidates => {
+                let mut diagnostic = Diagnostic::error()
+                    .with_message(format!(
+                        "function `{}` is implemented by multiple traits for `{}`",
+                        name,
+                        ty.display(&self.tcx)
+                    ))
+                    .with_label(Label::primary(caller_info.span, "ambiguous function"));
+
+                for (module, impl_index, ..) in candidates.iter() {
+                    let trait_name = module.impls[*impl_index].trait_name.as_ref().unwrap();
+
+                    diagnostic = diagnostic.with_label(Label::secondary(
+                        trait_name.span,
+                        format!("`{}` is implemented by `{}` here", name, trait_name.name),
+                    ));
+                }
+
+                Err(diagnostic)
+            }
+        }
+    }
+
+    // Returns whether `ty` implements the trait `trait_id`, checking the impl headers of all modules
+    pub(super) fn implements_trait(&mut self, trait_id: BindingId, ty: &Type) -> CheckResult<bool> {
+        // A type parameter implements the trait it is bound by
+        if let Type::Param(param_type) = ty {
+            return Ok(matches!(&param_type.bound, Some(bound) if bound.id == trait_id));
+        }
+
+        let modules = self.modules;
+
+        for module in modules.iter() {
+            self.queue_module(module);
+
+            for (impl_index, imp) in module.impls.iter().enumerate() {
+                if imp.trait_name.is_none() {
+                    continue;
+                }
+
+                let impl_type = self.check_impl_type(module, impl_index)?;
+
+                let impl_trait_id = *self
+                    .queued_modules
+                    .get(&module.id)
+                    .unwrap()
+                    .impl_traits
+                    .get(&impl_index)
+                    .unwrap();
+
+                if impl_trait_id == trait_id && is_same_type(&impl_type.normalize(&self.tcx), ty) {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    // Finds the method `access.member` of the accessed value.
+    // A struct's fields come first, then its associated functions, and then the functions of the traits it implements
+    pub(super) fn find_method(
+        &mut self,
+        env: &Env,
+        node: &hir::Node,
+        access: &ast::MemberAccess,
+    ) -> CheckResult<Option<hir::Node>> {
+        let receiver_type = node.ty().normalize(&self.tcx);
+
+        // Modules and types don't have methods, their members are accessed directly
+        if matches!(receiver_type, Type::Module(_) | Type::Type(_) | Type::AnyType) {
+            return Ok(None);
+        }
+
+        let caller_info = CallerInfo {
+            module_id: env.module_id(),
+            span: access.member_span,
+        };
+
+        if let Type::Struct(struct_type) = receiver_type.maybe_deref_once() {
+            if struct_type.field(access.member).is_some() {
+                return Ok(None);
+            }
+
+            self.record_member_completion_site(env, node.ty(), access.member_span);
+
+            match self.find_associated_function(env, &struct_type, access.member, caller_info)? {
+                Some(function) if self.is_method(&function) => return Ok(Some(function)),
+                Some(_) => return Err(not_a_method_error(&struct_type.name, access)),
+                None => (),
+            }
+        }
+
+        if let Type::Param(param_type) = receiver_type.maybe_deref_once() {
+            return match self.find_type_param_method(&param_type, access)? {
+                Some(function) if self.is_method(&function) => Ok(Some(function)),
+                Some(_) => Err(not_a_method_error(&param_type.name, access)),
+                None => Ok(None),
+            };
+        }
+
+        let function = match self.find_trait_function(env, &receiver_type, access.member, caller_info)? {
+            Some(function) => Some(function),
+            None if receiver_type.is_pointer() => {
+                self.find_trait_function(env, &receiver_type.maybe_deref_once(), access.member, caller_info)?
+            }
+            None => None,
+        };
+
+        match function {
+            Some(function) if self.is_method(&function) => Ok(Some(function)),
+            Some(_) => Err(not_a_method_error(
+                &receiver_type.maybe_deref_once().display(&self.tcx),
+                access,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    // The methods of a type parameter are the functions of the trait it is bound by.
+    // They are checked against the trait's declaration, where `Self` is the type parameter
+    fn find_type_param_method(
+        &mut self,
+        param_type: &ParamType,
+        access: &ast::MemberAccess,
+    ) -> CheckResult<Option<hir::Node>> {
+        let trait_ = match &param_type.bound {
+            Some(bound) => self.traits.get(&bound.id).unwrap().clone(),
+            None => return Ok(None),
+        };
+
+        let sig = match trait_.find_function(access.member) {
+            Some(sig) => sig,
+            None => return Ok(None),
+        };
+
+        let self_type = self.tcx.bound(Type::Param(param_type.clone()), access.member_span);
+
+        let sig_node = self.with_clean_env(trait_.module_id, |sess, mut env| {
+            sess.self_types.push(self_type);
+            let node = sig.check(sess, &mut env, None);
+            sess.self_types.pop();
+            node
+        })?;
+
+        let ty = sig_node.ty().normalize(&self.tcx).into_type();
+        let ty = self.tcx.bound(ty, access.member_span);
+
+        // Generic bodies are never lowered, their instances call the trait's implementations instead
+        Ok(Some(hir::Node::Const(hir::Const {
+            value: ConstValue::Unit(()),
+            ty,
+            span: access.member_span,
+        })))
+    }
+
+    // A type parameter has no fields, and its only methods are the functions of its trait bound
+    pub(super) fn type_param_member_error(&self, param_type: &ParamType, access: &ast::MemberAccess) -> Diagnostic {
+        let diagnostic = Diagnostic::error().with_message(format!(
+            "type parameter `{}` has no member `{}`",
+            param_type.name, access.member
+        ));
+
+        match &param_type.bound {
+            Some(bound) => {
+                let trait_ = self.traits.get(&bound.id).unwrap();
+
+                let names = trait_
+                    .functions
+                    .iter()
+                    .map(|sig| format!("`{}`", sig.name.unwrap()))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                diagnostic
+                    .with_label(Label::primary(
+                        access.member_span,
+                        format!("not a function of `{}`", trait_.name),
+                    ))
+                    .with_label(Label::secondary(
+                        trait_.span,
+                        format!("`{}` is declared here", trait_.name),
+                    ))
+                    .with_note(format!(
+                        "`{}` is bound by `{}`, so it can only be used through {}",
+                        param_type.name, trait_.name, names
+                    ))
+            }
+            None => diagnostic
+                .with_label(Label::primary(access.member_span, "unknown member"))
+                .with_note(format!(
+                    "`{}` isn't bound by a trait, so it has no methods. bind it with `{}: Trait`",
+                    param_type.name, param_type.name
+                )),
+        }
+    }
+
+    // Accessing an associated function through a value is only valid when it is called as a method
+    pub(super) fn validate_associated_function_value_access(
+        &mut self,
+        env: &Env,
+        struct_type: &StructType,
+        access: &ast::MemberAccess,
+    ) -> CheckResult<()> {
+        let caller_info = CallerInfo {
+            module_id: env.module_id(),
+            span: access.member_span,
+        };
+
+        match self.find_associated_function(env, struct_type, access.member, caller_info)? {
+            Some(function) if self.is_method(&function) => Err(Diagnostic::error()
+                .with_message(format!("method `{}` must be called", access.member))
+                .with_label(Label::primary(access.member_span, "not called"))
+                .with_note(format!(
+                    "to refer to the function itself, use `{}.{}`",
+                    struct_type.name, access.member
+                ))),
+            Some(_) => Err(not_a_method_error(&struct_type.name, access)),
+            None => Ok(()),
+        }
+    }
+
+    // A method is an associated function which takes `self` as its first parameter
+    pub(super) fn is_method(&self, function: &hir::Node) -> bool {
+        match function.ty().normalize(&self.tcx) {
+            Type::Function(function_type) => function_type.self_param().is_some(),
+            _ => false,
+        }
+    }
+
+    // Adapts the receiver of a method call to the method's `self` parameter.
+    // The receiver is referenced if the method expects a pointer, or dereferenced if it expects a value.
+    pub(super) fn build_method_receiver(
+        &mut self,
+        env: &mut Env,
+        receiver: hir::Node,
+        self_type: &Type,
+    ) -> CheckResult {
+        let receiver_type = receiver.ty().normalize(&self.tcx);
+        let span = receiver.span();
+
+        match (self_type, &receiver_type) {
+            (Type::Pointer(_, is_mutable), receiver_type) if !receiver_type.is_pointer() => {
+                let ptr_type = self
+                    .tcx
+                    .bound(Type::Pointer(Box::new(receiver_type.clone()), *is_mutable), span);
+
+                if self.is_lvalue(&receiver) {
+                    Ok(hir::Node::Builtin(hir::Builtin::Ref(hir::Ref {
+                        value: Box::new(receiver),
+                        is_mutable: *is_mutable,
+                        ty: ptr_type,
+                        span,
+                    })))
+                } else {
+                    let (bound_node, rvalue_node) = self.build_rvalue_ref(env, receiver, *is_mutable, span)?;
+
+                    Ok(hir::Node::Sequence(hir::Sequence {
+                        statements: vec![bound_node, rvalue_node],
+                        ty: ptr_type,
+                        span,
+                        is_scope: false,
+                    }))
+                }
+            }
+            (self_type, Type::Pointer(inner, _)) if !self_type.is_pointer() => {
+                Ok(hir::Node::Builtin(hir::Builtin::Deref(hir::Unary {
+                    ty: self.tcx.bound(inner.as_ref().clone(), span),
+                    span,
+                    value: Box::new(receiver),
+                })))
+            }
+            _ => Ok(receiver),
+        }
+    }
+
+    // Resolves the type an impl block is declared for.
+    // An inherent impl must be declared for a named struct type, declared in the same module.
+    // A trait impl can be declared for any type, as long as the trait or the type are declared in the same module.
+    fn check_impl_type(&mut self, module: &ast::Module, impl_index: usize) -> CheckResult<TypeId> {
+        if let Some(&ty) = self.queued_modules.get(&module.id).unwrap().impl_types.get(&impl_index) {
+            return Ok(ty);
+        }
+
+        let imp = &module.impls[impl_index];
+        let target = &imp.target;
+
+        let ty = self.check_impl_name(module, target)?;
+
+        if let Some(trait_name) = &imp.trait_name {
+            let trait_id = self.check_impl_trait(module, imp, trait_name, ty)?;
+
+            let queued_module = self.queued_modules.get_mut(&module.id).unwrap();
+            queued_module.impl_types.insert(impl_index, ty);
+            queued_module.impl_traits.insert(impl_index, trait_id);
+
+            return Ok(ty);
+        }
+
+        match ty.normalize(&self.tcx) {
+            Type::Struct(StructType { id: Some(id), .. }) => {
+                let binding_info = self.workspace.binding_infos.get(id).unwrap();
+
+                if binding_info.module_id != module.id {
+                    return Err(Diagnostic::error()
+                        .with_message(format!(
+                            "cannot declare associated functions for `{}` outside of the module it is declared in",
+                            target.name
+                        ))
+                        .with_label(Label::primary(target.span, "declared in another module"))
+                        .with_label(Label::secondary(
+                            binding_info.span,
+                            format!("`{}` is defined here", binding_info.name),
+                        )));
+                }
+            }
+            ty => {
+                return Err(Diagnostic::error()
+                    .with_message(format!("expected a named struct type, got `{}`", ty.display(&self.tcx)))
+                    .with_label(Label::primary(target.span, "expected a named struct type"))
+                    .with_note("associated functions can only be declared for named struct types"))
+            }
+        }
+
+        self.queued_modules
+            .get_mut(&module.id)
+            .unwrap()
+            .impl_types
+            .insert(impl_index, ty);
+
+        Ok(ty)
+    }
+
+    fn check_impl_name(&mut self, module: &ast::Module, name: &ast::NameAndSpan) -> CheckResult<TypeId> {
+        self.with_clean_env(module.id, |sess, _| {
+            let node = sess.check_top_level_name(
+                name.name,
+                module.id,
+                CallerInfo {
+                    module_id: module.id,
+                    span: name.span,
+                },
+                false,
+            )?;
+
+            let node = sess.validate_generic_binding_use(node)?;
+
+            sess.require_const_type(&node)
+        })
+    }
+
+    // Resolves the trait of a trait impl, and validates that the impl's functions are the trait's functions
+    fn check_impl_trait(
+        &mut self,
+        module: &ast::Module,
+        imp: &ast::Impl,
+        trait_name: &ast::NameAndSpan,
+        ty: TypeId,
+    ) -> CheckResult<BindingId> {
+        let trait_type = match self.check_impl_name(module, trait_name)?.normalize(&self.tcx) {
+            Type::Trait(trait_type) => trait_type,
+            ty => {
+                return Err(Diagnostic::error()
+                    .with_message(format!("expected a trait, got `{}`", ty.display(&self.tcx)))
+                    .with_label(Label::primary(trait_name.span, "expected a trait")))
+            }
+        };
+
+        let ty = ty.normalize(&self.tcx);
+        let trait_ = self.traits.get(&trait_type.id).unwrap().clone();
+
+        // Orphan rule: an impl can't be declared by a module that owns neither the trait nor the type,
+        // otherwise two unrelated modules could implement the same trait for the same type
+        let type_module_id = match &ty {
+            Type::Struct(StructType { id: Some(id), .. }) | Type::Enum(EnumType { id: Some(id), .. }) => {
+                Some(self.workspace.binding_infos.get(*id).unwrap().module_id)
+            }
+            _ => None,
+        };
+
+        if trait_.module_id != module.id && type_module_id != Some(module.id) {
+            return Err(Diagnostic::error()
+                .with_message(format!(
+                    "cannot implement trait `{}` for `{}` in this module",
+                    trait_.name,
+                    ty.display(&self.tcx)
+                ))
+                .with_label(Label::primary(
+                    imp.target.span,
+                    "neither the trait nor the type are declared here",
+                ))
+                .with_label(Label::secondary(
+                    trait_.span,
+                    format!("`{}` is declared here", trait_.name),
+                ))
+                .with_note("a trait can only be implemented in the module of the trait, or the module of the type"));
+        }
+
+        let mut defined_functions = UstrMap::<Span>::default();
+
+        for binding in imp.bindings.iter() {
+            let (name, span) = match &binding.kind {
+                ast::BindingKind::Function { name, .. } => (name.name, name.span),
+                _ => continue,
+            };
+
+            if let Some(defined_span) = defined_functions.insert(name, span) {
+                return Err(SyntaxError::duplicate_binding(name, span, defined_span));
+            }
+
+            if trait_.find_function(name).is_none() {
+                return Err(Diagnostic::error()
+                    .with_message(format!("`{}` is not a function of trait `{}`", name, trait_.name))
+                    .with_label(Label::primary(span, format!("not a member of `{}`", trait_.name)))
+                    .with_label(Label::secondary(
+                        trait_.span,
+                        format!("`{}` is declared here", trait_.name),
+                    )));
+            }
+        }
+
+        let missing_functions: Vec<&ast::FunctionSig> = trait_
+            .functions
+            .iter()
+            .filter(|sig| !defined_functions.contains_key(&sig.name.unwrap()))
+            .collect();
+
+        if !missing_functions.is_empty() {
+            let names = missing_functions
+                .iter()
+                .map(|sig| format!("`{}`", sig.name.unwrap()))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            let mut diagnostic = Diagnostic::error()
+                .with_message(format!(
+                    "missing functions of trait `{}` in its impl for `{}`: {}",
+                    trait_.name,
+                    ty.display(&self.tcx),
+                    names
+                ))
+                .with_label(Label::primary(imp.target.span, format!("missing {}", names)));
+
+            for sig in missing_functions {
+                diagnostic = diagnostic.with_label(Label::secondary(
+                    sig.span,
+                    format!("`{}` is declared here", sig.name.unwrap()),
+                ));
+            }
+
+            return Err(diagnostic);
+        }
+
+        Ok(trait_type.id)
+    }
+
+    // Checks that a function of a trait impl matches its declaration in the trait
+    fn check_trait_function_sig(&mut self, trait_id: BindingId, binding: &hir::Binding, ty: TypeId) -> CheckResult<()> {
+        let trait_ = self.traits.get(&trait_id).unwrap().clone();
+        let sig = trait_.find_function(binding.name).unwrap();
+
+        // The trait's declaration is checked in the trait's module, where `Self` is the implementing type
+        let expected_type = self.with_clean_env(trait_.module_id, |sess, mut env| {
+            sess.self_types.push(ty);
+            let node = sig.check(sess, &mut env, None);
+            sess.self_types.pop();
+            node
+        })?;
+
+        let expected_type = expected_type.ty().normalize(&self.tcx).into_type();
+        let actual_type = binding.value.ty().normalize(&self.tcx);
+
+        let is_match = match (&expected_type, &actual_type) {
+            (Type::Function(expected), Type::Function(actual)) => {
+                expected.params.len() == actual.params.len()
+                    && expected
+                        .params
+                        .iter()
+                        .zip(actual.params.iter())
+                        .all(|(expected, actual)| is_same_type(&expected.ty, &actual.ty))
+                    && is_same_type(&expected.return_type, &actual.return_type)
+                    && expected.varargs.as_ref().map(|v| v.ty.clone()) == actual.varargs.as_ref().map(|v| v.ty.clone())
+            }
+            _ => false,
+        };
+
+        if is_match {
+            Ok(())
+        } else {
+            Err(Diagnostic::error()
+                .with_message(format!(
+                    "function `{}` doesn't match its declaration in trait `{}`",
+                    binding.name, trait_.name
+                ))
+                .with_label(Label::primary(
+                    binding.span,
+                    format!(
+                        "expected `{}`, found `{}`",
+                        expected_type.display(&self.tcx),
+                        actual_type.display(&self.tcx)
+                    ),
+                ))
+                .with_label(Label::secondary(sig.span, "declared here")))
+        }
+    }
+
+    fn check_associated_function(
+        &mut self,
+        module: &ast::Module,
+        impl_index: usize,
+        function_index: usize,
+        ty: TypeId,
+    ) -> CheckResult<BindingId> {
+        let imp = &module.impls[impl_index];
+        let binding = &imp.bindings[function_index];
+
+        // Associated functions are bound in a scope named after their type, and `Self` refers to their type
+        let node = self.with_clean_env(module.id, |sess, mut env| {
+            env.push_named_scope(impl_scope_name(imp), ScopeKind::Block);
+            sess.self_types.push(ty);
+
+            let node = binding.check(sess, &mut env, None);
+
+            sess.self_types.pop();
+            env.pop_scope();
+
+            node
+        })?;
+
+        if let Err(mut diagnostics) = substitute_node(&node, &mut self.tcx) {
+            let last = diagnostics.pop().unwrap();
+            self.workspace.diagnostics.extend(diagnostics);
+            return Err(last);
+        }
+
+        let binding = match node {
+            hir::Node::Binding(binding) => binding,
+            _ => unreachable!("{:#?}", node),
+        };
+
+        self.check_self_param(&binding, ty)?;
+
+        let (id, name) = (binding.id, binding.name);
+
+        // Trait functions are found through the traits their type implements, and are as visible as their trait
+        if let Some(&trait_id) = self
+            .queued_modules
+            .get(&module.id)
+            .unwrap()
+            .impl_traits
+            .get(&impl_index)
+        {
+            self.check_trait_function_sig(trait_id, &binding, ty)?;
+
+            let binding_info = self.workspace.binding_infos.get_mut(id).unwrap();
+            binding_info.flags |= BindingInfoFlags::ASSOCIATED_FUNCTION;
+            binding_info.vis = ast::Vis::Public;
+
+            self.cache.bindings.insert(id, binding);
+
+            self.queued_modules
+                .get_mut(&module.id)
+                .unwrap()
+                .trait_impl_functions
+                .insert((impl_index, function_index), id);
+
+            return Ok(id);
+        }
+
+        let type_id = struct_type_id(&self.tcx, ty).unwrap();
+
+        if let Some(&defined_id) = self
+            .cache
+            .associated_functions
+            .get(&type_id)
+            .and_then(|functions| functions.get(&name))
+        {
+            let defined_span = self.workspace.binding_infos.get(defined_id).unwrap().span;
+            return Err(SyntaxError::duplicate_binding(name, binding.span, defined_span));
+        }
+
+        // Methods are looked up after fields, so a function with the same name as a field could never be called
+        if let Some(field) = ty.normalize(&self.tcx).as_struct().field(name) {
+            return Err(Diagnostic::error()
+                .with_message(format!(
+                    "associated function `{}` has the same name as a field of `{}`",
+                    name, imp.target.name
+                ))
+                .with_label(Label::primary(binding.span, "conflicts with a field"))
+                .with_label(Label::secondary(
+                    field.span,
+                    format!("field `{}` is defined here", name),
+                )));
+        }
+
+        self.workspace.binding_infos.get_mut(id).unwrap().flags |= BindingInfoFlags::ASSOCIATED_FUNCTION;
+        self.cache.bindings.insert(id, binding);
+
+        self.cache
+            .associated_functions
+            .entry(type_id)
+            .or_default()
+            .insert(name, id);
+
+        Ok(id)
+    }
+
+    // A method's `self` parameter must be its type, or a pointer to it
+    fn check_self_param(&self, binding: &hir::Binding, ty: TypeId) -> CheckResult<()> {
+        let function_type = match binding.value.ty().normalize(&self.tcx) {
+            Type::Function(function_type) => function_type,
+            _ => return Ok(()),
+        };
+
+        let param = match function_type.self_param() {
+            Some(param) => param,
+            None => return Ok(()),
+        };
+
+        let ty = ty.normalize(&self.tcx);
+
+        let is_valid = match &param.ty {
+            Type::Pointer(inner, _) => is_same_type(inner, &ty),
+            param_type => is_same_type(param_type, &ty),
+        };
+
+        if is_valid {
+            Ok(())
+        } else {
+            Err(Diagnostic::error()
+                .with_message(format!(
+                    "invalid `self` parameter type `{}`",
+                    param.ty.display(&self.tcx)
+                ))
+                .with_label(Label::primary(binding.span, "invalid `self` parameter"))
+                .with_note("the `self` parameter's type must be one of `Self`, `*Self` or `*mut Self`"))
+        }
+    }
+
+    // Refers to a function of an impl block, and checks it if it wasn't checked yet
+    #[allow(clippy::too_many_arguments)]
+    fn impl_function_node(
+        &mut self,
+        env: &Env,
+        module: &ast::Module,
+        impl_index: usize,
+        function_index: usize,
+        name: Ustr,
+        ty: TypeId,
+        caller_info: CallerInfo,
+    ) -> CheckResult {
+        let queued_module = self.queued_modules.get_mut(&module.id).unwrap();
+
+        if let Some(&id) = queued_module.trait_impl_functions.get(&(impl_index, function_index)) {
+            return self.associated_function_node(id, caller_info);
+        }
+
+        if queued_module
+            .queued_associated_functions
+            .insert((impl_index, function_index))
+        {
+            let id = self.check_associated_function(module, impl_index, function_index, ty)?;
+            return self.associated_function_node(id, caller_info);
+        }
+
+        // The function is still being checked, so this is a recursive call.
+        // Since it isn't bound yet, it is found through the function's own scope
+        let imp = &module.impls[impl_index];
+        let qualified_name = get_qualified_name(
+            get_qualified_name(module.info.qualified_name, impl_scope_name(imp)),
+            name,
+        );
+
+        match env.find_function(name) {
+            Some(function_id) if self.cache.functions.get(function_id).unwrap().qualified_name == qualified_name => {
+                let function = self.cache.functions.get(function_id).unwrap();
+
+                Ok(hir::Node::Const(hir::Const {
+                    value: ConstValue::Function(ConstFunction {
+                        id: function.id,
+                        name: function.qualified_name,
+                    }),
+                    ty: function.ty,
+                    span: caller_info.span,
+                }))
+            }
+            _ => Err(Diagnostic::error()
+                .with_message(format!("cycle detected while checking `{}.{}`", imp.target.name, name))
+                .with_label(Label::primary(caller_info.span, format!("`{}` refers to itself", name)))
+                .with_label(Label::secondary(
+                    imp.bindings[function_index].pat_span(),
+                    format!("`{}` is defined here", name),
+                ))),
+        }
+    }
+
+    fn associated_function_node(&mut self, id: BindingId, caller_info: CallerInfo) -> CheckResult {
+        self.workspace.add_binding_info_use(id, caller_info.span);
+        self.validate_item_vis(id, caller_info)?;
+        Ok(self.id_or_const_by_id(id, caller_info.span))
+    }
+}
+
+// The functions of a trait impl are scoped by both their type and trait,
+// so that a type can implement several traits which declare functions of the same name
+fn impl_scope_name(imp: &ast::Impl) -> Ustr {
+    match &imp.trait_name {
+        Some(trait_name) => get_qualified_name(imp.target.name, trait_name.name),
+        None => imp.target.name,
+    }
+}
+
+fn struct_type_id(tcx: &TypeCtx, ty: TypeId) -> Option<BindingId> {
+    match ty.normalize(tcx) {
+        Type::Struct(struct_type) => struct_type.id,
+        _ => None,
+    }
+}
+
+fn not_a_method_error(type_name: &str, access: &ast::MemberAccess) -> Diagnostic {
+    Diagnostic::error()
+        .with_message(format!(
+            "`{}` is an associated function of `{}`, not a method",
+            access.member, type_name
+        ))
+        .with_label(Label::primary(access.member_span, "not a method"))
+        .with_note(format!(
+            "call it through its type, i.e: `{}.{}(...)`",
+            type_name, access.member
+        ))
+}
+
+// Named types are compared by their identity, other types are compared structurally
+fn is_same_type(a: &Type, b: &Type) -> bool {
+    match (a, b) {
+        (Type::Struct(StructType { id: Some(a), .. }), Type::Struct(StructType { id: Some(b), .. })) => a == b,
+        (Type::Enum(EnumType { id: Some(a), .. }), Type::Enum(EnumType { id: Some(b), .. })) => a == b,
+        (Type::Pointer(a, a_mut), Type::Pointer(b, b_mut)) => a_mut == b_mut && is_same_type(a, b),
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[test]
+    fn trait_impl_in_unchecked_module() {
+        // `main` is checked before `use describe`, so the impl's module wasn't checked when `describe` is called
+        let source_file = write_sources(&[
+            (
+                "main.chl",
+                "fn main() = {\n    let n = 41\n    let _ = n.describe()\n}\n\nuse describe\n",
+            ),
+            (
+                "describe.chl",
+                "pub type Describe = trait {\n    fn describe(self: *Self) -> int\n}\n\nimpl Describe for int {\n    fn describe(self: *Self) -> int = *self + 1\n}\n",
+            ),
+        ]);
+
+        let result = check_file(&source_file, false);
+
+        assert!(errors(&result).is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use super::{CheckResult, CheckSess};
+use crate::incremental::module_signature_hash;
+use std::collections::HashSet;
+
+impl<'s> CheckSess<'s> {
+    // Modules which didn't change since the last compilation are checked lazily,
+    // which means that only the bindings used by other modules are checked.
+    pub(super) fn collect_lazily_checked_modules(&mut self) {
+        if let Some(module_cache) = self.module_cache {
+            self.cache.lazily_checked_modules = module_cache.unchanged_modules(self.workspace, self.modules);
+        }
+    }
+
+    // Once all changed modules are checked, their public signatures are compared with the cached ones.
+    // Modules which depend on a module whose signature changed are checked again, eagerly.
+    pub(super) fn check_invalidated_modules(&mut self) -> CheckResult<()> {
+        let module_cache = match self.module_cache {
+            Some(module_cache) => module_cache,
+            None => return Ok(()),
+        };
+
+        let modules = self.modules;
+        let mut invalidated_modules = HashSet::new();
+
+        for module in modules.iter() {
+            if self.cache.lazily_checked_modules.contains(&module.id) {
+                continue;
+            }
+
+            let signature_hash = module_signature_hash(self.workspace, &self.tcx, module.id);
+
+            if module_cache.signature_hash(self.workspace, module.id) != Some(signature_hash) {
+                invalidated_modules.extend(module_cache.dependents(self.workspace, module.id));
+            }
+        }
+
+        for module in modules.iter() {
+            if invalidated_modules.contains(&module.id) && self.cache.lazily_checked_modules.remove(&module.id) {
+                self.check_module(module)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -14,7 +14,9 @@ pub(super) fn can_dispatch_intrinsic_at_comptime(sess: &mut CheckSess, callee: &
                 hir::Intrinsic::Location
                 | hir::Intrinsic::CallerLocation
                 | hir::Intrinsic::CompilerError
-                | hir::Intrinsic::CompilerWarning => Some(*intrinsic),
+                | hir::Intrinsic::CompilerWarning
+                | hir::Intrinsic::Print
+                | hir::Intrinsic::Println => Some(*intrinsic),
                 hir::Intrinsic::StartWorkspace | hir::Intrinsic::Os | hir::Intrinsic::Arch => None,
             },
             _ => None,
@@ -28,6 +30,7 @@ pub(super) fn dispatch_intrinsic(
     sess: &mut CheckSess,
     env: &mut Env,
     intrinsic: &hir::Intrinsic,
+    callee: &hir::Node,
     args: &[hir::Node],
     ty: TypeId,
     span: Span,
@@ -75,6 +78,13 @@ pub(super) fn dispatch_intrinsic(
                     .with_label(Label::primary(first_arg.span(), "not a string literal")))
             }
         }
+        hir::Intrinsic::Print | hir::Intrinsic::Println => {
+            // The functions that write the formatted values are defined next to the intrinsic
+            let function_id = callee.as_const_value().unwrap().as_function().unwrap().id;
+            let module_id = sess.cache.functions.get(function_id).unwrap().module_id;
+
+            sess.check_format_call(env, module_id, args, *intrinsic == hir::Intrinsic::Println, span)
+        }
         hir::Intrinsic::StartWorkspace | hir::Intrinsic::Os | hir::Intrinsic::Arch => unreachable!(),
     }
 }
@@ -7,13 +7,16 @@ use crate::{
     hir,
     infer::{display::DisplayType, normalize::Normalize},
     span::Span,
+    sym,
     types::Type,
     workspace::BindingId,
 };
+use ustr::Ustr;
 
 pub enum LvalueAccessErr {
     ImmutableReference { ty: Type, span: Span },
     ImmutableId { id: BindingId, span: Span },
+    CapturedBinding { name: Ustr, span: Span },
     InvalidLvalue,
 }
 
@@ -43,6 +46,10 @@ impl<'s> CheckSess<'s> {
                             format!("consider making this binding mutable: `mut {}`", binding_info.name),
                         ))
                 }
+                CapturedBinding { name, span } => Diagnostic::error()
+                    .with_message(format!("cannot assign to `{}`, as it is captured by a closure", name))
+                    .with_label(Label::primary(span, "cannot assign"))
+                    .with_note("closures capture their environment by value, and cannot change it"),
                 InvalidLvalue => Diagnostic::error()
                     .with_message("invalid left-hand side of assign")
                     .with_label(Label::primary(node.span(), "cannot assign to this expression")),
@@ -71,7 +78,16 @@ impl<'s> CheckSess<'s> {
                 }
             }
             hir::Node::Builtin(hir::Builtin::Offset(offset)) => self.check_lvalue_access_inner(&offset.value, false),
-            hir::Node::MemberAccess(access) => self.check_lvalue_access_inner(&access.value, false),
+            hir::Node::MemberAccess(access) => match access.value.as_ref() {
+                // A captured binding is accessed through its closure's environment parameter
+                hir::Node::Builtin(hir::Builtin::Deref(hir::Unary { value, .. })) if matches!(value.as_ref(), hir::Node::Id(id) if self.is_closure_env_param(id.id)) => {
+                    Err(CapturedBinding {
+                        name: access.member_name,
+                        span: access.span,
+                    })
+                }
+                _ => self.check_lvalue_access_inner(&access.value, false),
+            },
             hir::Node::Id(id) => {
                 let binding_info = self.workspace.binding_infos.get(id.id).unwrap();
 
@@ -99,4 +115,8 @@ impl<'s> CheckSess<'s> {
             _ => Err(InvalidLvalue),
         }
     }
+
+    fn is_closure_env_param(&self, id: BindingId) -> bool {
+        self.workspace.binding_infos.get(id).unwrap().name == sym::CLOSURE_ENV_PARAM
+    }
 }
@@ -0,0 +1,394 @@
+use super::{check_type_expr, env::Env, env::ScopeKind, Check, CheckResult, CheckSess};
+use crate::{
+    ast::{
+        self,
+        pat::{MatchPat, Pat},
+    },
+    error::diagnostic::{Diagnostic, Label},
+    hir::{self, const_value::ConstValue},
+    infer::{
+        coerce::OrCoerceIntoTy,
+        display::{DisplayType, OrReportErr},
+        normalize::Normalize,
+        unify::UnifyType,
+    },
+    span::Span,
+    types::{Type, TypeId, ENUM_TAG_FIELD},
+    workspace::{BindingInfoFlags, BindingInfoKind},
+};
+use ustr::{ustr, Ustr};
+
+// A `match` is desugared into a chain of `if` expressions over the matched value, which is bound once.
+// For enums, each arm compares the value's tag, and unpacks the variant's payload into the arm's scope.
+impl Check for ast::Match {
+    fn check(&self, sess: &mut CheckSess, env: &mut Env, expected_type: Option<TypeId>) -> CheckResult {
+        let bool_type = sess.tcx.common_types.bool;
+        let unit_type = sess.tcx.common_types.unit;
+
+        let value_node = self.value.check(sess, env, None)?;
+        let value_ty = value_node.ty();
+        let value_type = value_ty.normalize(&sess.tcx);
+        let value_span = self.value.span();
+
+        match &value_type {
+            Type::Enum(_) | Type::Bool | Type::Int(_) | Type::Uint(_) | Type::Infer(_, _) => (),
+            ty => {
+                return Err(Diagnostic::error()
+                    .with_message(format!("cannot match on a value of type `{}`", ty.display(&sess.tcx)))
+                    .with_label(Label::primary(value_span, "cannot match on this value"))
+                    .with_note("only enums, integers and booleans can be matched"))
+            }
+        }
+
+        if self.arms.is_empty() {
+            return Err(Diagnostic::error()
+                .with_message("match has no arms")
+                .with_label(Label::primary(self.span, "expected at least one arm")));
+        }
+
+        env.push_scope(ScopeKind::Block);
+
+        let mut statements = vec![];
+
+        // bind the value to a local variable, so we evaluate the matched expression only once
+        let value_name = sess.generate_name("match");
+        let (value_id, value_binding) = sess.bind_name(
+            env,
+            value_name,
+            ast::Vis::Private,
+            value_ty,
+            Some(value_node),
+            false,
+            BindingInfoKind::LetConst,
+            value_span,
+            BindingInfoFlags::NO_CONST_FOLD,
+        )?;
+
+        statements.push(value_binding);
+
+        let value_id_node = hir::Node::Id(hir::Id {
+            id: value_id,
+            ty: value_ty,
+            span: value_span,
+        });
+
+        let mut arms: Vec<(Option<hir::Node>, hir::Node)> = vec![];
+
+        let mut catch_all_span: Option<Span> = None;
+        let mut matched_variants: Vec<(usize, Span)> = vec![];
+        let mut matched_values: Vec<(ConstValue, Span)> = vec![];
+
+        let mut result_type: Option<(TypeId, Span)> = None;
+        let mut is_result_discarded = false;
+
+        for arm in self.arms.iter() {
+            let arm_span = arm.pat.span();
+
+            if let Some(catch_all_span) = catch_all_span {
+                return Err(unreachable_arm_err(arm_span, catch_all_span));
+            }
+
+            env.push_scope(ScopeKind::Block);
+
+            let mut arm_statements = vec![];
+
+            let condition = match &arm.pat {
+                MatchPat::Name(pat) => {
+                    catch_all_span = Some(pat.span);
+
+                    if !pat.ignore {
+                        let (_, binding) = sess.bind_name_pat(
+                            env,
+                            pat,
+                            ast::Vis::Private,
+                            value_ty,
+                            Some(value_id_node.clone()),
+                            BindingInfoKind::LetConst,
+                            BindingInfoFlags::IS_USER_DEFINED
+                                | BindingInfoFlags::TYPE_WAS_INFERRED
+                                | BindingInfoFlags::NO_CONST_FOLD,
+                        )?;
+
+                        arm_statements.push(binding);
+                    }
+
+                    None
+                }
+                MatchPat::Variant(pat) => {
+                    if let Some(enum_type_expr) = &pat.enum_type {
+                        let ty = check_type_expr(enum_type_expr, sess, env)?;
+
+                        ty.unify(&value_ty, &mut sess.tcx).or_report_err(
+                            &sess.tcx,
+                            &value_ty,
+                            Some(value_span),
+                            &ty,
+                            enum_type_expr.span(),
+                        )?;
+                    }
+
+                    let enum_type = match value_ty.normalize(&sess.tcx) {
+                        Type::Enum(enum_type) => enum_type,
+                        ty => {
+                            return Err(Diagnostic::error()
+                                .with_message(format!(
+                                    "expected a value of type `{}`, found enum variant `{}`",
+                                    ty.display(&sess.tcx),
+                                    pat.name.name
+                                ))
+                                .with_label(Label::primary(pat.span, "unexpected variant pattern")))
+                        }
+                    };
+
+                    let (index, variant) = match enum_type.variant_and_position(pat.name.name) {
+                        Some(variant) => variant,
+                        None => {
+                            return Err(Diagnostic::error()
+                                .with_message(format!(
+                                    "enum `{}` has no variant `{}`",
+                                    enum_type.display(&sess.tcx),
+                                    pat.name.name
+                                ))
+                                .with_label(Label::primary(pat.name.span, "unknown variant")))
+                        }
+                    };
+
+                    if let Some(&(_, matched_span)) = matched_variants.iter().find(|(i, _)| *i == index) {
+                        return Err(unreachable_arm_err(arm_span, matched_span));
+                    }
+
+                    matched_variants.push((index, arm_span));
+
+                    if let Some(payload_pat) = &pat.payload {
+                        if !variant.has_payload() {
+                            return Err(Diagnostic::error()
+                                .with_message(format!("variant `{}` doesn't have a payload", variant.name))
+                                .with_label(Label::primary(payload_pat.span, "nothing to unpack")));
+                        }
+
+                        let payload_ty = sess.tcx.bound(variant.payload_type(), payload_pat.span);
+
+                        let payload_node = hir::Node::MemberAccess(hir::MemberAccess {
+                            value: Box::new(value_id_node.clone()),
+                            member_name: variant.name,
+                            member_index: index as u32 + 1,
+                            ty: payload_ty,
+                            span: payload_pat.span,
+                        });
+
+                        let (_, binding) = sess.bind_pat(
+                            env,
+                            &Pat::Tuple(payload_pat.clone()),
+                            ast::Vis::Private,
+                            payload_ty,
+                            Some(payload_node),
+                            BindingInfoKind::LetConst,
+                            payload_pat.span,
+                            BindingInfoFlags::IS_USER_DEFINED
+                                | BindingInfoFlags::TYPE_WAS_INFERRED
+                                | BindingInfoFlags::NO_CONST_FOLD,
+                        )?;
+
+                        arm_statements.push(binding);
+                    }
+
+                    let u32_type = sess.tcx.common_types.u32;
+
+                    let tag_node = hir::Node::MemberAccess(hir::MemberAccess {
+                        value: Box::new(value_id_node.clone()),
+                        member_name: ustr(ENUM_TAG_FIELD),
+                        member_index: 0,
+                        ty: u32_type,
+                        span: pat.span,
+                    });
+
+                    Some(hir::Node::Builtin(hir::Builtin::Eq(hir::Binary {
+                        lhs: Box::new(tag_node),
+                        rhs: Box::new(hir::Node::Const(hir::Const {
+                            value: ConstValue::Int(index as _),
+                            ty: u32_type,
+                            span: pat.span,
+                        })),
+                        ty: bool_type,
+                        span: pat.span,
+                    })))
+                }
+                MatchPat::Literal(pat) => {
+                    let literal = ast::Ast::Literal(ast::Literal {
+                        kind: pat.kind,
+                        span: pat.span,
+                    });
+
+                    let mut literal_node = literal.check(sess, env, Some(value_ty))?;
+
+                    literal_node
+                        .ty()
+                        .unify(&value_ty, &mut sess.tcx)
+                        .or_coerce_into_ty(
+                            &mut literal_node,
+                            &value_ty,
+                            &mut sess.tcx,
+                            sess.target_metrics.word_size,
+                        )
+                        .or_report_err(&sess.tcx, &value_ty, Some(value_span), &literal_node.ty(), pat.span)?;
+
+                    let const_value = literal_node.as_const_value().unwrap().clone();
+
+                    if let Some((_, matched_span)) = matched_values.iter().find(|(v, _)| *v == const_value) {
+                        return Err(unreachable_arm_err(arm_span, *matched_span));
+                    }
+
+                    matched_values.push((const_value, arm_span));
+
+                    Some(hir::Node::Builtin(hir::Builtin::Eq(hir::Binary {
+                        lhs: Box::new(value_id_node.clone()),
+                        rhs: Box::new(literal_node),
+                        ty: bool_type,
+                        span: pat.span,
+                    })))
+                }
+            };
+
+            let mut body_node = arm
+                .expr
+                .check(sess, env, result_type.map(|(ty, _)| ty).or(expected_type))?;
+
+            match result_type {
+                Some((result_ty, result_span)) => {
+                    let unify_result = body_node.ty().unify(&result_ty, &mut sess.tcx).or_coerce_into_ty(
+                        &mut body_node,
+                        &result_ty,
+                        &mut sess.tcx,
+                        sess.target_metrics.word_size,
+                    );
+
+                    match unify_result {
+                        Ok(_) => (),
+                        Err(_) if expected_type.map_or(false, |ty| ty.normalize(&sess.tcx).is_unit()) => {
+                            // If the arms' types don't match, and the expected type is unit,
+                            // then we can assume that the match's result is not used
+                            is_result_discarded = true;
+                        }
+                        Err(err) => {
+                            return Err(err.into_diagnostic(
+                                &sess.tcx,
+                                &result_ty,
+                                Some(result_span),
+                                &body_node.ty(),
+                                arm.expr.span(),
+                            ))
+                        }
+                    }
+                }
+                None => result_type = Some((body_node.ty(), arm.expr.span())),
+            }
+
+            env.pop_scope();
+
+            let body_ty = body_node.ty();
+            arm_statements.push(body_node);
+
+            arms.push((
+                condition,
+                hir::Node::Sequence(hir::Sequence {
+                    statements: arm_statements,
+                    ty: body_ty,
+                    span: arm.span,
+                    is_scope: true,
+                }),
+            ));
+        }
+
+        env.pop_scope();
+
+        if catch_all_span.is_none() {
+            check_exhaustiveness(sess, self, &value_type, &matched_variants, &matched_values)?;
+        }
+
+        let result_ty = match result_type {
+            Some((ty, _)) if !is_result_discarded => ty,
+            _ => unit_type,
+        };
+
+        // the last arm is always taken when all arms before it didn't match, since the match is exhaustive
+        let mut arms = arms.into_iter().rev();
+        let (_, last_body) = arms.next().unwrap();
+
+        let chain = arms.fold(last_body, |otherwise, (condition, then)| {
+            hir::Node::Control(hir::Control::If(hir::If {
+                ty: result_ty,
+                span: then.span(),
+                condition: Box::new(condition.unwrap()),
+                then: Box::new(then),
+                otherwise: Some(Box::new(otherwise)),
+            }))
+        });
+
+        statements.push(chain);
+
+        if is_result_discarded {
+            statements.push(hir::Node::Const(hir::Const {
+                value: ConstValue::Unit(()),
+                ty: unit_type,
+                span: self.span,
+            }));
+        }
+
+        Ok(hir::Node::Sequence(hir::Sequence {
+            statements,
+            ty: result_ty,
+            span: self.span,
+            is_scope: true,
+        }))
+    }
+}
+
+fn check_exhaustiveness(
+    sess: &CheckSess,
+    match_: &ast::Match,
+    value_type: &Type,
+    matched_variants: &[(usize, Span)],
+    matched_values: &[(ConstValue, Span)],
+) -> CheckResult<()> {
+    let missing: Vec<Ustr> = match value_type {
+        Type::Enum(enum_type) => enum_type
+            .variants
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !matched_variants.iter().any(|(i, _)| i == index))
+            .map(|(_, variant)| variant.name)
+            .collect(),
+        Type::Bool => [true, false]
+            .into_iter()
+            .filter(|b| !matched_values.iter().any(|(v, _)| *v == ConstValue::Bool(*b)))
+            .map(|b| ustr(&b.to_string()))
+            .collect(),
+        _ => vec![ustr("_")],
+    };
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let missing_str = missing
+        .iter()
+        .map(|name| format!("`{}`", name))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    Err(Diagnostic::error()
+        .with_message(format!(
+            "non-exhaustive match on type `{}` - {} not covered",
+            value_type.display(&sess.tcx),
+            missing_str
+        ))
+        .with_label(Label::primary(match_.value.span(), "not all values are matched"))
+        .with_note("add the missing patterns, or a `_` arm to match all remaining values"))
+}
+
+fn unreachable_arm_err(arm_span: Span, matched_span: Span) -> Diagnostic {
+    Diagnostic::error()
+        .with_message("unreachable match arm")
+        .with_label(Label::primary(arm_span, "unreachable arm"))
+        .with_label(Label::secondary(matched_span, "already matched here"))
+}
@@ -1,13 +1,34 @@
 mod attrs;
+mod closures;
+mod completion;
 mod const_fold;
+mod defer;
 mod entry;
+mod enums;
 mod env;
+mod exports;
+mod format;
+mod generics;
+mod impls;
+mod incremental;
 mod intrinsics;
 mod lvalue_access;
+mod match_expr;
 mod pat;
 mod top_level;
-
-use self::pat::get_qualified_name;
+mod traits;
+mod try_expr;
+
+use self::{
+    closures::{build_closure, ClosureFrame},
+    enums::check_enum_variant,
+    generics::{
+        check_generic_binding, infer_generic_args, instantiate_generic_binding, instantiate_with_args, GenericArg,
+        GenericBinding,
+    },
+    pat::get_qualified_name,
+    traits::Trait,
+};
 use crate::{
     ast::{self, pat::Pat},
     check::intrinsics::{can_dispatch_intrinsic_at_comptime, dispatch_intrinsic},
@@ -21,6 +42,7 @@ use crate::{
         attrs::AttrKind,
         const_value::{ConstArray, ConstElement, ConstExternVariable, ConstFunction, ConstValue},
     },
+    incremental::ModuleCache,
     infer::{
         cast::{can_cast_type, try_cast_const_value},
         coerce::{coerce_array_to_slice, OrCoerce, OrCoerceIntoTy},
@@ -53,8 +75,8 @@ use ustr::{ustr, Ustr, UstrMap, UstrSet};
 
 pub type CheckData = (hir::Cache, TypeCtx);
 
-pub fn check(workspace: &mut Workspace, module: Vec<ast::Module>) -> CheckData {
-    let mut sess = CheckSess::new(workspace, &module);
+pub fn check(workspace: &mut Workspace, module: Vec<ast::Module>, module_cache: Option<&ModuleCache>) -> CheckData {
+    let mut sess = CheckSess::new(workspace, &module, module_cache);
 
     if let Err(diag) = sess.start() {
         sess.workspace.diagnostics.push(diag);
@@ -75,6 +97,15 @@ pub(super) struct QueuedModule {
     pub(super) all_complete: bool,
     pub(super) queued_bindings: HashSet<usize>, // Binding indices
     pub(super) queued_comptime: HashSet<usize>, // Comptime indices
+    // Impl indices, mapped to the types they are declared for
+    pub(super) impl_types: HashMap<usize, TypeId>,
+    // Impl indices, mapped to the traits they implement
+    pub(super) impl_traits: HashMap<usize, BindingId>,
+    // (Impl index, Function index)
+    pub(super) queued_associated_functions: HashSet<(usize, usize)>,
+    // The functions of trait impls, which are not associated with their type by name:
+    // (Impl index, Function index) -> Function binding
+    pub(super) trait_impl_functions: HashMap<(usize, usize), BindingId>,
 }
 
 pub(super) struct CheckSess<'s> {
@@ -88,6 +119,9 @@ pub(super) struct CheckSess<'s> {
     // The module's being processed
     pub modules: &'s [ast::Module],
 
+    // The cache of the last compilation, used to decide which modules can be checked lazily
+    pub module_cache: Option<&'s ModuleCache>,
+
     pub cache: hir::Cache,
     pub queued_modules: HashMap<ModuleId, QueuedModule>,
 
@@ -97,6 +131,9 @@ pub(super) struct CheckSess<'s> {
     // Stack of function frames, each ast::Function creates its own frame
     pub function_frames: Vec<FunctionFrame>,
 
+    // Stack of closure frames, for functions which are declared inside of other functions
+    pub closure_frames: Vec<ClosureFrame>,
+
     // Stack of `Self` types
     pub self_types: Vec<TypeId>,
 
@@ -110,6 +147,21 @@ pub(super) struct CheckSess<'s> {
 
     // A stack of encountered items. Used to detect global bindings that refer themselves
     pub encountered_items: HashSet<(ModuleId, usize)>,
+
+    // Declared traits, by their binding
+    traits: HashMap<BindingId, Trait>,
+
+    // Generic functions and types, along with their instances
+    generic_bindings: HashMap<BindingId, GenericBinding>,
+
+    // A stack of the instantiations being checked. Used to detect instantiations that require themselves
+    instantiation_stack: Vec<(BindingId, Vec<GenericArg>)>,
+
+    // The functions declared while a generic body is checked against its opaque parameters.
+    // It is `Some` only while such a body is checked, and its functions are removed afterwards, since only instances are lowered
+    generic_body_functions: Option<Vec<hir::FunctionId>>,
+
+    pub in_instantiation_context: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -117,10 +169,16 @@ pub struct FunctionFrame {
     return_type: TypeId,
     return_type_span: Span,
     scope_level: ScopeLevel,
+    // The span of the `defer` currently being checked, which control flow can't escape from
+    defer_span: Option<Span>,
 }
 
 impl<'s> CheckSess<'s> {
-    pub fn new(workspace: &'s mut Workspace, old_asts: &'s Vec<ast::Module>) -> Self {
+    pub fn new(
+        workspace: &'s mut Workspace,
+        old_asts: &'s Vec<ast::Module>,
+        module_cache: Option<&'s ModuleCache>,
+    ) -> Self {
         let target_metrics = workspace.build_options.target_platform.metrics();
         let interp = Interp::new(workspace.build_options.clone());
 
@@ -130,21 +188,30 @@ impl<'s> CheckSess<'s> {
             interp,
             tcx: TypeCtx::default(),
             modules: old_asts,
+            module_cache,
             cache: hir::Cache::new(),
             queued_modules: HashMap::new(),
             global_scopes: HashMap::new(),
             function_frames: vec![],
+            closure_frames: vec![],
             self_types: vec![],
             loop_depth: 0,
             unique_name_indices: UstrMap::default(),
             in_lvalue_context: false,
             encountered_items: HashSet::new(),
+            traits: HashMap::new(),
+            generic_bindings: HashMap::new(),
+            instantiation_stack: vec![],
+            generic_body_functions: None,
+            in_instantiation_context: false,
         }
     }
 
     pub fn start(&mut self) -> CheckResult<()> {
         self.set_libraries_root_module_id();
+        self.collect_lazily_checked_modules();
         self.check_all_libraries()?;
+        self.check_invalidated_modules()?;
         self.perform_final_substitution()?;
         Ok(())
     }
@@ -202,10 +269,38 @@ impl<'s> CheckSess<'s> {
         f(self, Env::new(module_id, module_info))
     }
 
+    // Same as `with_env`, but `f` is also checked regardless of the state of the checker at the call site.
+    // Used for items which are checked on demand, such as generic instances and associated functions
+    pub fn with_clean_env<T, F: FnMut(&mut Self, Env) -> T>(&mut self, module_id: ModuleId, f: F) -> T {
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let function_frames = std::mem::take(&mut self.function_frames);
+        let closure_frames = std::mem::take(&mut self.closure_frames);
+        let self_types = std::mem::take(&mut self.self_types);
+        let in_lvalue_context = std::mem::replace(&mut self.in_lvalue_context, false);
+        let in_instantiation_context = std::mem::replace(&mut self.in_instantiation_context, false);
+        let generic_body_functions = self.generic_body_functions.take();
+
+        let result = self.with_env(module_id, f);
+
+        self.loop_depth = loop_depth;
+        self.function_frames = function_frames;
+        self.closure_frames = closure_frames;
+        self.self_types = self_types;
+        self.in_lvalue_context = in_lvalue_context;
+        self.in_instantiation_context = in_instantiation_context;
+        self.generic_body_functions = generic_body_functions;
+
+        result
+    }
+
     pub fn function_frame(&self) -> Option<FunctionFrame> {
         self.function_frames.last().map(|&f| f)
     }
 
+    pub fn defer_span(&self) -> Option<Span> {
+        self.function_frame().and_then(|f| f.defer_span)
+    }
+
     pub fn require_const_type(&self, node: &hir::Node) -> DiagnosticResult<TypeId> {
         match node.as_const_value() {
             Some(ConstValue::Type(t)) => Ok(*t),
@@ -560,26 +655,55 @@ impl Check for ast::Binding {
             }
             ast::BindingKind::Function {
                 name: ast::NameAndSpan { name, span },
+                generic_params,
                 sig,
                 body,
             } => {
                 let (name, span) = (*name, *span);
 
+                let track_caller = if attrs.has(AttrKind::TrackCaller) {
+                    TrackCaller::Yes
+                } else {
+                    TrackCaller::No
+                };
+
+                let test_attr = attrs.get(AttrKind::Test);
+
+                if let Some(test_attr) = test_attr {
+                    sess.validate_test_function_binding(env, test_attr, !generic_params.is_empty())?;
+                }
+
+                let export_attr = attrs.get(AttrKind::Export);
+
+                if let Some(export_attr) = export_attr {
+                    sess.validate_exported_function_binding(
+                        env,
+                        export_attr,
+                        !generic_params.is_empty(),
+                        attrs.get(AttrKind::TrackCaller),
+                    )?;
+                }
+
+                if !generic_params.is_empty() {
+                    return check_generic_binding(sess, env, self, name, span, track_caller);
+                }
+
                 check_function_sig_has_type_annotations(sess, sig)?;
 
-                let node = check_function(
-                    sess,
-                    env,
-                    sig,
-                    body,
-                    span,
-                    None,
-                    if attrs.has(AttrKind::TrackCaller) {
-                        TrackCaller::Yes
-                    } else {
-                        TrackCaller::No
-                    },
-                )?;
+                let node = check_function(sess, env, sig, body, span, None, track_caller)?;
+
+                if test_attr.is_some() {
+                    sess.collect_test_function(&node)?;
+                }
+
+                if export_attr.is_some() {
+                    let link_name = match attrs.get(AttrKind::LinkName) {
+                        Some(attr) => *attr.value.as_str().unwrap(),
+                        None => name,
+                    };
+
+                    sess.collect_exported_function(&node, link_name)?;
+                }
 
                 // If this function binding matches the entry point function's requirements, Tag it as the entry function
                 // Requirements:
@@ -649,7 +773,9 @@ impl Check for ast::Binding {
                                 | hir::Intrinsic::Location
                                 | hir::Intrinsic::CallerLocation
                                 | hir::Intrinsic::CompilerError
-                                | hir::Intrinsic::CompilerWarning => (
+                                | hir::Intrinsic::CompilerWarning
+                                | hir::Intrinsic::Print
+                                | hir::Intrinsic::Println => (
                                     get_qualified_name(env.scope_name(), name),
                                     hir::FunctionKind::Intrinsic(intrinsic),
                                     BindingInfoKind::Intrinsic(intrinsic),
@@ -691,6 +817,8 @@ impl Check for ast::Binding {
                     span: self.span,
                 });
 
+                sess.record_generic_body_function(function_id);
+
                 let function_value = hir::Node::Const(hir::Const {
                     value: ConstValue::Function(ConstFunction {
                         id: function_id,
@@ -751,7 +879,9 @@ impl Check for ast::Binding {
                                 | hir::Intrinsic::Location
                                 | hir::Intrinsic::CallerLocation
                                 | hir::Intrinsic::CompilerError
-                                | hir::Intrinsic::CompilerWarning => {
+                                | hir::Intrinsic::CompilerWarning
+                                | hir::Intrinsic::Print
+                                | hir::Intrinsic::Println => {
                                     return Err(Diagnostic::error()
                                         .with_message(format!("intrinsic name `{}` is reserved for a function", name))
                                         .with_label(Label::primary(span, "intrinsic is a function")));
@@ -823,15 +953,22 @@ impl Check for ast::Binding {
             }
             ast::BindingKind::Type {
                 name: ast::NameAndSpan { name, span },
+                generic_params,
                 type_expr,
             } => {
                 let (name, span) = (*name, *span);
 
+                if !generic_params.is_empty() {
+                    return check_generic_binding(sess, env, self, name, span, TrackCaller::No);
+                }
+
                 let type_node = type_expr.check(sess, env, Some(sess.tcx.common_types.anytype))?;
 
                 match type_node.as_const_value() {
-                    Some(ConstValue::Type(_)) => sess
-                        .bind_name(
+                    Some(ConstValue::Type(ty)) => {
+                        sess.apply_copy_attr(&attrs, *ty)?;
+
+                        sess.bind_name(
                             env,
                             name,
                             self.vis,
@@ -842,7 +979,8 @@ impl Check for ast::Binding {
                             span,
                             BindingInfoFlags::IS_USER_DEFINED,
                         )
-                        .map(|(_, node)| node),
+                        .map(|(_, node)| node)
+                    }
                     _ => Err(Diagnostic::error()
                         .with_message(format!(
                             "expected a type, got a value of type `{}`",
@@ -856,6 +994,206 @@ impl Check for ast::Binding {
     }
 }
 
+fn check_member_access(
+    sess: &mut CheckSess,
+    env: &mut Env,
+    access: &ast::MemberAccess,
+    node: hir::Node,
+) -> CheckResult {
+    sess.record_member_completion_site(env, node.ty(), access.member_span);
+
+    // The parser recovered from a missing member, i.e: `foo.`. It was already reported as a syntax error.
+    if access.member.is_empty() {
+        return Ok(hir::Node::Const(hir::Const {
+            value: ConstValue::Unit(()),
+            ty: sess.tcx.common_types.never,
+            span: access.span,
+        }));
+    }
+
+    let node_type = node.ty().normalize(&sess.tcx);
+
+    match &node_type {
+        Type::Pointer(inner, is_mutable) => match inner.as_ref() {
+            Type::Slice(inner) | Type::Str(inner) => {
+                if access.member.as_str() == sym::BUILTIN_FIELD_LEN {
+                    let ty = sess.tcx.common_types.uint;
+
+                    if let Some(ConstValue::Str(s)) = node.as_const_value() {
+                        return Ok(hir::Node::Const(hir::Const {
+                            value: ConstValue::Int(s.len() as _),
+                            ty,
+                            span: access.span,
+                        }));
+                    } else {
+                        return Ok(hir::Node::MemberAccess(hir::MemberAccess {
+                            ty,
+                            span: access.span,
+                            value: Box::new(node),
+                            member_name: access.member,
+                            member_index: 1,
+                        }));
+                    }
+                } else if access.member.as_str() == sym::BUILTIN_FIELD_PTR {
+                    return Ok(hir::Node::MemberAccess(hir::MemberAccess {
+                        value: Box::new(node),
+                        member_name: access.member,
+                        member_index: 0,
+                        ty: sess.tcx.bound(Type::Pointer(inner.clone(), *is_mutable), access.span),
+                        span: access.span,
+                    }));
+                }
+            }
+            _ => (),
+        },
+        Type::Type(inner) => match inner.as_ref() {
+            Type::Enum(enum_type) => return check_enum_variant(sess, env, enum_type, access, None, access.span),
+            Type::Struct(struct_type) => {
+                let caller_info = CallerInfo {
+                    module_id: env.module_id(),
+                    span: access.member_span,
+                };
+
+                if let Some(node) = sess.find_associated_function(env, struct_type, access.member, caller_info)? {
+                    return Ok(node);
+                }
+
+                return match sess.find_trait_function(env, inner, access.member, caller_info)? {
+                    Some(node) => Ok(node),
+                    None => Err(Diagnostic::error()
+                        .with_message(format!(
+                            "type `{}` has no associated function `{}`",
+                            struct_type.name, access.member
+                        ))
+                        .with_label(Label::primary(access.member_span, "unknown associated function"))),
+                };
+            }
+            // The functions of a trait implemented for a type, i.e: `int.zero()`
+            ty => {
+                let caller_info = CallerInfo {
+                    module_id: env.module_id(),
+                    span: access.member_span,
+                };
+
+                if let Some(node) = sess.find_trait_function(env, ty, access.member, caller_info)? {
+                    return Ok(node);
+                }
+            }
+        },
+        Type::Module(module_id) => {
+            let node = sess.check_top_level_name(
+                access.member,
+                *module_id,
+                CallerInfo {
+                    module_id: env.module_id(),
+                    span: access.member_span,
+                },
+                true,
+            )?;
+
+            return sess.validate_generic_binding_use(node);
+        }
+        _ => (),
+    }
+
+    // Note (Ron): If the accessed value is a pointer, we auto dereference it.
+    let node = if node_type.is_pointer() {
+        hir::Node::Builtin(hir::Builtin::Deref(hir::Unary {
+            ty: sess.tcx.bound(node_type.maybe_deref_once().clone(), node.span()),
+            span: node.span(),
+            value: Box::new(node),
+        }))
+    } else {
+        node
+    };
+
+    match &node_type.maybe_deref_once() {
+        ty @ Type::Tuple(elements) => {
+            match access.member.as_str().parse::<usize>() {
+                Ok(index) => match elements.get(index) {
+                    Some(field_ty) => {
+                        let ty = sess.tcx.bound(field_ty.clone(), access.span);
+
+                        if let Some(ConstValue::Tuple(const_elements)) = node.as_const_value() {
+                            Ok(hir::Node::Const(hir::Const {
+                                value: const_elements[index].value.clone(),
+                                ty,
+                                span: access.span,
+                            }))
+                        } else {
+                            // TODO: The index here *could be wrong*.
+                            // TODO: We need to test this to make sure there aren't messing anything here
+                            Ok(hir::Node::MemberAccess(hir::MemberAccess {
+                                ty,
+                                span: access.span,
+                                value: Box::new(node),
+                                member_name: access.member,
+                                member_index: index as _,
+                            }))
+                        }
+                    }
+                    None => Err(TypeError::tuple_field_out_of_bounds(
+                        access.expr.span(),
+                        &access.member,
+                        ty.display(&sess.tcx),
+                        elements.len() - 1,
+                    )),
+                },
+                Err(_) => Err(TypeError::non_numeric_tuple_field(
+                    access.expr.span(),
+                    &access.member,
+                    ty.display(&sess.tcx),
+                )),
+            }
+        }
+        ty @ Type::Struct(st) => match st.field_and_position(access.member) {
+            Some((index, field)) => {
+                let ty = sess.tcx.bound(field.ty.clone(), access.span);
+
+                if let Some(ConstValue::Struct(const_fields)) = node.as_const_value() {
+                    Ok(hir::Node::Const(hir::Const {
+                        value: const_fields[&field.name].value.clone(),
+                        ty,
+                        span: access.span,
+                    }))
+                } else {
+                    // TODO: The index here *could be wrong*.
+                    // TODO: We need to test this to make sure there aren't messing anything here
+                    Ok(hir::Node::MemberAccess(hir::MemberAccess {
+                        ty,
+                        span: access.span,
+                        value: Box::new(node),
+                        member_name: access.member,
+                        member_index: index as _,
+                    }))
+                }
+            }
+            None => {
+                sess.validate_associated_function_value_access(env, st, access)?;
+
+                Err(TypeError::invalid_struct_field(
+                    access.expr.span(),
+                    access.member,
+                    ty.display(&sess.tcx),
+                ))
+            }
+        },
+        Type::Array(_, size) if access.member.as_str() == sym::BUILTIN_FIELD_LEN => Ok(hir::Node::Const(hir::Const {
+            value: ConstValue::Int(*size as _),
+            ty: sess.tcx.common_types.uint,
+            span: access.span,
+        })),
+        Type::Param(param_type) => Err(sess.type_param_member_error(param_type, access)),
+        ty => Err(Diagnostic::error()
+            .with_message(format!(
+                "type `{}` has no member `{}`",
+                ty.display(&sess.tcx),
+                access.member
+            ))
+            .with_label(Label::primary(access.expr.span(), ""))),
+    }
+}
+
 impl Check for ast::FunctionSig {
     fn check(&self, sess: &mut CheckSess, env: &mut Env, expected_type: Option<TypeId>) -> CheckResult {
         check_function_sig_has_type_annotations(sess, self)?;
@@ -893,6 +1231,7 @@ impl Check for ast::Ast {
         match self {
             ast::Ast::Binding(binding) => binding.check(sess, env, None),
             ast::Ast::Cast(cast) => cast.check(sess, env, expected_type),
+            ast::Ast::Try(try_) => try_.check(sess, env, expected_type),
             ast::Ast::Import(import) => {
                 let import_path = import.path.to_str().unwrap();
 
@@ -951,6 +1290,8 @@ impl Check for ast::Ast {
                         ty: sess.tcx.common_types.never,
                         span: term.span,
                     })))
+                } else if let Some(defer_span) = sess.defer_span() {
+                    Err(SyntaxError::inside_of_defer(term.span, defer_span, "break"))
                 } else {
                     Err(SyntaxError::outside_of_loop(term.span, "break"))
                 }
@@ -961,16 +1302,30 @@ impl Check for ast::Ast {
                         ty: sess.tcx.common_types.never,
                         span: term.span,
                     })))
+                } else if let Some(defer_span) = sess.defer_span() {
+                    Err(SyntaxError::inside_of_defer(term.span, defer_span, "continue"))
                 } else {
                     Err(SyntaxError::outside_of_loop(term.span, "continue"))
                 }
             }
             ast::Ast::Return(return_) => return_.check(sess, env, expected_type),
+            ast::Ast::Defer(defer) => Err(Diagnostic::error()
+                .with_message("`defer` can only be used as a statement in a block")
+                .with_label(Label::primary(defer.span, "not a statement in a block"))),
             ast::Ast::If(if_) => if_.check(sess, env, expected_type),
+            ast::Ast::Match(match_) => match_.check(sess, env, expected_type),
             ast::Ast::Block(block) => block.check(sess, env, expected_type),
             ast::Ast::Binary(binary) => binary.check(sess, env, expected_type),
             ast::Ast::Unary(unary) => unary.check(sess, env, expected_type),
+            ast::Ast::Instantiation(instantiation) => instantiation.check(sess, env, expected_type),
             ast::Ast::Subscript(sub) => {
+                let node = sess.with_instantiation_context(|sess| sub.expr.check(sess, env, None))?;
+
+                // An instantiation of a generic binding with a single argument, i.e: `Foo[i32]`
+                if let Some(id) = sess.generic_binding_id(&node) {
+                    return instantiate_with_args(sess, env, id, std::slice::from_ref(sub.index.as_ref()), sub.span);
+                }
+
                 let uint = sess.tcx.common_types.uint;
 
                 let mut offset_node = sub.index.check(sess, env, None)?;
@@ -981,7 +1336,6 @@ impl Check for ast::Ast {
                     .or_coerce_into_ty(&mut offset_node, &uint, &mut sess.tcx, sess.target_metrics.word_size)
                     .or_report_err(&sess.tcx, &uint, None, &offset_node.ty(), sub.index.span())?;
 
-                let node = sub.expr.check(sess, env, None)?;
                 let node_type = node.ty().normalize(&sess.tcx);
 
                 let const_value = if let Some(ConstValue::Int(const_index)) = offset_node.as_const_value() {
@@ -996,9 +1350,10 @@ impl Check for ast::Ast {
                             .with_label(Label::primary(sub.index.span(), "index out of bounds")));
                     }
 
-                    // compile-time array bounds check
+                    // compile-time array bounds check.
+                    // skipped in generic bodies, where array sizes may depend on placeholder values
                     if let Type::Array(_, size) = node_type.maybe_deref_once() {
-                        if const_index >= size as _ {
+                        if const_index >= size as _ && !sess.is_checking_generic_body() {
                             return Err(Diagnostic::error()
                                 .with_message(format!(
                                     "index out of array bounds - expected 0 to {}, but found {}",
@@ -1010,7 +1365,7 @@ impl Check for ast::Ast {
                     }
 
                     if let Some(ConstValue::Array(const_array)) = node.as_const_value() {
-                        Some(const_array.values[const_index as usize].clone())
+                        const_array.values.get(const_index as usize).cloned()
                     } else {
                         None
                     }
@@ -1141,152 +1496,14 @@ impl Check for ast::Ast {
             ast::Ast::Call(call) => call.check(sess, env, expected_type),
             ast::Ast::MemberAccess(access) => {
                 let node = access.expr.check(sess, env, None)?;
-
-                let node_type = node.ty().normalize(&sess.tcx);
-
-                match &node_type {
-                    Type::Pointer(inner, is_mutable) => match inner.as_ref() {
-                        Type::Slice(inner) | Type::Str(inner) => {
-                            if access.member.as_str() == sym::BUILTIN_FIELD_LEN {
-                                let ty = sess.tcx.common_types.uint;
-
-                                if let Some(ConstValue::Str(s)) = node.as_const_value() {
-                                    return Ok(hir::Node::Const(hir::Const {
-                                        value: ConstValue::Int(s.len() as _),
-                                        ty,
-                                        span: access.span,
-                                    }));
-                                } else {
-                                    return Ok(hir::Node::MemberAccess(hir::MemberAccess {
-                                        ty,
-                                        span: access.span,
-                                        value: Box::new(node),
-                                        member_name: access.member,
-                                        member_index: 1,
-                                    }));
-                                }
-                            } else if access.member.as_str() == sym::BUILTIN_FIELD_PTR {
-                                return Ok(hir::Node::MemberAccess(hir::MemberAccess {
-                                    value: Box::new(node),
-                                    member_name: access.member,
-                                    member_index: 0,
-                                    ty: sess.tcx.bound(Type::Pointer(inner.clone(), *is_mutable), access.span),
-                                    span: access.span,
-                                }));
-                            }
-                        }
-                        _ => (),
-                    },
-                    Type::Module(module_id) => {
-                        return sess.check_top_level_name(
-                            access.member,
-                            *module_id,
-                            CallerInfo {
-                                module_id: env.module_id(),
-                                span: access.member_span,
-                            },
-                            true,
-                        )
-                    }
-                    _ => (),
-                }
-
-                // Note (Ron): If the accessed value is a pointer, we auto dereference it.
-                let node = if node_type.is_pointer() {
-                    hir::Node::Builtin(hir::Builtin::Deref(hir::Unary {
-                        ty: sess.tcx.bound(node_type.maybe_deref_once().clone(), node.span()),
-                        span: node.span(),
-                        value: Box::new(node),
-                    }))
-                } else {
-                    node
-                };
-
-                match &node_type.maybe_deref_once() {
-                    ty @ Type::Tuple(elements) => {
-                        match access.member.as_str().parse::<usize>() {
-                            Ok(index) => match elements.get(index) {
-                                Some(field_ty) => {
-                                    let ty = sess.tcx.bound(field_ty.clone(), access.span);
-
-                                    if let Some(ConstValue::Tuple(const_elements)) = node.as_const_value() {
-                                        Ok(hir::Node::Const(hir::Const {
-                                            value: const_elements[index].value.clone(),
-                                            ty,
-                                            span: access.span,
-                                        }))
-                                    } else {
-                                        // TODO: The index here *could be wrong*.
-                                        // TODO: We need to test this to make sure there aren't messing anything here
-                                        Ok(hir::Node::MemberAccess(hir::MemberAccess {
-                                            ty,
-                                            span: access.span,
-                                            value: Box::new(node),
-                                            member_name: access.member,
-                                            member_index: index as _,
-                                        }))
-                                    }
-                                }
-                                None => Err(TypeError::tuple_field_out_of_bounds(
-                                    access.expr.span(),
-                                    &access.member,
-                                    ty.display(&sess.tcx),
-                                    elements.len() - 1,
-                                )),
-                            },
-                            Err(_) => Err(TypeError::non_numeric_tuple_field(
-                                access.expr.span(),
-                                &access.member,
-                                ty.display(&sess.tcx),
-                            )),
-                        }
-                    }
-                    ty @ Type::Struct(st) => match st.field_and_position(access.member) {
-                        Some((index, field)) => {
-                            let ty = sess.tcx.bound(field.ty.clone(), access.span);
-
-                            if let Some(ConstValue::Struct(const_fields)) = node.as_const_value() {
-                                Ok(hir::Node::Const(hir::Const {
-                                    value: const_fields[&field.name].value.clone(),
-                                    ty,
-                                    span: access.span,
-                                }))
-                            } else {
-                                // TODO: The index here *could be wrong*.
-                                // TODO: We need to test this to make sure there aren't messing anything here
-                                Ok(hir::Node::MemberAccess(hir::MemberAccess {
-                                    ty,
-                                    span: access.span,
-                                    value: Box::new(node),
-                                    member_name: access.member,
-                                    member_index: index as _,
-                                }))
-                            }
-                        }
-                        None => Err(TypeError::invalid_struct_field(
-                            access.expr.span(),
-                            access.member,
-                            ty.display(&sess.tcx),
-                        )),
-                    },
-                    Type::Array(_, size) if access.member.as_str() == sym::BUILTIN_FIELD_LEN => {
-                        Ok(hir::Node::Const(hir::Const {
-                            value: ConstValue::Int(*size as _),
-                            ty: sess.tcx.common_types.uint,
-                            span: access.span,
-                        }))
-                    }
-                    ty => Err(Diagnostic::error()
-                        .with_message(format!(
-                            "type `{}` has no member `{}`",
-                            ty.display(&sess.tcx),
-                            access.member
-                        ))
-                        .with_label(Label::primary(access.expr.span(), ""))),
-                }
+                check_member_access(sess, env, access, node)
             }
             ast::Ast::Ident(ident) => {
+                sess.record_scope_completion_site(env, ident.span);
+
                 if let Some(id) = env.find_function(ident.name) {
+                    sess.record_closure_self_reference(id, ident.span);
+
                     let function = sess.cache.functions.get(id).unwrap();
 
                     Ok(hir::Node::Const(hir::Const {
@@ -1298,7 +1515,7 @@ impl Check for ast::Ast {
                         span: ident.span,
                     }))
                 } else {
-                    match sess.get_binding_id(env, ident.name) {
+                    let node = match sess.get_binding_id(env, ident.name) {
                         Some(id) => {
                             // this is a local binding
 
@@ -1317,9 +1534,7 @@ impl Check for ast::Ast {
                                     && !binding_info.scope_level.is_global()
                                     && binding_info.scope_level < function_scope
                                 {
-                                    return Err(Diagnostic::error()
-                                        .with_message("can't capture environment - closures are not implemented yet")
-                                        .with_label(Label::primary(ident.span, "can't capture")));
+                                    return sess.capture_binding(env, id, ident.span);
                                 }
                             }
 
@@ -1337,7 +1552,9 @@ impl Check for ast::Ast {
                                 false,
                             )
                         }
-                    }
+                    }?;
+
+                    sess.validate_generic_binding_use(node)
                 }
             }
             ast::Ast::ArrayLiteral(lit) => match &lit.kind {
@@ -1547,7 +1764,9 @@ impl Check for ast::Ast {
                 }))
             }
             ast::Ast::StructType(struct_type) => struct_type.check(sess, env, expected_type),
-            ast::Ast::FunctionType(sig) => {
+            ast::Ast::EnumType(enum_type) => enum_type.check(sess, env, expected_type),
+            ast::Ast::TraitType(trait_type) => trait_type.check(sess, env, expected_type),
+            ast::Ast::FunctionType(sig) | ast::Ast::ClosureType(sig) => {
                 let node = sig.check(sess, env, Some(sess.tcx.common_types.anytype))?;
 
                 for param in sig.params.iter() {
@@ -1562,7 +1781,18 @@ impl Check for ast::Ast {
                     }
                 }
 
-                Ok(node)
+                if let ast::Ast::ClosureType(_) = self {
+                    let function_type = node.ty().normalize(&sess.tcx).into_type().into_function();
+                    let closure_type = Type::Closure(function_type);
+
+                    Ok(hir::Node::Const(hir::Const {
+                        ty: sess.tcx.bound(closure_type.clone().create_type(), sig.span),
+                        span: sig.span,
+                        value: ConstValue::Type(sess.tcx.bound(closure_type, sig.span)),
+                    }))
+                } else {
+                    Ok(node)
+                }
             }
             ast::Ast::SelfType(expr) => match sess.self_types.last() {
                 Some(&ty) => {
@@ -1575,7 +1805,7 @@ impl Check for ast::Ast {
                     }))
                 }
                 None => Err(Diagnostic::error()
-                    .with_message("Self is only available within struct types")
+                    .with_message("Self is only available within struct types and impl blocks")
                     .with_label(Label::primary(expr.span, "invalid Self"))),
             },
             ast::Ast::Placeholder(expr) => {
@@ -2035,6 +2265,9 @@ impl Check for ast::Comptime {
             // TODO: This is a hack so that printing won't interfere with our communication
             // TODO: with the language server. This causes false-positives, and needs to be fixed.
             Ok(node)
+        } else if sess.is_checking_generic_body() {
+            // The opaque parameters of a generic body have no values, so it is evaluated in its instances
+            Ok(node)
         } else {
             let value = sess.eval(&node, env.module_id(), self.span)?;
 
@@ -2067,6 +2300,10 @@ impl Check for ast::Return {
             .function_frame()
             .ok_or(SyntaxError::outside_of_function(self.span, "return"))?;
 
+        if let Some(defer_span) = function_frame.defer_span {
+            return Err(SyntaxError::inside_of_defer(self.span, defer_span, "return"));
+        }
+
         let value = if let Some(expr) = &self.expr {
             let return_type = function_frame.return_type;
             let mut node = expr.check(sess, env, Some(return_type))?;
@@ -2663,10 +2900,48 @@ impl Check for ast::Call {
             Ok(())
         }
 
-        let callee = self.callee.check(sess, env, None)?;
+        let (callee, receiver) = match self.callee.as_ref() {
+            ast::Ast::MemberAccess(access) => {
+                // The accessed value is checked as an lvalue, since a method may take it by reference
+                sess.in_lvalue_context = true;
+                let node = access.expr.check(sess, env, None)?;
+                sess.in_lvalue_context = false;
+
+                // `Enum.variant(...)` constructs a variant with a payload
+                if let Type::Type(inner) = node.ty().normalize(&sess.tcx) {
+                    if let Type::Enum(enum_type) = inner.as_ref() {
+                        sess.record_member_completion_site(env, node.ty(), access.member_span);
+                        return check_enum_variant(sess, env, enum_type, access, Some(&self.args), self.span);
+                    }
+                }
+
+                // `value.method(...)` passes the accessed value as the method's `self` argument
+                match sess.find_method(env, &node, access)? {
+                    Some(method) => (method, Some(node)),
+                    None => (
+                        sess.with_instantiation_context(|sess| check_member_access(sess, env, access, node))?,
+                        None,
+                    ),
+                }
+            }
+            callee => (
+                sess.with_instantiation_context(|sess| callee.check(sess, env, None))?,
+                None,
+            ),
+        };
+
+        // A call to a generic function without generic arguments, i.e: `max(1, 2)`.
+        // Its generic arguments are inferred from the call's arguments
+        let callee = match sess.generic_binding_id(&callee) {
+            Some(id) => {
+                let args = infer_generic_args(sess, env, id, self)?;
+                instantiate_generic_binding(sess, id, args, self.callee.span())?
+            }
+            None => callee,
+        };
 
         match callee.ty().normalize(&sess.tcx) {
-            Type::Function(function_type) => {
+            Type::Function(function_type) | Type::Closure(function_type) => {
                 fn arg_mismatch(
                     sess: &CheckSess,
                     function_type: &FunctionType,
@@ -2736,6 +3011,27 @@ impl Check for ast::Call {
                     _ => 0,
                 };
 
+                // The receiver of a method call is passed as its `self` argument
+                let param_offset = match receiver {
+                    Some(receiver) => {
+                        let param = &function_type.params[param_offset];
+                        let param_type = sess.tcx.bound(param.ty.clone(), receiver.span());
+
+                        let mut node = sess.build_method_receiver(env, receiver, &param.ty)?;
+                        let span = node.span();
+
+                        node.ty()
+                            .unify(&param_type, &mut sess.tcx)
+                            .or_coerce_into_ty(&mut node, &param_type, &mut sess.tcx, sess.target_metrics.word_size)
+                            .or_report_err(&sess.tcx, &param_type, None, &node.ty(), span)?;
+
+                        args.push(node);
+
+                        param_offset + 1
+                    }
+                    None => param_offset,
+                };
+
                 // Check the arguments passed against the function's parameter types
                 for (index, arg) in self.args.iter().enumerate() {
                     if let Some(param) = function_type.params.get(index + param_offset) {
@@ -2957,7 +3253,7 @@ impl Check for ast::Call {
                 let ty = sess.tcx.bound(function_type.return_type.as_ref().clone(), self.span);
 
                 if let Some(intrinsic) = can_dispatch_intrinsic_at_comptime(sess, &callee) {
-                    dispatch_intrinsic(sess, env, &intrinsic, &args, ty, self.span)
+                    dispatch_intrinsic(sess, env, &intrinsic, &callee, &args, ty, self.span)
                 } else {
                     Ok(hir::Node::Call(hir::Call {
                         callee: Box::new(callee),
@@ -3076,7 +3372,10 @@ impl Check for ast::Block {
                 span: self.span,
                 is_scope: true,
             })),
-            1 => self.statements[0].check(sess, env, expected_type),
+            // A deferred statement needs a scope to be attached to
+            1 if !matches!(self.statements[0], ast::Ast::Defer(_)) => {
+                self.statements[0].check(sess, env, expected_type)
+            }
             _ => {
                 let mut statements: Vec<hir::Node> = vec![];
 
@@ -3090,7 +3389,10 @@ impl Check for ast::Block {
                         Some(unit_type)
                     };
 
-                    let node = expr.check(sess, env, expected_type)?;
+                    let node = match expr {
+                        ast::Ast::Defer(defer) => defer.check(sess, env, expected_type)?,
+                        _ => expr.check(sess, env, expected_type)?,
+                    };
 
                     statements.push(node);
                 }
@@ -3542,6 +3844,9 @@ fn check_function<'s>(
     let name = sig.name_or_anonymous();
     let qualified_name = get_qualified_name(env.scope_name(), name);
 
+    // A function that is declared inside of another function can capture its environment, making it a closure
+    let is_nested = sess.function_frame().is_some();
+
     let sig_node = check_function_sig(sess, env, sig, expected_type, None, track_caller)?;
     let sig_type = sess.require_const_type(&sig_node)?;
     let function_type = sig_type.normalize(&sess.tcx).into_function();
@@ -3674,16 +3979,27 @@ fn check_function<'s>(
         span,
     });
 
+    sess.record_generic_body_function(function_id);
+
     env.insert_function(name, function_id);
 
+    if is_nested {
+        sess.closure_frames.push(ClosureFrame::new(function_id));
+    }
+
     let body_node = sess.with_function_frame(
         FunctionFrame {
             return_type,
             return_type_span,
             scope_level: env.scope_level(),
+            defer_span: None,
         },
         |sess| body.check(sess, env, Some(return_type)),
-    )?;
+    );
+
+    let closure_frame = if is_nested { sess.closure_frames.pop() } else { None };
+
+    let body_node = body_node?;
 
     let mut body_sequence = match body_node {
         hir::Node::Sequence(sequence) => sequence,
@@ -3764,11 +4080,16 @@ fn check_function<'s>(
         .unwrap()
         .set_body(body_sequence);
 
-    Ok(hir::Node::Const(hir::Const {
-        value: ConstValue::Function(ConstFunction { id: function_id, name }),
-        ty: sig_type,
-        span,
-    }))
+    match closure_frame {
+        Some(closure_frame) if closure_frame.env.is_some() => {
+            build_closure(sess, env, closure_frame, &function_type, span)
+        }
+        _ => Ok(hir::Node::Const(hir::Const {
+            value: ConstValue::Function(ConstFunction { id: function_id, name }),
+            ty: sig_type,
+            span,
+        })),
+    }
 }
 
 fn check_function_sig<'s>(
@@ -3891,7 +4212,12 @@ fn check_function_sig<'s>(
         _ => sess.tcx.var(sig.span),
     };
 
-    let expected_type_norm = expected_type.map(|ty| ty.normalize(&sess.tcx));
+    // When a closure is expected, the function is expected to have the closure's signature
+    let expected_type_norm = expected_type.map(|ty| match ty.normalize(&sess.tcx) {
+        Type::Closure(f) => Type::Function(f),
+        ty => ty,
+    });
+
     if let Some(Type::Function(expected_function_type)) = &expected_type_norm {
         // if the function signature has no parameters, and the
         // parent type is a function with 1 parameter, add an implicit `it` parameter
@@ -4086,12 +4412,15 @@ fn can_type_be_in_function_sig(ty: &Type) -> bool {
         | Type::Float(_)
         | Type::Pointer(_, _)
         | Type::Function(_)
+        | Type::Closure(_)
         | Type::Array(_, _)
         | Type::Slice(_)
         | Type::Str(_)
         | Type::Tuple(_)
-        | Type::Struct(_) => true,
+        | Type::Struct(_)
+        | Type::Enum(_)
+        | Type::Param(_) => true,
 
-        Type::Module(_) | Type::Type(_) | Type::AnyType | Type::Var(_) | Type::Infer(_, _) => false,
+        Type::Module(_) | Type::Trait(_) | Type::Type(_) | Type::AnyType | Type::Var(_) | Type::Infer(_, _) => false,
     }
 }
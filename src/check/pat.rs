@@ -72,6 +72,7 @@ impl<'s> CheckSess<'s> {
             is_mutable,
             kind,
             scope_level,
+            scope_path: env.scope_path(),
             qualified_name: get_qualified_name(env.scope_name(), name),
             span,
             flags,
@@ -8,7 +8,7 @@ use crate::{
     types::{Type, TypeId},
     workspace::{BindingId, ModuleId, ModuleInfo},
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use ustr::{Ustr, UstrMap};
 
 #[derive(Debug, Clone, Copy)]
@@ -226,26 +226,13 @@ impl<'s> CheckSess<'s> {
         if let Some(ty) = self.get_completed_module_type(module.id) {
             Ok(ty)
         } else {
-            let module_type = match self.queued_modules.get(&module.id) {
-                Some(queued) => queued.module_type,
-                None => {
-                    let span = Span::initial(module.file_id);
-                    let module_type = self.tcx.bound(Type::Module(module.id), span);
-
-                    // Add the module to the queued modules map
-                    self.queued_modules.insert(
-                        module.id,
-                        QueuedModule {
-                            module_type,
-                            all_complete: false,
-                            queued_bindings: HashSet::new(),
-                            queued_comptime: HashSet::new(),
-                        },
-                    );
-
-                    module_type
-                }
-            };
+            let module_type = self.queue_module(module);
+
+            // Bindings of lazily checked modules are checked on demand, when they are used by another module.
+            // Modules with items that aren't used by name, such as impls, are never checked lazily
+            if self.cache.lazily_checked_modules.contains(&module.id) {
+                return Ok(module_type);
+            }
 
             for (index, binding) in module.bindings.iter().enumerate() {
                 if self
@@ -259,6 +246,10 @@ impl<'s> CheckSess<'s> {
                 }
             }
 
+            for impl_index in 0..module.impls.len() {
+                self.check_impl(module, impl_index)?;
+            }
+
             self.queued_modules.get_mut(&module.id).unwrap().all_complete = true;
 
             for (index, comptime) in module.comptime_blocks.iter().enumerate() {
@@ -281,6 +272,32 @@ impl<'s> CheckSess<'s> {
         }
     }
 
+    // Adds the module to the queued modules map, so that its items can be checked on demand
+    pub(super) fn queue_module(&mut self, module: &ast::Module) -> TypeId {
+        if let Some(queued) = self.queued_modules.get(&module.id) {
+            return queued.module_type;
+        }
+
+        let span = Span::initial(module.file_id);
+        let module_type = self.tcx.bound(Type::Module(module.id), span);
+
+        self.queued_modules.insert(
+            module.id,
+            QueuedModule {
+                module_type,
+                all_complete: false,
+                queued_bindings: HashSet::new(),
+                queued_comptime: HashSet::new(),
+                impl_types: HashMap::new(),
+                impl_traits: HashMap::new(),
+                queued_associated_functions: HashSet::new(),
+                trait_impl_functions: HashMap::new(),
+            },
+        );
+
+        module_type
+    }
+
     fn get_module_type(&self, id: ModuleId) -> TypeId {
         self.queued_modules.get(&id).unwrap().module_type
     }
@@ -354,7 +371,11 @@ where
 
 impl CheckTopLevel for ast::Binding {
     fn check_top_level(&self, sess: &mut CheckSess, module_id: ModuleId) -> CheckResult<UstrMap<BindingId>> {
-        let node = sess.with_env(module_id, |sess, mut env| self.check(sess, &mut env, None))?;
+        // A global binding may be checked on demand from a generic body, but it is never a part of that body
+        let generic_body_functions = sess.generic_body_functions.take();
+        let node = sess.with_env(module_id, |sess, mut env| self.check(sess, &mut env, None));
+        sess.generic_body_functions = generic_body_functions;
+        let node = node?;
 
         if let Err(mut diagnostics) = substitute_node(&node, &mut sess.tcx) {
             let last = diagnostics.pop().unwrap();
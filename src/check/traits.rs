@@ -0,0 +1,103 @@
+use super::{env::Env, env::ScopeKind, Check, CheckResult, CheckSess};
+use crate::{
+    ast,
+    error::{
+        diagnostic::{Diagnostic, Label},
+        SyntaxError,
+    },
+    hir::{self, const_value::ConstValue},
+    span::Span,
+    types::{TraitType, Type, TypeId},
+    workspace::{BindingInfoFlags, BindingInfoKind, ModuleId},
+};
+use ustr::{Ustr, UstrMap};
+
+// A trait's function signatures are checked against each of its implementations,
+// in the context of the trait's module, where `Self` refers to the implementing type
+#[derive(Debug, Clone)]
+pub(super) struct Trait {
+    pub module_id: ModuleId,
+    pub name: Ustr,
+    pub functions: Vec<ast::FunctionSig>,
+    pub span: Span,
+}
+
+impl Trait {
+    pub fn find_function(&self, name: Ustr) -> Option<&ast::FunctionSig> {
+        self.functions.iter().find(|sig| sig.name == Some(name))
+    }
+}
+
+impl Check for ast::TraitType {
+    fn check(&self, sess: &mut CheckSess, env: &mut Env, _expected_type: Option<TypeId>) -> CheckResult {
+        if self.name.is_empty() {
+            return Err(Diagnostic::error()
+                .with_message("traits must be named")
+                .with_label(Label::primary(self.span, "unnamed trait"))
+                .with_note("declare it with a name, i.e: `type Display = trait { ... }`"));
+        }
+
+        if sess.function_frame().is_some() {
+            return Err(Diagnostic::error()
+                .with_message(format!("trait `{}` must be declared at the top level", self.name))
+                .with_label(Label::primary(self.span, "declared inside of a function")));
+        }
+
+        // the trait's main type variable
+        let trait_type_var = sess.tcx.var(self.span);
+
+        // the trait's main type variable, in its `type` variation
+        let trait_type_type_var = sess.tcx.bound(trait_type_var.as_kind().create_type(), self.span);
+
+        // traits are nominal - their identity is the binding of their name
+        env.push_scope(ScopeKind::Block);
+
+        let (id, _) = sess.bind_name(
+            env,
+            self.name,
+            ast::Vis::Private,
+            trait_type_type_var,
+            Some(hir::Node::Const(hir::Const {
+                value: ConstValue::Type(trait_type_var),
+                ty: sess.tcx.common_types.anytype,
+                span: self.span,
+            })),
+            false,
+            BindingInfoKind::LetConst,
+            self.span,
+            BindingInfoFlags::empty(),
+        )?;
+
+        env.pop_scope();
+
+        let mut function_map = UstrMap::<Span>::default();
+
+        for sig in self.functions.iter() {
+            let name = sig.name.unwrap();
+
+            if let Some(defined_span) = function_map.insert(name, sig.span) {
+                return Err(SyntaxError::duplicate_binding(name, sig.span, defined_span));
+            }
+        }
+
+        sess.traits.insert(
+            id,
+            Trait {
+                module_id: env.module_id(),
+                name: self.name,
+                functions: self.functions.clone(),
+                span: self.span,
+            },
+        );
+
+        let trait_type = Type::Trait(TraitType { name: self.name, id });
+
+        sess.tcx.bind_ty(trait_type_var, trait_type.clone());
+
+        Ok(hir::Node::Const(hir::Const {
+            ty: sess.tcx.bound(trait_type.create_type(), self.span),
+            span: self.span,
+            value: ConstValue::Type(trait_type_var),
+        }))
+    }
+}
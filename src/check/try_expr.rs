@@ -0,0 +1,279 @@
+use super::{env::Env, env::ScopeKind, Check, CheckResult, CheckSess};
+use crate::{
+    ast,
+    error::{
+        diagnostic::{Diagnostic, Label},
+        SyntaxError,
+    },
+    hir::{
+        self,
+        const_value::{ConstEnum, ConstValue},
+    },
+    infer::{
+        coerce::OrCoerceIntoTy,
+        display::{DisplayType, OrReportErr},
+        normalize::Normalize,
+        unify::UnifyType,
+    },
+    span::Span,
+    sym,
+    types::{EnumType, Type, TypeId, ENUM_TAG_FIELD},
+    workspace::{BindingInfoFlags, BindingInfoKind},
+};
+use ustr::ustr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TryKind {
+    Option,
+    Result,
+}
+
+impl TryKind {
+    fn name(self) -> &'static str {
+        match self {
+            TryKind::Option => sym::OPTION,
+            TryKind::Result => sym::RESULT,
+        }
+    }
+
+    fn success_variant(self) -> &'static str {
+        match self {
+            TryKind::Option => sym::OPTION_SOME,
+            TryKind::Result => sym::RESULT_OK,
+        }
+    }
+
+    fn failure_variant(self) -> &'static str {
+        match self {
+            TryKind::Option => sym::OPTION_NONE,
+            TryKind::Result => sym::RESULT_ERR,
+        }
+    }
+}
+
+// `value?` is desugared into:
+// > let tmp = value
+// > if tmp.tag == <failure> { return <failure> }
+// > tmp.<success>.0
+// For an `Option`, the failure is `none`. For a `Result`, the failure is `err`, and its error is passed along.
+impl Check for ast::Try {
+    fn check(&self, sess: &mut CheckSess, env: &mut Env, _expected_type: Option<TypeId>) -> CheckResult {
+        let function_frame = sess
+            .function_frame()
+            .ok_or(SyntaxError::outside_of_function(self.span, "?"))?;
+
+        if let Some(defer_span) = function_frame.defer_span {
+            return Err(SyntaxError::inside_of_defer(self.span, defer_span, "?"));
+        }
+
+        let value_node = self.expr.check(sess, env, None)?;
+        let value_ty = value_node.ty();
+        let value_span = self.expr.span();
+
+        let (kind, value_enum) = match try_kind(sess, &value_ty.normalize(&sess.tcx)) {
+            Some(kind) => kind,
+            None => {
+                return Err(Diagnostic::error()
+                    .with_message(format!(
+                        "the `?` operator can only be applied to an `Option` or a `Result`, found `{}`",
+                        value_ty.display(&sess.tcx)
+                    ))
+                    .with_label(Label::primary(value_span, "cannot use `?` on this value")))
+            }
+        };
+
+        let return_type = function_frame.return_type;
+
+        // A function whose return type is inferred returns the same type as the value
+        if let Type::Var(_) = return_type.normalize(&sess.tcx) {
+            return_type.unify(&value_ty, &mut sess.tcx).or_report_err(
+                &sess.tcx,
+                &return_type,
+                Some(function_frame.return_type_span),
+                &value_ty,
+                value_span,
+            )?;
+        }
+
+        let return_enum = match try_kind(sess, &return_type.normalize(&sess.tcx)) {
+            Some((return_kind, return_enum)) if return_kind == kind => return_enum,
+            _ => {
+                return Err(Diagnostic::error()
+                    .with_message(format!(
+                        "the `?` operator can only be used in a function that returns `{}`",
+                        kind.name()
+                    ))
+                    .with_label(Label::primary(self.span, "cannot use `?` here"))
+                    .with_label(Label::secondary(
+                        function_frame.return_type_span,
+                        format!("this function returns `{}`", return_type.display(&sess.tcx)),
+                    )))
+            }
+        };
+
+        env.push_scope(ScopeKind::Block);
+
+        // bind the value to a local variable, so we evaluate the expression only once
+        let value_name = sess.generate_name("try");
+        let (value_id, value_binding) = sess.bind_name(
+            env,
+            value_name,
+            ast::Vis::Private,
+            value_ty,
+            Some(value_node),
+            false,
+            BindingInfoKind::LetConst,
+            value_span,
+            BindingInfoFlags::NO_CONST_FOLD,
+        )?;
+
+        env.pop_scope();
+
+        let value_id_node = hir::Node::Id(hir::Id {
+            id: value_id,
+            ty: value_ty,
+            span: value_span,
+        });
+
+        let (failure_index, _) = value_enum.variant_and_position(kind.failure_variant()).unwrap();
+        let (return_failure_index, return_failure_variant) =
+            return_enum.variant_and_position(kind.failure_variant()).unwrap();
+
+        let failure_value = match kind {
+            TryKind::Option => hir::Node::Const(hir::Const {
+                value: ConstValue::Enum(ConstEnum {
+                    variant_index: return_failure_index,
+                    variant_name: return_failure_variant.name,
+                }),
+                ty: return_type,
+                span: self.span,
+            }),
+            TryKind::Result => {
+                let mut error_node =
+                    payload_element(sess, &value_id_node, &value_enum, kind.failure_variant(), self.span);
+                let return_error_type = sess.tcx.bound(
+                    return_failure_variant.payload[0].clone(),
+                    function_frame.return_type_span,
+                );
+
+                error_node
+                    .ty()
+                    .unify(&return_error_type, &mut sess.tcx)
+                    .or_coerce_into_ty(
+                        &mut error_node,
+                        &return_error_type,
+                        &mut sess.tcx,
+                        sess.target_metrics.word_size,
+                    )
+                    .or_report_err(
+                        &sess.tcx,
+                        &return_error_type,
+                        Some(function_frame.return_type_span),
+                        &error_node.ty(),
+                        value_span,
+                    )?;
+
+                hir::Node::Literal(hir::Literal::Enum(hir::EnumLiteral {
+                    variant_index: return_failure_index as _,
+                    payload: Box::new(hir::Node::Literal(hir::Literal::Tuple(hir::TupleLiteral {
+                        elements: vec![error_node],
+                        ty: sess.tcx.bound(return_failure_variant.payload_type(), self.span),
+                        span: self.span,
+                    }))),
+                    ty: return_type,
+                    span: self.span,
+                }))
+            }
+        };
+
+        let u32_type = sess.tcx.common_types.u32;
+
+        let tag_node = hir::Node::MemberAccess(hir::MemberAccess {
+            value: Box::new(value_id_node.clone()),
+            member_name: ustr(ENUM_TAG_FIELD),
+            member_index: 0,
+            ty: u32_type,
+            span: self.span,
+        });
+
+        let early_return = hir::Node::Control(hir::Control::If(hir::If {
+            condition: Box::new(hir::Node::Builtin(hir::Builtin::Eq(hir::Binary {
+                lhs: Box::new(tag_node),
+                rhs: Box::new(hir::Node::Const(hir::Const {
+                    value: ConstValue::Int(failure_index as _),
+                    ty: u32_type,
+                    span: self.span,
+                })),
+                ty: sess.tcx.common_types.bool,
+                span: self.span,
+            }))),
+            then: Box::new(hir::Node::Control(hir::Control::Return(hir::Return {
+                value: Box::new(failure_value),
+                ty: sess.tcx.common_types.never,
+                span: self.span,
+            }))),
+            otherwise: None,
+            ty: sess.tcx.common_types.unit,
+            span: self.span,
+        }));
+
+        let success_node = payload_element(sess, &value_id_node, &value_enum, kind.success_variant(), self.span);
+        let success_ty = success_node.ty();
+
+        Ok(hir::Node::Sequence(hir::Sequence {
+            statements: vec![value_binding, early_return, success_node],
+            ty: success_ty,
+            span: self.span,
+            is_scope: true,
+        }))
+    }
+}
+
+// Returns whether `ty` is an instance of the standard library's `Option` or `Result`
+fn try_kind(sess: &CheckSess, ty: &Type) -> Option<(TryKind, EnumType)> {
+    let enum_type = match ty {
+        Type::Enum(enum_type) => enum_type,
+        _ => return None,
+    };
+
+    let binding_info = sess.workspace.binding_infos.get(enum_type.id?).unwrap();
+
+    if binding_info.module_id != sess.workspace.std_library().root_module_id {
+        return None;
+    }
+
+    let kind = match binding_info.name.as_str() {
+        sym::OPTION => TryKind::Option,
+        sym::RESULT => TryKind::Result,
+        _ => return None,
+    };
+
+    Some((kind, enum_type.clone()))
+}
+
+// Accesses the single payload value of `variant`, i.e: the `x` in `some(x)`
+fn payload_element(
+    sess: &mut CheckSess,
+    value: &hir::Node,
+    enum_type: &EnumType,
+    variant: &str,
+    span: Span,
+) -> hir::Node {
+    let (index, variant) = enum_type.variant_and_position(variant).unwrap();
+
+    let payload_node = hir::Node::MemberAccess(hir::MemberAccess {
+        value: Box::new(value.clone()),
+        member_name: variant.name,
+        member_index: index as u32 + 1,
+        ty: sess.tcx.bound(variant.payload_type(), span),
+        span,
+    });
+
+    hir::Node::MemberAccess(hir::MemberAccess {
+        value: Box::new(payload_node),
+        member_name: ustr("0"),
+        member_index: 0,
+        ty: sess.tcx.bound(variant.payload[0].clone(), span),
+        span,
+    })
+}
@@ -1,5 +1,8 @@
 use super::target::TargetPlatform;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone)]
 pub struct BuildOptions {
@@ -16,12 +19,19 @@ pub struct BuildOptions {
     /// The overall codegen optimization level
     pub optimization_level: OptimizationLevel,
 
+    /// Whether the workspace is built as an executable, or as a library that exports its `@export` functions
+    pub output_kind: OutputKind,
+
     pub diagnostic_options: DiagnosticOptions,
     pub codegen_options: CodegenOptions,
 
     /// Additional include paths
     pub include_paths: Vec<PathBuf>,
 
+    /// The linker to use instead of the default one.
+    /// Without it, building for a platform other than the current one outputs an object file instead of an executable
+    pub linker: Option<PathBuf>,
+
     /// Print timing information of every compiler pass
     pub emit_times: bool,
 
@@ -33,6 +43,18 @@ pub struct BuildOptions {
 
     /// Whether the workspace is running in check mode
     pub check_mode: bool,
+
+    /// In check mode, an offset in the root module to collect completion candidates for
+    pub completion_offset: Option<usize>,
+
+    /// In check mode, the unsaved contents of files that are open in an editor, which are checked instead of the files on disk
+    pub source_overrides: HashMap<PathBuf, String>,
+
+    /// Lazily check modules whose sources, and whose dependencies' public signatures, didn't change since the last compilation
+    pub incremental: bool,
+
+    /// Whether the workspace is built as a test runner, which runs all `@test` functions instead of `main`
+    pub test_mode: bool,
 }
 
 impl BuildOptions {
@@ -40,25 +62,45 @@ impl BuildOptions {
         self.source_file.parent().unwrap()
     }
 
+    /// Whether the target platform is different from the one the compiler is running on
+    pub fn is_cross_compiling(&self) -> bool {
+        TargetPlatform::current().map_or(true, |current| current != self.target_platform)
+    }
+
     pub fn need_entry_point_function(&self) -> bool {
-        matches!(self.codegen_options, CodegenOptions::Codegen { .. })
-            && matches!(
-                self.target_platform,
-                TargetPlatform::Windows386
-                    | TargetPlatform::WindowsAmd64
-                    | TargetPlatform::Linux386
-                    | TargetPlatform::LinuxAmd64
-                    | TargetPlatform::LinuxArm64
-                    | TargetPlatform::DarwinAmd64
-                    | TargetPlatform::DarwinArm64
-                    | TargetPlatform::FreeBSD386
-                    | TargetPlatform::FreeBSDAmd64
-                    | TargetPlatform::EssenceAmd64
-            )
+        // A library is only used through its exported functions
+        if self.output_kind.is_library() {
+            return false;
+        }
+
+        matches!(
+            self.codegen_options,
+            CodegenOptions::Codegen { .. } | CodegenOptions::Interp
+        ) && matches!(
+            self.target_platform,
+            TargetPlatform::Windows386
+                | TargetPlatform::WindowsAmd64
+                | TargetPlatform::Linux386
+                | TargetPlatform::LinuxAmd64
+                | TargetPlatform::LinuxArm64
+                | TargetPlatform::DarwinAmd64
+                | TargetPlatform::DarwinArm64
+                | TargetPlatform::FreeBSD386
+                | TargetPlatform::FreeBSDAmd64
+                | TargetPlatform::EssenceAmd64
+                | TargetPlatform::WasiWasm32
+        )
     }
 
-    pub fn start_function_name(&self) -> Option<&'static str> {
-        self.need_entry_point_function().then(|| match &self.target_platform {
+    /// The name of the function that initializes static variables, and runs the entry point function.
+    /// Libraries and targets without an entry point function get an `_initialize` function,
+    /// which only initializes static variables
+    pub fn start_function_name(&self) -> &'static str {
+        if self.output_kind.is_library() {
+            return "_initialize";
+        }
+
+        match &self.target_platform {
             TargetPlatform::Windows386
             | TargetPlatform::WindowsAmd64
             | TargetPlatform::Linux386
@@ -69,9 +111,9 @@ impl BuildOptions {
             | TargetPlatform::FreeBSD386
             | TargetPlatform::FreeBSDAmd64
             | TargetPlatform::EssenceAmd64 => "main",
-
-            p => panic!("unexpected TargetPlatform::{:?}", p),
-        })
+            TargetPlatform::WasiWasm32 => "_start",
+            TargetPlatform::FreestandingWasm32 | TargetPlatform::JsWasm32 => "_initialize",
+        }
     }
 }
 
@@ -93,6 +135,19 @@ impl OptimizationLevel {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    Executable,
+    StaticLibrary,
+    SharedLibrary,
+}
+
+impl OutputKind {
+    pub fn is_library(&self) -> bool {
+        matches!(self, OutputKind::StaticLibrary | OutputKind::SharedLibrary)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum DiagnosticOptions {
     Emit { no_color: bool },
@@ -103,6 +158,8 @@ pub enum DiagnosticOptions {
 pub enum CodegenOptions {
     Codegen { emit_llvm_ir: bool },
     Skip { emit_llvm_ir: bool },
+    // Code generation is skipped, and the entry point function is run in the bytecode VM instead
+    Interp,
 }
 
 impl CodegenOptions {
@@ -110,6 +167,7 @@ impl CodegenOptions {
         match self {
             CodegenOptions::Codegen { emit_llvm_ir } => *emit_llvm_ir,
             CodegenOptions::Skip { emit_llvm_ir } => *emit_llvm_ir,
+            CodegenOptions::Interp => false,
         }
     }
 }
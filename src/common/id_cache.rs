@@ -88,6 +88,10 @@ where
     pub fn get_mut(&mut self, id: I) -> Option<&mut V> {
         self.inner.get_mut(id.into())
     }
+
+    pub fn remove(&mut self, id: I) -> V {
+        self.inner.remove(id.into())
+    }
 }
 
 impl<I, V> IdCache<I, V>
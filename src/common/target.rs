@@ -2,41 +2,18 @@ use std::env;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TargetPlatform {
-    #[allow(unused)]
     Windows386,
-
     WindowsAmd64,
-
-    #[allow(unused)]
     Linux386,
-
     LinuxAmd64,
-
-    #[allow(unused)]
     LinuxArm64,
-
-    #[allow(unused)]
     DarwinAmd64,
-
-    #[allow(unused)]
     DarwinArm64,
-
-    #[allow(unused)]
     FreeBSD386,
-
-    #[allow(unused)]
     FreeBSDAmd64,
-
-    #[allow(unused)]
     EssenceAmd64,
-
-    #[allow(unused)]
     FreestandingWasm32,
-
-    #[allow(unused)]
     JsWasm32,
-
-    #[allow(unused)]
     WasiWasm32,
 }
 
@@ -116,7 +93,7 @@ impl TargetPlatform {
                 arch: Arch::Arm64,
                 word_size: 8,
                 max_align: 16,
-                target_triplet: "aarch64-linux-elf",
+                target_triplet: "aarch64-unknown-linux-gnu",
                 target_data_layout: "e-m:e-i8:8:32-i16:32-i64:64-i128:128-n32:64-S128",
             },
             TargetPlatform::DarwinAmd64 => TargetMetrics {
@@ -164,8 +141,8 @@ impl TargetPlatform {
                 arch: Arch::Wasm32,
                 word_size: 4,
                 max_align: 8,
-                target_triplet: "wasm32-freestanding-js",
-                target_data_layout: "",
+                target_triplet: "wasm32-unknown-unknown",
+                target_data_layout: "e-m:e-p:32:32-i64:64-n32:64-S128",
             },
             TargetPlatform::JsWasm32 => TargetMetrics {
                 os: Os::Js,
@@ -173,26 +150,102 @@ impl TargetPlatform {
                 word_size: 4,
                 max_align: 8,
                 target_triplet: "wasm32-js-js",
-                target_data_layout: "",
+                target_data_layout: "e-m:e-p:32:32-i64:64-n32:64-S128",
             },
             TargetPlatform::WasiWasm32 => TargetMetrics {
                 os: Os::Wasi,
                 arch: Arch::Wasm32,
                 word_size: 4,
                 max_align: 8,
-                target_triplet: "wasm32-wasi-js",
-                target_data_layout: "",
+                target_triplet: "wasm32-unknown-wasi",
+                target_data_layout: "e-m:e-p:32:32-i64:64-n32:64-S128",
             },
         }
     }
 
     pub fn current() -> Result<Self, &'static str> {
-        match env::consts::OS {
-            "linux" => Ok(Self::LinuxAmd64),
-            "windows" => Ok(Self::WindowsAmd64),
-            os => Err(os),
+        match (env::consts::OS, env::consts::ARCH) {
+            ("linux", "x86_64") => Ok(Self::LinuxAmd64),
+            ("linux", "x86") => Ok(Self::Linux386),
+            ("linux", "aarch64") => Ok(Self::LinuxArm64),
+            ("windows", "x86_64") => Ok(Self::WindowsAmd64),
+            ("windows", "x86") => Ok(Self::Windows386),
+            ("linux" | "windows", arch) => Err(arch),
+            (os, _) => Err(os),
         }
     }
+
+    pub fn all() -> &'static [TargetPlatform] {
+        &[
+            TargetPlatform::Windows386,
+            TargetPlatform::WindowsAmd64,
+            TargetPlatform::Linux386,
+            TargetPlatform::LinuxAmd64,
+            TargetPlatform::LinuxArm64,
+            TargetPlatform::DarwinAmd64,
+            TargetPlatform::DarwinArm64,
+            TargetPlatform::FreeBSD386,
+            TargetPlatform::FreeBSDAmd64,
+            TargetPlatform::EssenceAmd64,
+            TargetPlatform::FreestandingWasm32,
+            TargetPlatform::JsWasm32,
+            TargetPlatform::WasiWasm32,
+        ]
+    }
+
+    // The platform's name, which is made of its os and arch, i.e: `linux_amd64`
+    pub fn name(&self) -> String {
+        let metrics = self.metrics();
+        format!("{}_{}", metrics.os.name(), metrics.arch.name())
+    }
+
+    // Parses a platform's name (i.e: `linux_arm64`), or a target triple (i.e: `aarch64-unknown-linux-gnu`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+
+        Self::all()
+            .iter()
+            .find(|platform| platform.name() == name || platform.metrics().target_triplet == name)
+            .cloned()
+            .or_else(|| Self::from_triple(&name))
+    }
+
+    fn from_triple(triple: &str) -> Option<Self> {
+        let mut components = triple.split('-');
+
+        let arch = match components.next()? {
+            "x86_64" | "amd64" => Arch::Amd64,
+            "i386" | "i486" | "i586" | "i686" | "x86" => Arch::_386,
+            "aarch64" | "arm64" => Arch::Arm64,
+            "wasm32" => Arch::Wasm32,
+            "wasm64" => Arch::Wasm64,
+            _ => return None,
+        };
+
+        // The vendor and environment components are optional, so the os is the first component that names one.
+        // A wasm triple without an os, such as `wasm32-unknown-unknown`, targets no os at all
+        let os = components
+            .find_map(|component| match component {
+                "linux" => Some(Os::Linux),
+                "windows" | "win32" => Some(Os::Windows),
+                "essence" => Some(Os::Essence),
+                "wasi" => Some(Os::Wasi),
+                "js" => Some(Os::Js),
+                "freestanding" => Some(Os::Freestanding),
+                _ if component.starts_with("darwin") || component.starts_with("macos") => Some(Os::Darwin),
+                _ if component.starts_with("freebsd") => Some(Os::FreeBSD),
+                _ => None,
+            })
+            .or_else(|| arch.is_wasm().then(|| Os::Freestanding))?;
+
+        Self::all()
+            .iter()
+            .find(|platform| {
+                let metrics = platform.metrics();
+                metrics.os == os && metrics.arch == arch
+            })
+            .cloned()
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -218,7 +271,6 @@ pub enum Os {
 }
 
 impl Os {
-    #[allow(unused)]
     pub fn name(&self) -> &'static str {
         match self {
             Os::Windows => "windows",
@@ -239,8 +291,6 @@ pub enum Arch {
     _386,
     Arm64,
     Wasm32,
-
-    #[allow(unused)]
     Wasm64,
 }
 
@@ -255,6 +305,10 @@ impl Arch {
         }
     }
 
+    pub fn is_wasm(&self) -> bool {
+        matches!(self, Arch::Wasm32 | Arch::Wasm64)
+    }
+
     #[allow(unused)]
     pub fn endianness(&self) -> Endianness {
         match self {
@@ -7,7 +7,9 @@ use crate::{
     },
     error::diagnostic::Diagnostic,
     hir,
+    incremental::ModuleCache,
     infer::type_ctx::TypeCtx,
+    interp::interp::Interp,
     time,
     workspace::{library::Library, LibraryId, ModuleId, Workspace},
 };
@@ -84,14 +86,26 @@ pub fn start_workspace(name: String, build_options: BuildOptions) -> StartWorksp
         crate::astgen::generate_ast(&mut workspace)
     }};
 
-    if workspace.diagnostics.has_errors() {
+    // In check mode, modules which recovered from syntax errors are still checked,
+    // so that language support features (such as completions) keep working while the user is typing
+    let can_check_with_syntax_errors =
+        workspace.build_options.check_mode && modules.len() == workspace.module_infos.len();
+
+    if workspace.diagnostics.has_errors() && !can_check_with_syntax_errors {
         workspace.emit_diagnostics();
         return StartWorkspaceResult::new_untyped(workspace);
     }
 
+    // The module cache of the last compilation is used to skip checking modules which didn't change
+    let module_cache = if workspace.build_options.incremental {
+        ModuleCache::load(&workspace.build_options)
+    } else {
+        None
+    };
+
     // Type inference, type checking, static analysis, const folding, etc..
     let (cache, tcx) = time! { workspace.build_options.emit_times, "check", {
-        crate::check::check(&mut workspace, modules)
+        crate::check::check(&mut workspace, modules, module_cache.as_ref())
     }};
 
     if workspace.diagnostics.has_errors() {
@@ -113,6 +127,11 @@ pub fn start_workspace(name: String, build_options: BuildOptions) -> StartWorksp
         return StartWorkspaceResult::new_typed(workspace, tcx, cache);
     }
 
+    // The cache is only updated when the workspace is free of errors, so that erroneous modules are always re-checked
+    if workspace.build_options.incremental {
+        ModuleCache::from_workspace(&workspace, &tcx, &cache, module_cache.as_ref()).save(&workspace.build_options);
+    }
+
     // Code generation
     match &workspace.build_options.codegen_options {
         CodegenOptions::Codegen { .. } => {
@@ -124,6 +143,24 @@ pub fn start_workspace(name: String, build_options: BuildOptions) -> StartWorksp
 
             StartWorkspaceResult::new_typed_with_output(workspace, tcx, cache, output_file)
         }
+        CodegenOptions::Interp => {
+            let mut interp = Interp::new(workspace.build_options.clone());
+
+            let result = time! { workspace.build_options.emit_times, "interp",
+                interp.create_session(&workspace, &tcx, &cache).run_entry_point()
+            };
+
+            if let Err(diagnostics) = result {
+                workspace.diagnostics.extend(diagnostics);
+                workspace.emit_diagnostics();
+            }
+
+            if workspace.build_options.emit_times {
+                print_stats(stats, all_sw.unwrap().elapsed().as_millis());
+            }
+
+            StartWorkspaceResult::new_typed(workspace, tcx, cache)
+        }
         _ => {
             if workspace.build_options.emit_times {
                 print_stats(stats, all_sw.unwrap().elapsed().as_millis());
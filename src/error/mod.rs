@@ -107,6 +107,16 @@ impl SyntaxError {
             ))
     }
 
+    pub fn duplicate_enum_variant(defined_variant_span: Span, variant_span: Span, variant_name: String) -> Diagnostic {
+        Diagnostic::error()
+            .with_message(format!("enum variant `{}` is defined more than once", variant_name))
+            .with_label(Label::primary(variant_span, "variant defined more than once"))
+            .with_label(Label::secondary(
+                defined_variant_span,
+                format!("previous definition of `{}` here", variant_name),
+            ))
+    }
+
     pub fn outside_of_loop(span: Span, word: &str) -> Diagnostic {
         let msg = format!("`{}` outside of loop", word);
         Diagnostic::error()
@@ -119,6 +129,13 @@ impl SyntaxError {
             .with_message(format!("`{}` outside of function", word))
             .with_label(Label::primary(span, ""))
     }
+
+    pub fn inside_of_defer(span: Span, defer_span: Span, word: &str) -> Diagnostic {
+        Diagnostic::error()
+            .with_message(format!("cannot use `{}` in a deferred expression", word))
+            .with_label(Label::primary(span, format!("cannot `{}` here", word)))
+            .with_label(Label::secondary(defer_span, "the deferred expression starts here"))
+    }
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -57,6 +57,9 @@ pub enum AttrKind {
     Dylib,
     LinkName,
     TrackCaller,
+    Test,
+    Copy,
+    Export,
 }
 
 pub const ATTR_NAME_INTRINSIC: &str = "intrinsic";
@@ -64,6 +67,9 @@ pub const ATTR_NAME_LIB: &str = "lib";
 pub const ATTR_NAME_DYLIB: &str = "dylib";
 pub const ATTR_NAME_LINK_NAME: &str = "link_name";
 pub const ATTR_NAME_TRACK_CALLER: &str = "track_caller";
+pub const ATTR_NAME_TEST: &str = "test";
+pub const ATTR_NAME_COPY: &str = "copy";
+pub const ATTR_NAME_EXPORT: &str = "export";
 
 impl TryFrom<&str> for AttrKind {
     type Error = ();
@@ -75,6 +81,9 @@ impl TryFrom<&str> for AttrKind {
             ATTR_NAME_DYLIB => Ok(AttrKind::Dylib),
             ATTR_NAME_LINK_NAME => Ok(AttrKind::LinkName),
             ATTR_NAME_TRACK_CALLER => Ok(AttrKind::TrackCaller),
+            ATTR_NAME_TEST => Ok(AttrKind::Test),
+            ATTR_NAME_COPY => Ok(AttrKind::Copy),
+            ATTR_NAME_EXPORT => Ok(AttrKind::Export),
             _ => Err(()),
         }
     }
@@ -91,6 +100,9 @@ impl Display for AttrKind {
                 AttrKind::Dylib => ATTR_NAME_DYLIB,
                 AttrKind::LinkName => ATTR_NAME_LINK_NAME,
                 AttrKind::TrackCaller => ATTR_NAME_TRACK_CALLER,
+                AttrKind::Test => ATTR_NAME_TEST,
+                AttrKind::Copy => ATTR_NAME_COPY,
+                AttrKind::Export => ATTR_NAME_EXPORT,
             }
         )
     }
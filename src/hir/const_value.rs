@@ -21,6 +21,7 @@ pub enum ConstValue {
     Array(ConstArray),
     Tuple(Vec<ConstElement>),
     Struct(ConstStruct),
+    Enum(ConstEnum),
     Function(ConstFunction),
     ExternVariable(ConstExternVariable),
 }
@@ -39,6 +40,14 @@ pub struct ConstArray {
     pub element_type: TypeId,
 }
 
+// Only variants without a payload can be constant
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConstEnum {
+    pub variant_index: usize,
+    // Name is only used for display purposes
+    pub variant_name: Ustr,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ConstFunction {
     pub id: FunctionId,
@@ -99,6 +108,7 @@ impl ConstValue {
                         lname == rname && *left.value.eq(&right.value).as_bool().unwrap()
                     })
             }
+            (Self::Enum(left), Self::Enum(right)) => left.variant_index == right.variant_index,
             (Self::Function(left), Self::Function(right)) => left.id == right.id,
             _ => false,
         })
@@ -297,25 +307,33 @@ impl ConstValue {
                     .collect::<Vec<String>>()
                     .join(", "),
             ),
+            Self::Enum(e) => e.variant_name.to_string(),
             Self::Function(f) => f.name.to_string(),
             Self::ExternVariable(v) => v.name.to_string(),
         }
     }
 
+    // These values must match the `Os_*` and `Arch_*` constants in `std.intrinsics`
     pub fn from_os(os: Os) -> Self {
         Self::Int(match os {
             Os::Linux => 0,
             Os::Windows => 1,
-            Os::Darwin | Os::Essence | Os::FreeBSD | Os::Wasi | Os::Js | Os::Freestanding => {
-                todo!("{}", os.name())
-            }
+            Os::Darwin => 2,
+            Os::FreeBSD => 3,
+            Os::Essence => 4,
+            Os::Wasi => 5,
+            Os::Js => 6,
+            Os::Freestanding => 7,
         })
     }
 
     pub fn from_arch(arch: Arch) -> Self {
         Self::Int(match arch {
             Arch::Amd64 => 0,
-            Arch::_386 | Arch::Arm64 | Arch::Wasm32 | Arch::Wasm64 => todo!("{}", arch.name()),
+            Arch::_386 => 1,
+            Arch::Arm64 => 2,
+            Arch::Wasm32 => 3,
+            Arch::Wasm64 => 4,
         })
     }
 }
@@ -12,8 +12,11 @@ use crate::{
     workspace::{BindingId, ModuleId},
 };
 use enum_as_inner::EnumAsInner;
-use std::{collections::HashMap, fmt::Display};
-use ustr::Ustr;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+use ustr::{Ustr, UstrMap};
 
 macro_rules! node_struct {
     ($name:ident) => {
@@ -44,6 +47,21 @@ pub struct Cache {
 
     // The entry point function's id (usually named "main"). Resolved during semantic analysis
     pub entry_point_function_id: Option<FunctionId>,
+
+    // The functions marked with `@test`, in the order they were declared. Collected during semantic analysis
+    pub test_functions: Vec<FunctionId>,
+
+    // The functions marked with `@export`, and the names they are exported under. Collected during semantic analysis
+    pub exported_functions: Vec<(FunctionId, Ustr)>,
+
+    // The checked associated functions of each named struct type, keyed by the type's binding
+    pub associated_functions: HashMap<BindingId, UstrMap<BindingId>>,
+
+    // The expression found at `BuildOptions::completion_offset`, if there is one. Resolved during semantic analysis
+    pub completion_site: Option<CompletionSite>,
+
+    // Modules which didn't change since the last compilation, and had only their used bindings checked
+    pub lazily_checked_modules: HashSet<ModuleId>,
 }
 
 impl Cache {
@@ -52,6 +70,11 @@ impl Cache {
             bindings: HashMap::new(),
             functions: IdCache::new(),
             entry_point_function_id: None,
+            test_functions: vec![],
+            exported_functions: vec![],
+            associated_functions: HashMap::new(),
+            completion_site: None,
+            lazily_checked_modules: HashSet::new(),
         }
     }
 
@@ -60,6 +83,21 @@ impl Cache {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompletionSite {
+    pub kind: CompletionSiteKind,
+    // The span of the name being completed
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CompletionSiteKind {
+    // A member access, i.e: `foo.ba`. Holds the type of the accessed value
+    Member(TypeId),
+    // An identifier, i.e: `fo`. Holds all bindings visible from the identifier's scope
+    Scope(Vec<BindingId>),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Function {
     pub id: FunctionId,
@@ -127,6 +165,8 @@ pub enum Intrinsic {
     Arch,
     CompilerError,
     CompilerWarning,
+    Print,
+    Println,
 }
 
 pub const INTRINSIC_NAME_START_WORKSPACE: &str = "start_workspace";
@@ -136,6 +176,8 @@ pub const INTRINSIC_NAME_OS: &str = "os";
 pub const INTRINSIC_NAME_ARCH: &str = "arch";
 pub const INTRINSIC_NAME_COMPILER_ERROR: &str = "compiler_error";
 pub const INTRINSIC_NAME_COMPILER_WARNING: &str = "compiler_warning";
+pub const INTRINSIC_NAME_PRINT: &str = "print";
+pub const INTRINSIC_NAME_PRINTLN: &str = "println";
 
 impl TryFrom<&str> for Intrinsic {
     type Error = ();
@@ -149,6 +191,8 @@ impl TryFrom<&str> for Intrinsic {
             INTRINSIC_NAME_ARCH => Ok(Intrinsic::Arch),
             INTRINSIC_NAME_COMPILER_ERROR => Ok(Intrinsic::CompilerError),
             INTRINSIC_NAME_COMPILER_WARNING => Ok(Intrinsic::CompilerWarning),
+            INTRINSIC_NAME_PRINT => Ok(Intrinsic::Print),
+            INTRINSIC_NAME_PRINTLN => Ok(Intrinsic::Println),
             _ => Err(()),
         }
     }
@@ -167,6 +211,8 @@ impl Display for Intrinsic {
                 Intrinsic::Arch => INTRINSIC_NAME_ARCH,
                 Intrinsic::CompilerError => INTRINSIC_NAME_COMPILER_ERROR,
                 Intrinsic::CompilerWarning => INTRINSIC_NAME_COMPILER_WARNING,
+                Intrinsic::Print => INTRINSIC_NAME_PRINT,
+                Intrinsic::Println => INTRINSIC_NAME_PRINTLN,
             }
         )
     }
@@ -204,6 +250,8 @@ node_struct!(Sequence, { statements: Vec<Node>, is_scope: bool });
 node_struct!(If, { condition: Box<Node>, then: Box<Node>, otherwise: Option<Box<Node>> });
 node_struct!(While, { condition: Box<Node>, body: Box<Node> });
 node_struct!(Return, { value: Box<Node> });
+// An expression that runs when its enclosing scope exits, in reverse order of declaration
+node_struct!(Defer, { expr: Box<Node> });
 
 node_struct!(Binary, { lhs: Box<Node>, rhs: Box<Node> });
 node_struct!(Unary, { value: Box<Node> });
@@ -218,6 +266,11 @@ node_struct!(StructLiteralField, { name: Ustr, value: Box<Node> });
 node_struct!(TupleLiteral, { elements: Vec<Node> });
 node_struct!(ArrayLiteral, { elements: Vec<Node> });
 node_struct!(ArrayFillLiteral, { value: Box<Node>, len: usize });
+// Constructs an enum variant with a payload, which is a tuple of the payload's values
+node_struct!(EnumLiteral, { variant_index: u32, payload: Box<Node> });
+// Pairs a function with its captured environment, which is moved to the heap when the closure is created.
+// A closure without an environment is a plain function
+node_struct!(ClosureLiteral, { function: Box<Node>, env: Option<Box<Node>> });
 
 #[derive(Debug, PartialEq, Clone, EnumAsInner)]
 pub enum Control {
@@ -226,6 +279,7 @@ pub enum Control {
     Return(Return),
     Break(Empty),
     Continue(Empty),
+    Defer(Defer),
 }
 
 #[derive(Debug, PartialEq, Clone, EnumAsInner)]
@@ -269,6 +323,8 @@ pub enum Literal {
     Tuple(TupleLiteral),
     Array(ArrayLiteral),
     ArrayFill(ArrayFillLiteral),
+    Enum(EnumLiteral),
+    Closure(ClosureLiteral),
 }
 
 impl Node {
@@ -316,6 +372,7 @@ impl Control {
             Self::Return(x) => x.ty,
             Self::Break(x) => x.ty,
             Self::Continue(x) => x.ty,
+            Self::Defer(x) => x.ty,
         }
     }
 
@@ -327,6 +384,7 @@ impl Control {
             Self::Return(x) => x.span,
             Self::Break(x) => x.span,
             Self::Continue(x) => x.span,
+            Self::Defer(x) => x.span,
         }
     }
 }
@@ -401,6 +459,8 @@ impl Literal {
             Self::Tuple(x) => x.ty,
             Self::Array(x) => x.ty,
             Self::ArrayFill(x) => x.ty,
+            Self::Enum(x) => x.ty,
+            Self::Closure(x) => x.ty,
         }
     }
 
@@ -411,6 +471,8 @@ impl Literal {
             Self::Tuple(x) => x.span,
             Self::Array(x) => x.span,
             Self::ArrayFill(x) => x.span,
+            Self::Enum(x) => x.span,
+            Self::Closure(x) => x.span,
         }
     }
 }
@@ -256,6 +256,7 @@ impl<'a, W: Write> Print<'a, W> for ConstValue {
                 p.dedent();
                 p.write_indented(")", true);
             }
+            ConstValue::Enum(e) => p.write_indented(&e.variant_name, is_line_start),
             ConstValue::Function(f) => p.write_indented(&f.name, is_line_start),
             ConstValue::ExternVariable(v) => p.write_indented(&v.name, is_line_start),
         }
@@ -361,6 +362,10 @@ impl<'a, W: Write> Print<'a, W> for hir::Control {
             }
             hir::Control::Break(_) => p.write_indented("break", is_line_start),
             hir::Control::Continue(_) => p.write_indented("continue", is_line_start),
+            hir::Control::Defer(defer) => {
+                p.write_indented("defer ", is_line_start);
+                defer.expr.print(p, false);
+            }
         }
     }
 }
@@ -493,6 +498,25 @@ impl<'a, W: Write> Print<'a, W> for hir::Literal {
 
                 p.write("]");
             }
+            hir::Literal::Enum(lit) => {
+                let ty = lit.ty.normalize(p.tcx);
+                let variant = &ty.as_enum().variants[lit.variant_index as usize];
+
+                p.write_indented(&variant.name, is_line_start);
+                lit.payload.print(p, false);
+            }
+            hir::Literal::Closure(lit) => {
+                p.write_indented("closure(", is_line_start);
+                lit.function.print(p, false);
+                p.write(", ");
+
+                match &lit.env {
+                    Some(env) => env.print(p, false),
+                    None => p.write("null"),
+                }
+
+                p.write(")");
+            }
         }
     }
 }
@@ -85,7 +85,12 @@ impl<'a> CollectHints<'a> for hir::Binding {
 
         if should_show_hint {
             match binding_info.ty.normalize(sess.tcx) {
-                Type::Function(_) | Type::Module(_) | Type::Type(_) | Type::AnyType => (),
+                Type::Function(_)
+                | Type::Closure(_)
+                | Type::Module(_)
+                | Type::Trait(_)
+                | Type::Type(_)
+                | Type::AnyType => (),
                 ty => sess.push_hint(self.span, ty.display(&sess.tcx), HintKind::Binding),
             }
         }
@@ -200,6 +205,7 @@ impl<'a> CollectHints<'a> for hir::Control {
             hir::Control::If(x) => x.collect_hints(sess),
             hir::Control::While(x) => x.collect_hints(sess),
             hir::Control::Return(x) => x.collect_hints(sess),
+            hir::Control::Defer(x) => x.collect_hints(sess),
             hir::Control::Break(_) | hir::Control::Continue(_) => (),
         }
     }
@@ -226,6 +232,12 @@ impl<'a> CollectHints<'a> for hir::Return {
     }
 }
 
+impl<'a> CollectHints<'a> for hir::Defer {
+    fn collect_hints(&self, sess: &mut HintSess<'a>) {
+        self.expr.collect_hints(sess);
+    }
+}
+
 impl<'a> CollectHints<'a> for hir::Builtin {
     fn collect_hints(&self, sess: &mut HintSess<'a>) {
         match self {
@@ -310,6 +322,13 @@ impl<'a> CollectHints<'a> for hir::Literal {
             hir::Literal::ArrayFill(lit) => {
                 lit.value.collect_hints(sess);
             }
+            hir::Literal::Enum(lit) => {
+                lit.payload.collect_hints(sess);
+            }
+            hir::Literal::Closure(lit) => {
+                lit.function.collect_hints(sess);
+                lit.env.collect_hints(sess);
+            }
         }
     }
 }
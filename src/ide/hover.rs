@@ -0,0 +1,357 @@
+use super::types::HoverInfo;
+use crate::{
+    ast, hir,
+    hir::const_value::ConstValue,
+    infer::{display::DisplayType, normalize::Normalize, type_ctx::TypeCtx},
+    span::{FileId, Span},
+    types::{EnumType, FunctionType, StructType, Type},
+    workspace::{BindingInfo, BindingInfoFlags, BindingInfoKind, Workspace},
+};
+
+pub(super) fn find_hover_info(
+    workspace: &Workspace,
+    tcx: &TypeCtx,
+    cache: Option<&hir::Cache>,
+    file_id: FileId,
+    offset: usize,
+) -> Option<HoverInfo> {
+    let is_offset_in_span = |span: Span| span.file_id == file_id && span.contains(offset);
+
+    let binding_infos = || workspace.binding_infos.iter().map(|(_, binding_info)| binding_info);
+
+    // Names of bindings are resolved first - this includes uses of constants, which are folded into `Const` nodes
+    let binding_info = binding_infos()
+        .find(|binding_info| binding_info.is_is_user_defined() && is_offset_in_span(binding_info.span))
+        .or_else(|| binding_infos().find(|binding_info| binding_info.uses.iter().any(|&span| is_offset_in_span(span))));
+
+    if let Some(binding_info) = binding_info {
+        return Some(binding_hover_info(tcx, binding_info));
+    }
+
+    let cache = cache?;
+
+    let mut sess = FindNodeSess {
+        file_id,
+        offset,
+        node: None,
+    };
+
+    cache.bindings.values().for_each(|binding| binding.find_node(&mut sess));
+    cache
+        .functions
+        .iter()
+        .for_each(|(_, function)| function.find_node(&mut sess));
+
+    sess.node.map(|node| node_hover_info(workspace, tcx, cache, node))
+}
+
+fn binding_hover_info(tcx: &TypeCtx, binding_info: &BindingInfo) -> HoverInfo {
+    let ty = binding_info.ty.normalize(tcx);
+    let vis = match binding_info.vis {
+        ast::Vis::Public => "pub ",
+        ast::Vis::Private => "",
+    };
+    let name = binding_info.name;
+
+    let signature = match binding_info.kind {
+        BindingInfoKind::LetConst | BindingInfoKind::LetStatic => format!(
+            "{}let {}{}: {}",
+            vis,
+            if binding_info.is_mutable { "mut " } else { "" },
+            name,
+            ty.display(tcx)
+        ),
+        BindingInfoKind::Function | BindingInfoKind::Intrinsic(_) => match &ty {
+            Type::Function(function_type) => {
+                format!("{}{}", vis, function_signature(tcx, name.as_str(), function_type))
+            }
+            ty => format!("{}fn {}: {}", vis, name, ty.display(tcx)),
+        },
+        BindingInfoKind::ExternFunction => match &ty {
+            Type::Function(function_type) => format!(
+                "{}extern {}",
+                vis,
+                function_signature(tcx, name.as_str(), function_type)
+            ),
+            ty => format!("{}extern fn {}: {}", vis, name, ty.display(tcx)),
+        },
+        BindingInfoKind::ExternVariable => format!(
+            "{}extern let {}{}: {}",
+            vis,
+            if binding_info.is_mutable { "mut " } else { "" },
+            name,
+            ty.display(tcx)
+        ),
+        BindingInfoKind::Type => match &ty {
+            Type::Type(inner) => format!("{}type {} = {}", vis, name, type_definition(tcx, inner)),
+            ty => format!("{}type {} = {}", vis, name, ty.display(tcx)),
+        },
+    };
+
+    // Functions and types are already described by their signature
+    let const_value = match &binding_info.const_value {
+        Some(ConstValue::Function(_)) | Some(ConstValue::Type(_)) | None => None,
+        Some(const_value) if !binding_info.flags.contains(BindingInfoFlags::NO_CONST_FOLD) => {
+            Some(const_value.display(tcx))
+        }
+        Some(_) => None,
+    };
+
+    HoverInfo {
+        contents: match &const_value {
+            Some(const_value) => format!("{} = {}", signature, const_value),
+            None => signature.clone(),
+        },
+        type_name: ty.display(tcx),
+        signature: Some(signature),
+        visibility: Some(
+            match binding_info.vis {
+                ast::Vis::Public => "public",
+                ast::Vis::Private => "private",
+            }
+            .to_string(),
+        ),
+        is_mutable: Some(binding_info.is_mutable),
+        const_value,
+    }
+}
+
+fn node_hover_info(workspace: &Workspace, tcx: &TypeCtx, cache: &hir::Cache, node: &hir::Node) -> HoverInfo {
+    let type_name = node.ty().display(tcx);
+
+    match node {
+        hir::Node::Binding(hir::Binding { id, .. }) | hir::Node::Id(hir::Id { id, .. }) => {
+            binding_hover_info(tcx, workspace.binding_infos.get(*id).unwrap())
+        }
+        hir::Node::MemberAccess(access) => {
+            let signature = format!("{}: {}", access.member_name, type_name);
+
+            HoverInfo {
+                contents: signature.clone(),
+                type_name,
+                signature: Some(signature),
+                visibility: None,
+                is_mutable: None,
+                const_value: None,
+            }
+        }
+        hir::Node::Call(call) => {
+            // The callee's signature is shown, since the call itself is only described by its return type
+            let signature = match call.callee.as_ref() {
+                hir::Node::Const(hir::Const {
+                    value: ConstValue::Function(function),
+                    ..
+                }) => cache
+                    .functions
+                    .get(function.id)
+                    .map(|function| match function.ty.normalize(tcx) {
+                        Type::Function(function_type) => {
+                            function_signature(tcx, function.name.as_str(), &function_type)
+                        }
+                        ty => ty.display(tcx),
+                    }),
+                hir::Node::Id(id) => binding_hover_info(tcx, workspace.binding_infos.get(id.id).unwrap()).signature,
+                callee => Some(callee.ty().display(tcx)),
+            };
+
+            HoverInfo {
+                contents: signature.clone().unwrap_or_else(|| type_name.clone()),
+                type_name,
+                signature,
+                visibility: None,
+                is_mutable: None,
+                const_value: None,
+            }
+        }
+        hir::Node::Const(hir::Const { value, .. }) => {
+            let const_value = match value {
+                ConstValue::Type(ty) => type_definition(tcx, &ty.normalize(tcx)),
+                value => value.display(tcx),
+            };
+
+            HoverInfo {
+                contents: format!("{}: {}", const_value, type_name),
+                type_name,
+                signature: None,
+                visibility: None,
+                is_mutable: None,
+                const_value: Some(const_value),
+            }
+        }
+        _ => HoverInfo {
+            contents: type_name.clone(),
+            type_name,
+            signature: None,
+            visibility: None,
+            is_mutable: None,
+            const_value: None,
+        },
+    }
+}
+
+fn function_signature(tcx: &TypeCtx, name: &str, function_type: &FunctionType) -> String {
+    // A function type is displayed as `fn(...) -> ...`, so the name is inserted right after the `fn` keyword
+    let display = function_type.display(tcx);
+    format!("fn {}{}", name, display.strip_prefix("fn").unwrap_or(&display))
+}
+
+// Named struct and enum types are displayed by their name, so their members are displayed explicitly
+fn type_definition(tcx: &TypeCtx, ty: &Type) -> String {
+    match ty {
+        Type::Struct(struct_type) if struct_type.id.is_some() => StructType {
+            id: None,
+            ..struct_type.clone()
+        }
+        .display(tcx),
+        Type::Enum(enum_type) if enum_type.id.is_some() => EnumType {
+            id: None,
+            ..enum_type.clone()
+        }
+        .display(tcx),
+        ty => ty.display(tcx),
+    }
+}
+
+struct FindNodeSess<'a> {
+    file_id: FileId,
+    offset: usize,
+    node: Option<&'a hir::Node>,
+}
+
+trait FindNode<'a> {
+    fn find_node(&'a self, sess: &mut FindNodeSess<'a>);
+}
+
+impl<'a, T: FindNode<'a>> FindNode<'a> for Vec<T> {
+    fn find_node(&'a self, sess: &mut FindNodeSess<'a>) {
+        for element in self {
+            element.find_node(sess);
+        }
+    }
+}
+
+impl<'a, T: FindNode<'a>> FindNode<'a> for Option<T> {
+    fn find_node(&'a self, sess: &mut FindNodeSess<'a>) {
+        if let Some(x) = self {
+            x.find_node(sess);
+        }
+    }
+}
+
+impl<'a, T: FindNode<'a>> FindNode<'a> for Box<T> {
+    fn find_node(&'a self, sess: &mut FindNodeSess<'a>) {
+        self.as_ref().find_node(sess);
+    }
+}
+
+impl<'a> FindNode<'a> for hir::Node {
+    fn find_node(&'a self, sess: &mut FindNodeSess<'a>) {
+        let span = self.span();
+
+        if span.file_id != sess.file_id || !span.contains(sess.offset) {
+            return;
+        }
+
+        // Children are visited after their parent, so the innermost node wins
+        let is_innermost = sess
+            .node
+            .map_or(true, |node| span.range().len() <= node.span().range().len());
+
+        if is_innermost {
+            sess.node = Some(self);
+        }
+
+        match self {
+            hir::Node::Const(_) | hir::Node::Id(_) => (),
+            hir::Node::Binding(x) => x.value.find_node(sess),
+            hir::Node::Assign(x) => {
+                x.lhs.find_node(sess);
+                x.rhs.find_node(sess);
+            }
+            hir::Node::MemberAccess(x) => x.value.find_node(sess),
+            hir::Node::Call(x) => {
+                x.callee.find_node(sess);
+                x.args.find_node(sess);
+            }
+            hir::Node::Cast(x) => x.value.find_node(sess),
+            hir::Node::Sequence(x) => x.statements.find_node(sess),
+            hir::Node::Control(x) => match x {
+                hir::Control::If(x) => {
+                    x.condition.find_node(sess);
+                    x.then.find_node(sess);
+                    x.otherwise.find_node(sess);
+                }
+                hir::Control::While(x) => {
+                    x.condition.find_node(sess);
+                    x.body.find_node(sess);
+                }
+                hir::Control::Return(x) => x.value.find_node(sess),
+                hir::Control::Defer(x) => x.expr.find_node(sess),
+                hir::Control::Break(_) | hir::Control::Continue(_) => (),
+            },
+            hir::Node::Builtin(x) => match x {
+                hir::Builtin::Add(x)
+                | hir::Builtin::Sub(x)
+                | hir::Builtin::Mul(x)
+                | hir::Builtin::Div(x)
+                | hir::Builtin::Rem(x)
+                | hir::Builtin::Shl(x)
+                | hir::Builtin::Shr(x)
+                | hir::Builtin::And(x)
+                | hir::Builtin::Or(x)
+                | hir::Builtin::Lt(x)
+                | hir::Builtin::Le(x)
+                | hir::Builtin::Gt(x)
+                | hir::Builtin::Ge(x)
+                | hir::Builtin::Eq(x)
+                | hir::Builtin::Ne(x)
+                | hir::Builtin::BitAnd(x)
+                | hir::Builtin::BitOr(x)
+                | hir::Builtin::BitXor(x) => {
+                    x.lhs.find_node(sess);
+                    x.rhs.find_node(sess);
+                }
+                hir::Builtin::Not(x) | hir::Builtin::Neg(x) | hir::Builtin::Deref(x) => x.value.find_node(sess),
+                hir::Builtin::Ref(x) => x.value.find_node(sess),
+                hir::Builtin::Offset(x) => {
+                    x.value.find_node(sess);
+                    x.index.find_node(sess);
+                }
+                hir::Builtin::Slice(x) => {
+                    x.value.find_node(sess);
+                    x.low.find_node(sess);
+                    x.high.find_node(sess);
+                }
+            },
+            hir::Node::Literal(x) => match x {
+                hir::Literal::Struct(lit) => {
+                    for field in lit.fields.iter() {
+                        field.value.find_node(sess);
+                    }
+                }
+                hir::Literal::Tuple(lit) => lit.elements.find_node(sess),
+                hir::Literal::Array(lit) => lit.elements.find_node(sess),
+                hir::Literal::ArrayFill(lit) => lit.value.find_node(sess),
+                hir::Literal::Enum(lit) => lit.payload.find_node(sess),
+                hir::Literal::Closure(lit) => {
+                    lit.function.find_node(sess);
+                    lit.env.find_node(sess);
+                }
+            },
+        }
+    }
+}
+
+impl<'a> FindNode<'a> for hir::Binding {
+    fn find_node(&'a self, sess: &mut FindNodeSess<'a>) {
+        self.value.find_node(sess);
+    }
+}
+
+impl<'a> FindNode<'a> for hir::Function {
+    fn find_node(&'a self, sess: &mut FindNodeSess<'a>) {
+        if let hir::FunctionKind::Orphan { body: Some(body), .. } = &self.kind {
+            body.statements.find_node(sess);
+        }
+    }
+}
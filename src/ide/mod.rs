@@ -1,21 +1,73 @@
 mod hint;
-mod types;
+mod hover;
+pub mod types;
 mod util;
 
 use self::hint::{CollectHints, HintSess};
 use crate::{
+    ast,
     error::diagnostic::DiagnosticSeverity,
     hir,
     infer::{display::DisplayType, normalize::Normalize, type_ctx::TypeCtx},
-    span::{EndPosition, Position, Span},
-    types::Type,
-    workspace::Workspace,
+    span::{EndPosition, FileId, Position, Span},
+    sym,
+    token::TokenKind,
+    types::{StructType, Type, TypeId, UintType},
+    workspace::{BindingId, BindingInfo, BindingInfoFlags, BindingInfoKind, ModuleId, Workspace},
 };
 use indexmap::indexmap;
+use std::collections::HashSet;
 use types::*;
+use unicode_xid::UnicodeXID;
 use util::*;
 
 pub fn diagnostics(workspace: &Workspace, tcx: Option<&TypeCtx>, cache: Option<&hir::Cache>) {
+    write(&collect_objects(workspace, tcx, cache));
+}
+
+pub fn hover_info(workspace: &Workspace, tcx: Option<&TypeCtx>, cache: Option<&hir::Cache>, offset: usize) {
+    let root_file_id = workspace
+        .module_infos
+        .get(workspace.root_module_id)
+        .map(|module_info| module_info.file_id);
+
+    let hover_info = root_file_id.and_then(|file_id| find_hover_info(workspace, tcx, cache, file_id, offset));
+
+    match hover_info {
+        Some(hover_info) => write(&hover_info),
+        None => write_null(),
+    }
+}
+
+pub fn goto_definition(workspace: &Workspace, tcx: Option<&TypeCtx>, offset: usize) {
+    match find_definition(workspace, tcx, offset) {
+        Some(span) => write(&span),
+        None => write_null(),
+    }
+}
+
+pub fn find_references(workspace: &Workspace, offset: usize) {
+    match find_reference_spans(workspace, offset) {
+        Some(spans) => write(&spans),
+        None => write_null(),
+    }
+}
+
+pub fn rename(workspace: &Workspace, offset: usize, new_name: &str) {
+    write(&match find_rename_edit(workspace, offset, new_name) {
+        Ok(edit) => RenameResult::Edit(edit),
+        Err(message) => RenameResult::Error(RenameError { message }),
+    });
+}
+
+pub fn completions(workspace: &Workspace, tcx: Option<&TypeCtx>, cache: Option<&hir::Cache>, offset: usize) {
+    match find_completions(workspace, tcx, cache, offset) {
+        Some(completions) => write(&completions),
+        None => write_null(),
+    }
+}
+
+pub fn collect_objects(workspace: &Workspace, tcx: Option<&TypeCtx>, cache: Option<&hir::Cache>) -> Vec<IdeObject> {
     let mut objects: Vec<IdeObject> = vec![];
 
     objects.extend(
@@ -67,30 +119,27 @@ pub fn diagnostics(workspace: &Workspace, tcx: Option<&TypeCtx>, cache: Option<&
         _ => (),
     }
 
-    write(&objects);
+    objects
 }
 
-pub fn hover_info(workspace: &Workspace, tcx: Option<&TypeCtx>, offset: usize) {
-    if let Some(tcx) = tcx {
-        let searched_binding_info = workspace.binding_infos.iter().map(|(_, b)| b).find(|binding_info| {
-            binding_info.module_id == workspace.root_module_id
-                && binding_info.is_is_user_defined()
-                && binding_info.span.contains(offset)
-        });
-
-        if let Some(binding_info) = searched_binding_info {
-            write(&HoverInfo {
-                contents: binding_info.ty.normalize(tcx).display(tcx),
-            });
-        }
-    } else {
-        write_null();
-    }
+pub fn find_hover_info(
+    workspace: &Workspace,
+    tcx: Option<&TypeCtx>,
+    cache: Option<&hir::Cache>,
+    file_id: FileId,
+    offset: usize,
+) -> Option<HoverInfo> {
+    hover::find_hover_info(workspace, tcx?, cache, file_id, offset)
 }
 
-pub fn goto_definition(workspace: &Workspace, tcx: Option<&TypeCtx>, offset: usize) {
+pub fn find_definition(
+    workspace: &Workspace,
+    tcx: Option<&TypeCtx>,
+    file_id: FileId,
+    offset: usize,
+) -> Option<IdeSpan> {
     for (_, binding_info) in workspace.binding_infos.iter() {
-        if is_offset_in_span_and_root_module(workspace, offset, binding_info.span) {
+        if is_offset_in_span_and_file(file_id, offset, binding_info.span) {
             if let Some(tcx) = tcx {
                 match binding_info.ty.normalize(tcx) {
                     Type::Module(module_id) => {
@@ -102,26 +151,459 @@ pub fn goto_definition(workspace: &Workspace, tcx: Option<&TypeCtx>, offset: usi
                             end: EndPosition::initial(),
                         };
 
-                        write(&IdeSpan::from_span_and_file(span, module_info.file_path.to_string()));
-
-                        return;
+                        return Some(IdeSpan::from_span_and_file(span, module_info.file_path.to_string()));
                     }
                     _ => (),
                 }
             }
 
-            write(&IdeSpan::from_span(binding_info.span, workspace));
-
-            return;
+            return Some(IdeSpan::from_span(binding_info.span, workspace));
         }
 
         for &use_span in binding_info.uses.iter() {
-            if is_offset_in_span_and_root_module(workspace, offset, use_span) {
-                write(&IdeSpan::from_span(binding_info.span, workspace));
-                return;
+            if is_offset_in_span_and_file(file_id, offset, use_span) {
+                return Some(IdeSpan::from_span(binding_info.span, workspace));
+            }
+        }
+    }
+
+    None
+}
+
+pub fn find_reference_spans(workspace: &Workspace, file_id: FileId, offset: usize) -> Option<Vec<IdeSpan>> {
+    let binding_info = find_binding_info_at_offset(workspace, file_id, offset)?;
+
+    let spans = collect_reference_spans(workspace, binding_info.id)
+        .into_iter()
+        .map(|span| IdeSpan::from_span(span, workspace))
+        .collect();
+
+    Some(spans)
+}
+
+pub fn find_rename_edit(
+    workspace: &Workspace,
+    file_id: FileId,
+    offset: usize,
+    new_name: &str,
+) -> Result<WorkspaceEdit, String> {
+    let binding_info = find_binding_info_at_offset(workspace, file_id, offset)
+        .ok_or_else(|| "no binding found at the given offset".to_string())?;
+
+    let name = binding_info.name;
+
+    if !binding_info.is_is_user_defined() || binding_info.is_builtin_type() {
+        return Err(format!(
+            "`{}` is not a user defined binding, and cannot be renamed",
+            name
+        ));
+    }
+
+    let module_info = workspace.module_infos.get(binding_info.module_id).unwrap();
+
+    if module_info.library_id == workspace.std_library().id {
+        return Err(format!(
+            "`{}` is defined in the standard library, and cannot be renamed",
+            name
+        ));
+    }
+
+    if !is_valid_identifier(new_name) {
+        return Err(format!("`{}` is not a valid identifier", new_name));
+    }
+
+    if name.as_str() == new_name {
+        return Ok(WorkspaceEdit { edits: vec![] });
+    }
+
+    let linked_bindings = collect_linked_bindings(workspace, binding_info.id);
+
+    // Renaming a binding to a name which is already bound in its scope would
+    // either shadow that binding, or cause a duplicate definition
+    for &id in linked_bindings.iter() {
+        let linked_binding_info = workspace.binding_infos.get(id).unwrap();
+
+        if let Some(colliding_binding_info) = find_colliding_binding_info(workspace, linked_binding_info, new_name) {
+            let file = workspace
+                .diagnostics
+                .get_file(colliding_binding_info.span.file_id)
+                .unwrap();
+
+            return Err(format!(
+                "cannot rename `{}` to `{}` - `{}` is already defined in the same scope at {}:{}:{}",
+                name,
+                new_name,
+                new_name,
+                file.name(),
+                colliding_binding_info.span.start.line,
+                colliding_binding_info.span.start.column
+            ));
+        }
+    }
+
+    let edits = collect_reference_spans(workspace, binding_info.id)
+        .into_iter()
+        .filter_map(|span| narrow_span_to_name(workspace, span, name.as_str()))
+        .map(|span| TextEdit {
+            span: IdeSpan::from_span(span, workspace),
+            new_text: new_name.to_string(),
+        })
+        .collect();
+
+    Ok(WorkspaceEdit { edits })
+}
+
+fn find_binding_info_at_offset(workspace: &Workspace, file_id: FileId, offset: usize) -> Option<&BindingInfo> {
+    let binding_infos = || workspace.binding_infos.iter().map(|(_, binding_info)| binding_info);
+
+    binding_infos()
+        .find(|binding_info| is_offset_in_span_and_file(file_id, offset, binding_info.span))
+        .or_else(|| {
+            binding_infos().find(|binding_info| {
+                binding_info
+                    .uses
+                    .iter()
+                    .any(|&use_span| is_offset_in_span_and_file(file_id, offset, use_span))
+            })
+        })
+}
+
+// Collects the definition and uses spans of the binding, and all of the bindings linked to it.
+// The spans are sorted and deduplicated, and only spans which belong to a module are returned.
+fn collect_reference_spans(workspace: &Workspace, id: BindingId) -> Vec<Span> {
+    let mut spans: Vec<Span> = vec![];
+
+    for id in collect_linked_bindings(workspace, id) {
+        let binding_info = workspace.binding_infos.get(id).unwrap();
+        spans.push(binding_info.span);
+        spans.extend(binding_info.uses.iter().copied());
+    }
+
+    spans.retain(|span| !span.is_unknown() && workspace.find_module_id_by_file_id(span.file_id).is_some());
+
+    spans.sort_by_key(|span| (span.file_id, span.start.index, span.end.index));
+    spans.dedup();
+
+    spans
+}
+
+// Bindings which are re-bound under the same name are linked together, i.e: `use foo.{bar}`
+// binds a new `bar` in the importing module, which is defined at the use site of the original `bar`.
+fn collect_linked_bindings(workspace: &Workspace, id: BindingId) -> Vec<BindingId> {
+    let mut visited = HashSet::new();
+    let mut queue = vec![id];
+
+    while let Some(id) = queue.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+
+        let binding_info = workspace.binding_infos.get(id).unwrap();
+
+        for (_, other) in workspace.binding_infos.iter() {
+            if other.id == id || other.name != binding_info.name || !other.is_is_user_defined() {
+                continue;
+            }
+
+            if binding_info.uses.contains(&other.span) || other.uses.contains(&binding_info.span) {
+                queue.push(other.id);
+            }
+        }
+    }
+
+    let mut ids: Vec<BindingId> = visited.into_iter().collect();
+    ids.sort();
+
+    ids
+}
+
+// Only a binding of the same scope collides with the renamed binding.
+// Scopes are compared by identity, since sibling scopes share their level and name
+fn find_colliding_binding_info<'a>(
+    workspace: &'a Workspace,
+    binding_info: &BindingInfo,
+    new_name: &str,
+) -> Option<&'a BindingInfo> {
+    workspace.binding_infos.iter().map(|(_, b)| b).find(|other| {
+        other.id != binding_info.id
+            && other.name.as_str() == new_name
+            && other.module_id == binding_info.module_id
+            && other.scope_path == binding_info.scope_path
+            && !other.flags.contains(BindingInfoFlags::IGNORE)
+    })
+}
+
+// Returns the part of the span which spells the binding's name, if there is one.
+// This guards edits from touching spans which don't spell the name literally, such as glob imports.
+fn narrow_span_to_name(workspace: &Workspace, span: Span, name: &str) -> Option<Span> {
+    let file = workspace.diagnostics.get_file(span.file_id)?;
+    let text = file.source().get(span.range())?;
+
+    if text == name {
+        Some(span)
+    } else if text.ends_with(name) && text[..text.len() - name.len()].trim_end().ends_with('.') {
+        Some(span.with_start(Position {
+            index: span.end.index - name.len(),
+            ..span.start
+        }))
+    } else {
+        None
+    }
+}
+
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    let is_id = match chars.next() {
+        Some(first) => (first.is_xid_start() || first == '_') && chars.all(|ch| ch.is_xid_continue() || ch == '_'),
+        None => false,
+    };
+
+    is_id && name != "_" && matches!(TokenKind::from(name), TokenKind::Ident(_))
+}
+
+pub fn find_completions(
+    workspace: &Workspace,
+    tcx: Option<&TypeCtx>,
+    cache: Option<&hir::Cache>,
+    offset: usize,
+) -> Option<Vec<Completion>> {
+    let tcx = tcx?;
+    let cache = cache?;
+    let site = cache.completion_site.as_ref()?;
+
+    // Only candidates which start with the text typed so far are returned
+    let prefix = workspace
+        .diagnostics
+        .get_file(site.span.file_id)
+        .and_then(|file| {
+            file.source()
+                .get(site.span.start.index..offset.min(site.span.end.index))
+                .map(|prefix| prefix.to_string())
+        })
+        .unwrap_or_default();
+
+    let mut completions = match &site.kind {
+        hir::CompletionSiteKind::Member(ty) => member_completions(workspace, tcx, cache, *ty),
+        hir::CompletionSiteKind::Scope(bindings) => scope_completions(workspace, tcx, bindings),
+    };
+
+    completions.retain(|completion| completion.name.starts_with(&prefix));
+
+    Some(completions)
+}
+
+fn member_completions(workspace: &Workspace, tcx: &TypeCtx, cache: &hir::Cache, ty: TypeId) -> Vec<Completion> {
+    let ty = ty.normalize(tcx);
+
+    let field = |name: &str, ty: &Type| Completion {
+        name: name.to_string(),
+        type_name: ty.display(tcx),
+        kind: CompletionKind::Field.to_string(),
+    };
+
+    match (&ty, ty.maybe_deref_once()) {
+        (_, Type::Module(module_id)) => module_completions(workspace, tcx, module_id),
+        (Type::Type(inner), _) => match inner.as_ref() {
+            Type::Enum(enum_type) => enum_type
+                .variants
+                .iter()
+                .map(|v| Completion {
+                    name: v.name.to_string(),
+                    type_name: inner.display(tcx),
+                    kind: CompletionKind::Variant.to_string(),
+                })
+                .collect(),
+            Type::Struct(struct_type) => associated_function_completions(workspace, tcx, cache, struct_type, false),
+            _ => vec![],
+        },
+        (_, Type::Struct(struct_type)) => struct_type
+            .fields
+            .iter()
+            .map(|f| field(f.name.as_str(), &f.ty))
+            .chain(associated_function_completions(
+                workspace,
+                tcx,
+                cache,
+                &struct_type,
+                true,
+            ))
+            .collect(),
+        (_, Type::Tuple(elements)) => elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| field(&index.to_string(), element))
+            .collect(),
+        (Type::Pointer(_, is_mutable), Type::Slice(inner) | Type::Str(inner)) => vec![
+            field(sym::BUILTIN_FIELD_LEN, &Type::Uint(UintType::Uint)),
+            field(sym::BUILTIN_FIELD_PTR, &Type::Pointer(inner, *is_mutable)),
+        ],
+        (_, Type::Array(..)) => vec![field(sym::BUILTIN_FIELD_LEN, &Type::Uint(UintType::Uint))],
+        _ => vec![],
+    }
+}
+
+// Only methods can be accessed through a value, while all associated functions can be accessed through the type
+fn associated_function_completions(
+    workspace: &Workspace,
+    tcx: &TypeCtx,
+    cache: &hir::Cache,
+    struct_type: &StructType,
+    only_methods: bool,
+) -> Vec<Completion> {
+    let mut ids: Vec<BindingId> = match struct_type.id.and_then(|id| cache.associated_functions.get(&id)) {
+        Some(functions) => functions.values().copied().collect(),
+        None => return vec![],
+    };
+
+    ids.sort();
+
+    ids.into_iter()
+        .map(|id| workspace.binding_infos.get(id).unwrap())
+        .filter(|binding_info| {
+            binding_info.vis == ast::Vis::Public || binding_info.module_id == workspace.root_module_id
+        })
+        .filter_map(|binding_info| {
+            let ty = binding_info.ty.normalize(tcx);
+
+            let is_method = match &ty {
+                Type::Function(function_type) => function_type.self_param().is_some(),
+                _ => false,
+            };
+
+            if only_methods && !is_method {
+                return None;
+            }
+
+            Some(Completion {
+                name: binding_info.name.to_string(),
+                type_name: ty.display(tcx),
+                kind: if is_method {
+                    CompletionKind::Method
+                } else {
+                    CompletionKind::Function
+                }
+                .to_string(),
+            })
+        })
+        .collect()
+}
+
+fn scope_completions(workspace: &Workspace, tcx: &TypeCtx, local_bindings: &[BindingId]) -> Vec<Completion> {
+    let mut completions: Vec<Completion> = local_bindings
+        .iter()
+        .filter_map(|&id| binding_completion(tcx, workspace.binding_infos.get(id).unwrap()))
+        .collect();
+
+    // Local bindings shadow the root module's bindings, which shadow the `std` prelude
+    for module_id in [workspace.root_module_id, workspace.std_library().root_module_id] {
+        for completion in module_completions(workspace, tcx, module_id) {
+            if !completions.iter().any(|c| c.name == completion.name) {
+                completions.push(completion);
             }
         }
     }
 
-    write_null();
+    completions
+}
+
+fn module_completions(workspace: &Workspace, tcx: &TypeCtx, module_id: ModuleId) -> Vec<Completion> {
+    workspace
+        .binding_infos
+        .iter()
+        .map(|(_, binding_info)| binding_info)
+        .filter(|binding_info| {
+            binding_info.module_id == module_id
+                && binding_info.scope_level.is_global()
+                && (binding_info.vis == ast::Vis::Public || module_id == workspace.root_module_id)
+        })
+        .filter_map(|binding_info| binding_completion(tcx, binding_info))
+        .collect()
+}
+
+fn binding_completion(tcx: &TypeCtx, binding_info: &BindingInfo) -> Option<Completion> {
+    // Skip compiler generated and ignored bindings
+    if binding_info.name.contains('@') || binding_info.flags.contains(BindingInfoFlags::IGNORE) {
+        return None;
+    }
+
+    let ty = binding_info.ty.normalize(tcx);
+
+    let kind = match binding_info.kind {
+        BindingInfoKind::Function | BindingInfoKind::ExternFunction | BindingInfoKind::Intrinsic(_) => {
+            CompletionKind::Function
+        }
+        BindingInfoKind::Type => CompletionKind::Type,
+        _ if ty.is_module() => CompletionKind::Module,
+        _ if ty.is_type() => CompletionKind::Type,
+        _ => CompletionKind::Variable,
+    };
+
+    Some(Completion {
+        name: binding_info.name.to_string(),
+        type_name: ty.display(tcx),
+        kind: kind.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn file_id_of(workspace: &Workspace, file_name: &str) -> FileId {
+        workspace
+            .module_infos
+            .iter()
+            .find(|(_, module_info)| module_info.file_path.ends_with(file_name))
+            .map(|(_, module_info)| module_info.file_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn finds_references_from_imported_module() {
+        let util_source = "pub fn twice(x: int) -> int = x * 2\n";
+
+        let source_file = write_sources(&[
+            ("main.chl", "use util\n\nfn main() = {\n    let _ = util.twice(1)\n}\n"),
+            ("util.chl", util_source),
+        ]);
+
+        let result = check_file(&source_file, false);
+        assert!(errors(&result).is_empty());
+
+        let file_id = file_id_of(&result.workspace, "util.chl");
+        let offset = util_source.find("twice").unwrap() + 1;
+
+        let spans = find_reference_spans(&result.workspace, file_id, offset).unwrap();
+
+        assert_eq!(spans.len(), 2);
+        assert!(spans.iter().any(|span| span.file.ends_with("main.chl")));
+    }
+
+    #[test]
+    fn rename_to_binding_of_sibling_scope() {
+        let source = "fn main() = {\n    for i in 0..3 {\n        let a = i\n        let _ = a\n    }\n\n    for i in 0..3 {\n        let b = i\n        let _ = b\n    }\n}\n";
+
+        let result = check_file(&write_sources(&[("main.chl", source)]), false);
+        assert!(errors(&result).is_empty());
+
+        let file_id = file_id_of(&result.workspace, "main.chl");
+        let offset = source.find("let a").unwrap() + "let ".len();
+
+        assert!(find_rename_edit(&result.workspace, file_id, offset, "b").is_ok());
+    }
+
+    #[test]
+    fn rename_to_binding_of_same_scope() {
+        let source = "fn main() = {\n    let a = 1\n    let b = 2\n    let _ = a + b\n}\n";
+
+        let result = check_file(&write_sources(&[("main.chl", source)]), false);
+        assert!(errors(&result).is_empty());
+
+        let file_id = file_id_of(&result.workspace, "main.chl");
+        let offset = source.find("let a").unwrap() + "let ".len();
+
+        assert!(find_rename_edit(&result.workspace, file_id, offset, "b").is_err());
+    }
 }
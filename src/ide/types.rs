@@ -78,4 +78,68 @@ pub enum IdeObject {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HoverInfo {
     pub contents: String,
+    pub type_name: String,
+    pub signature: Option<String>,
+    pub visibility: Option<String>,
+    pub is_mutable: Option<bool>,
+    pub const_value: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TextEdit {
+    pub span: IdeSpan,
+    pub new_text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkspaceEdit {
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RenameError {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum RenameResult {
+    Edit(WorkspaceEdit),
+    Error(RenameError),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Completion {
+    pub name: String,
+    pub type_name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum CompletionKind {
+    Field,
+    Function,
+    Method,
+    Module,
+    Type,
+    Variable,
+    Variant,
+}
+
+impl Display for CompletionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CompletionKind::Field => "Field",
+                CompletionKind::Function => "Function",
+                CompletionKind::Method => "Method",
+                CompletionKind::Module => "Module",
+                CompletionKind::Type => "Type",
+                CompletionKind::Variable => "Variable",
+                CompletionKind::Variant => "Variant",
+            }
+        )
+    }
 }
@@ -1,12 +1,8 @@
-use crate::span::Span;
-use crate::workspace::Workspace;
+use crate::span::{FileId, Span};
 
 #[inline]
-pub fn is_offset_in_span_and_root_module(workspace: &Workspace, offset: usize, span: Span) -> bool {
-    span.contains(offset)
-        && workspace
-            .find_module_id_by_file_id(span.file_id)
-            .map_or(false, |module_id| module_id == workspace.root_module_id)
+pub fn is_offset_in_span_and_file(file_id: FileId, offset: usize, span: Span) -> bool {
+    span.file_id == file_id && span.contains(offset)
 }
 
 #[inline]
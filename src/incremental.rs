@@ -0,0 +1,445 @@
+use crate::{
+    ast,
+    common::build_options::BuildOptions,
+    error::diagnostic::DiagnosticSeverity,
+    hir::{
+        self,
+        attrs::{ATTR_NAME_EXPORT, ATTR_NAME_TEST},
+        const_value::ConstValue,
+    },
+    infer::{display::DisplayType, normalize::Normalize, type_ctx::TypeCtx},
+    span::{self, FileId},
+    types::{EnumType, StructType, Type},
+    workspace::{library::Library, BindingInfoFlags, ModuleId, ModuleInfo, ModulePath, Workspace},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use ustr::{Ustr, UstrMap};
+
+const CACHE_DIR: &str = ".chili-cache";
+
+// The module cache is persisted between compilations, and records each module's source hash,
+// the hash of its public signatures and the modules it depends on.
+// Modules which didn't change since the last compilation are only checked lazily -
+// that is, only the bindings used by other modules are checked.
+// Their parsed ASTs are cached separately, so that they aren't parsed again either (see `CachedAst`).
+// Checked signatures aren't persisted, so the bindings that are used are checked again on every compilation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModuleCache {
+    compiler_version: String,
+    target_platform: String,
+    modules: HashMap<String, CachedModule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedModule {
+    source_hash: u64,
+    signature_hash: u64,
+    dependencies: Vec<String>,
+    // Warnings are reported while a module is checked, so a module with warnings is always checked
+    has_warnings: bool,
+}
+
+impl ModuleCache {
+    // Returns None if there's no cache, or if it was created by a different compiler or for a different target
+    pub fn load(build_options: &BuildOptions) -> Option<Self> {
+        let source = fs::read_to_string(cache_path(build_options)).ok()?;
+        let cache: Self = serde_json::from_str(&source).ok()?;
+
+        if cache.compiler_version == env!("CARGO_PKG_VERSION")
+            && cache.target_platform == format!("{:?}", build_options.target_platform)
+        {
+            Some(cache)
+        } else {
+            None
+        }
+    }
+
+    // The cache is an optimization, so failing to write it is not an error
+    pub fn save(&self, build_options: &BuildOptions) {
+        let path = cache_path(build_options);
+
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let _ = fs::write(path, serde_json::to_string(self).unwrap());
+    }
+
+    pub fn from_workspace(
+        workspace: &Workspace,
+        tcx: &TypeCtx,
+        cache: &hir::Cache,
+        previous: Option<&ModuleCache>,
+    ) -> Self {
+        let dependencies = collect_module_dependencies(workspace, tcx);
+
+        let files_with_warnings: HashSet<FileId> = workspace
+            .diagnostics
+            .items()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Warning)
+            .flat_map(|diagnostic| diagnostic.labels.iter().map(|label| label.span.file_id))
+            .collect();
+
+        let modules = workspace
+            .module_infos
+            .iter()
+            .filter_map(|(id, module_info)| {
+                let id = ModuleId::from(id);
+                let path = module_info.file_path.to_string();
+
+                // Lazily checked modules didn't change, so their previous entry is still valid
+                if cache.lazily_checked_modules.contains(&id) {
+                    if let Some(cached) = previous.and_then(|previous| previous.modules.get(&path)) {
+                        return Some((path, cached.clone()));
+                    }
+                }
+
+                let source_hash = source_hash(workspace, module_info.file_id)?;
+
+                let dependencies = dependencies
+                    .get(&id)
+                    .map(|dependencies| {
+                        dependencies
+                            .iter()
+                            .map(|&id| workspace.module_infos.get(id).unwrap().file_path.to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                Some((
+                    path,
+                    CachedModule {
+                        source_hash,
+                        signature_hash: module_signature_hash(workspace, tcx, id),
+                        dependencies,
+                        has_warnings: files_with_warnings.contains(&module_info.file_id),
+                    },
+                ))
+            })
+            .collect();
+
+        Self {
+            compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            target_platform: format!("{:?}", workspace.build_options.target_platform),
+            modules,
+        }
+    }
+
+    // Returns the modules whose source didn't change, and whose dependencies still exist.
+    // The root module, modules with warnings, and modules that must be checked eagerly are always considered changed.
+    pub fn unchanged_modules(&self, workspace: &Workspace, modules: &[ast::Module]) -> HashSet<ModuleId> {
+        let existing_paths: HashSet<String> = workspace
+            .module_infos
+            .iter()
+            .map(|(_, module_info)| module_info.file_path.to_string())
+            .collect();
+
+        modules
+            .iter()
+            .filter(|module| module.id != workspace.root_module_id && !must_check_eagerly(module))
+            .filter(|module| match self.modules.get(module.info.file_path.as_str()) {
+                Some(cached) => {
+                    !cached.has_warnings
+                        && source_hash(workspace, module.file_id) == Some(cached.source_hash)
+                        && cached
+                            .dependencies
+                            .iter()
+                            .all(|dependency| existing_paths.contains(dependency))
+                }
+                None => false,
+            })
+            .map(|module| module.id)
+            .collect()
+    }
+
+    pub fn signature_hash(&self, workspace: &Workspace, module_id: ModuleId) -> Option<u64> {
+        let module_info = workspace.module_infos.get(module_id).unwrap();
+
+        self.modules
+            .get(module_info.file_path.as_str())
+            .map(|cached| cached.signature_hash)
+    }
+
+    // Returns all modules which depend on the given module, directly or transitively
+    pub fn dependents(&self, workspace: &Workspace, module_id: ModuleId) -> HashSet<ModuleId> {
+        let mut dependents = HashSet::new();
+        let mut queue = vec![workspace.module_infos.get(module_id).unwrap().file_path.to_string()];
+
+        while let Some(path) = queue.pop() {
+            for (dependent_path, cached) in self.modules.iter() {
+                if !cached.dependencies.contains(&path) {
+                    continue;
+                }
+
+                let dependent_id = workspace
+                    .module_infos
+                    .iter()
+                    .find(|(_, module_info)| module_info.file_path == dependent_path.as_str())
+                    .map(|(id, _)| ModuleId::from(id));
+
+                if let Some(dependent_id) = dependent_id {
+                    if dependents.insert(dependent_id) {
+                        queue.push(dependent_path.clone());
+                    }
+                }
+            }
+        }
+
+        dependents
+    }
+}
+
+// Lazily checked bindings are only checked when another module refers to them by name.
+// Comptime blocks, impls, and `@export` and `@test` functions are never referred to by name,
+// so modules that contain them are always checked eagerly
+fn must_check_eagerly(module: &ast::Module) -> bool {
+    !module.comptime_blocks.is_empty()
+        || !module.impls.is_empty()
+        || module.bindings.iter().any(|binding| {
+            binding
+                .attrs
+                .iter()
+                .any(|attr| matches!(attr.name.name.as_str(), ATTR_NAME_EXPORT | ATTR_NAME_TEST))
+        })
+}
+
+// A module's parsed AST, along with the modules it imports.
+// It is keyed by the module's path and source, so a module is parsed again only when its source changes
+#[derive(Serialize, Deserialize)]
+pub struct CachedAst {
+    compiler_version: String,
+    imports: Vec<CachedImport>,
+    bindings: Vec<ast::Binding>,
+    comptime_blocks: Vec<ast::Comptime>,
+    impls: Vec<ast::Impl>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedImport {
+    library: Ustr,
+    components: Vec<Ustr>,
+}
+
+impl CachedAst {
+    pub fn new(module: &ast::Module, imports: &[ModulePath]) -> Self {
+        Self {
+            compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            imports: imports
+                .iter()
+                .map(|module_path| CachedImport {
+                    library: module_path.library().name,
+                    components: module_path.components().to_vec(),
+                })
+                .collect(),
+            bindings: module.bindings.clone(),
+            comptime_blocks: module.comptime_blocks.clone(),
+            impls: module.impls.clone(),
+        }
+    }
+
+    // Returns None if the AST isn't cached, or if it was cached by a different compiler.
+    // The AST's spans are assigned to `file_id`, which is the module's file in the current compilation
+    pub fn load(cache_dir: &Path, path: &str, source: &str, file_id: FileId) -> Option<Self> {
+        let json = fs::read_to_string(ast_path(cache_dir, path, source)).ok()?;
+        let cached: Self = span::deserialize_in_file(file_id, || serde_json::from_str::<Self>(&json)).ok()?;
+
+        if cached.compiler_version == env!("CARGO_PKG_VERSION") {
+            Some(cached)
+        } else {
+            None
+        }
+    }
+
+    // Like the module cache, failing to write the AST is not an error
+    pub fn save(&self, cache_dir: &Path, path: &str, source: &str) {
+        let _ = fs::create_dir_all(cache_dir);
+        let _ = fs::write(ast_path(cache_dir, path, source), serde_json::to_string(self).unwrap());
+    }
+
+    // Returns the module, and the modules it imports.
+    // Returns None if one of the imported libraries is no longer available
+    pub fn into_module(
+        self,
+        module_info: ModuleInfo,
+        libraries: &UstrMap<Library>,
+    ) -> Option<(ast::Module, Vec<ModulePath>)> {
+        let imports = self
+            .imports
+            .into_iter()
+            .map(|import| {
+                libraries
+                    .get(&import.library)
+                    .map(|library| ModulePath::new(library.clone(), import.components))
+            })
+            .collect::<Option<Vec<ModulePath>>>()?;
+
+        let mut module = ast::Module::new(module_info.file_id, module_info);
+        module.bindings = self.bindings;
+        module.comptime_blocks = self.comptime_blocks;
+        module.impls = self.impls;
+
+        Some((module, imports))
+    }
+}
+
+pub fn ast_cache_dir(build_options: &BuildOptions) -> PathBuf {
+    build_options.root_dir().join(CACHE_DIR).join("ast")
+}
+
+fn ast_path(cache_dir: &Path, path: &str, source: &str) -> PathBuf {
+    cache_dir.join(format!("{:016x}.json", hash_str(&format!("{}\n{}", path, source))))
+}
+
+// Hashes the public signatures of a module's global bindings and associated functions.
+// Dependents of a module only need to be re-checked when this hash changes.
+pub fn module_signature_hash(workspace: &Workspace, tcx: &TypeCtx, module_id: ModuleId) -> u64 {
+    let mut signatures: Vec<String> = workspace
+        .binding_infos
+        .iter()
+        .map(|(_, binding_info)| binding_info)
+        .filter(|binding_info| {
+            binding_info.module_id == module_id
+                && (binding_info.scope_level.is_global() || binding_info.is_associated_function())
+                && binding_info.vis == ast::Vis::Public
+                && !binding_info.flags.contains(BindingInfoFlags::IGNORE)
+        })
+        .map(|binding_info| {
+            let ty = binding_info.ty.normalize(tcx);
+
+            let ty = match &ty {
+                Type::Type(inner) => match inner.as_ref() {
+                    // Named structs and enums are displayed by their name, so their members must be hashed explicitly
+                    Type::Struct(struct_type) => StructType {
+                        id: None,
+                        ..struct_type.clone()
+                    }
+                    .display(tcx),
+                    Type::Enum(enum_type) => EnumType {
+                        id: None,
+                        ..enum_type.clone()
+                    }
+                    .display(tcx),
+                    inner => inner.display(tcx),
+                },
+                _ => ty.display(tcx),
+            };
+
+            let const_value = match &binding_info.const_value {
+                Some(ConstValue::Type(_)) | None => String::new(),
+                Some(const_value) => const_value.display(tcx),
+            };
+
+            format!(
+                "{:?} {}{}: {} = {}",
+                binding_info.kind,
+                if binding_info.is_mutable { "mut " } else { "" },
+                binding_info.qualified_name,
+                ty,
+                const_value
+            )
+        })
+        .collect();
+
+    signatures.sort();
+
+    hash_str(&signatures.join("\n"))
+}
+
+// A module depends on another module if it uses any of its bindings, or binds it by name
+fn collect_module_dependencies(workspace: &Workspace, tcx: &TypeCtx) -> HashMap<ModuleId, BTreeSet<ModuleId>> {
+    let file_modules: HashMap<FileId, ModuleId> = workspace
+        .module_infos
+        .iter()
+        .map(|(id, module_info)| (module_info.file_id, ModuleId::from(id)))
+        .collect();
+
+    let mut dependencies: HashMap<ModuleId, BTreeSet<ModuleId>> = HashMap::new();
+
+    for (_, binding_info) in workspace.binding_infos.iter() {
+        if binding_info.is_builtin_type() {
+            continue;
+        }
+
+        for use_span in binding_info.uses.iter() {
+            if let Some(&user_module_id) = file_modules.get(&use_span.file_id) {
+                if user_module_id != binding_info.module_id {
+                    dependencies
+                        .entry(user_module_id)
+                        .or_default()
+                        .insert(binding_info.module_id);
+                }
+            }
+        }
+
+        if let Type::Module(module_id) = binding_info.ty.normalize(tcx) {
+            if module_id != binding_info.module_id {
+                dependencies
+                    .entry(binding_info.module_id)
+                    .or_default()
+                    .insert(module_id);
+            }
+        }
+    }
+
+    dependencies
+}
+
+fn source_hash(workspace: &Workspace, file_id: FileId) -> Option<u64> {
+    workspace
+        .diagnostics
+        .get_file(file_id)
+        .map(|file| hash_str(file.source()))
+}
+
+fn cache_path(build_options: &BuildOptions) -> PathBuf {
+    let name = build_options
+        .source_file
+        .file_stem()
+        .map_or("root", |stem| stem.to_str().unwrap())
+        .to_string();
+
+    build_options.root_dir().join(CACHE_DIR).join(format!("{}.json", name))
+}
+
+// FNV-1a, which is used since it's stable across compilations and platforms
+fn hash_str(s: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    s.bytes()
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[test]
+    fn unchanged_module_reports_warnings_again() {
+        // `unused` is never referred to by `main`, so it is only checked if `foo` is checked eagerly
+        let source_file = write_sources(&[
+            ("main.chl", "use foo\n\nfn main() = foo.bar()\n"),
+            (
+                "foo.chl",
+                "pub fn bar() = ()\n\npub fn unused() = std.intrinsics.compiler_warning(\"unused is deprecated\")\n",
+            ),
+        ]);
+
+        // The second compilation uses the module cache, and the cached ASTs
+        for _ in 0..2 {
+            let result = check_file(&source_file, true);
+
+            assert!(errors(&result).is_empty());
+            assert_eq!(warnings(&result).len(), 1);
+        }
+
+        let ast_cache_dir = source_file.parent().unwrap().join(".chili-cache").join("ast");
+        assert!(ast_cache_dir.read_dir().unwrap().count() > 0);
+    }
+}
@@ -35,6 +35,9 @@ pub fn can_cast_type(from: &Type, to: &Type) -> bool {
                 if can_coerce_mut(*from_mutable, *to_mutable) {
                     match (left.as_ref(), right.as_ref()) {
                         (Type::Array(t_array, ..), Type::Slice(right)) => t_array == right,
+                        // Fat pointers can only be cast to fat pointers with the same layout, i.e: `*[]u8` to `*str`
+                        (Type::Slice(left) | Type::Str(left), Type::Slice(right) | Type::Str(right)) => left == right,
+                        (Type::Slice(_) | Type::Str(_), _) | (_, Type::Slice(_) | Type::Str(_)) => false,
                         (Type::Array(t_array, ..), right) => t_array.as_ref() == right,
                         (_, _) => true,
                     }
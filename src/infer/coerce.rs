@@ -21,6 +21,7 @@ pub enum CoercionResult {
 pub enum CoercionKind {
     Cast,
     Slice,
+    Closure,
 }
 
 trait Coerce {
@@ -108,6 +109,14 @@ impl Coerce for Type {
                 }
             }
 
+            // * fn -> closure with the same signature
+            (Type::Function(left), Type::Closure(right)) => Type::Function(left.clone())
+                .unify(&Type::Function(right.clone()), tcx)
+                .map_or(NoCoercion, |_| CoerceToRight(CoercionKind::Closure)),
+            (Type::Closure(left), Type::Function(right)) => Type::Function(left.clone())
+                .unify(&Type::Function(right.clone()), tcx)
+                .map_or(NoCoercion, |_| CoerceToLeft(CoercionKind::Closure)),
+
             _ => NoCoercion,
         }
     }
@@ -195,6 +204,13 @@ pub fn coerce_node(tcx: &mut TypeCtx, node: &mut hir::Node, to: Type, kind: Coer
             span: node.span(),
         }),
         CoercionKind::Slice => coerce_array_to_slice(tcx, node, to),
+        // A function is a closure without an environment
+        CoercionKind::Closure => hir::Node::Literal(hir::Literal::Closure(hir::ClosureLiteral {
+            function: Box::new(node.clone()),
+            env: None,
+            ty: tcx.bound(to, node.span()),
+            span: node.span(),
+        })),
     }
 }
 
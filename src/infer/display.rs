@@ -40,6 +40,7 @@ fn display_type(ty: &Type, tcx: &TypeCtx) -> String {
         .to_string(),
         Type::Pointer(ty, is_mutable) => format!("*{}{}", if *is_mutable { "mut " } else { "" }, display_type(ty, tcx)),
         Type::Function(ty) => ty.display(tcx),
+        Type::Closure(ty) => ty.display(tcx).replacen("fn", "closure", 1),
         Type::Array(inner, size) => format!("[{}]{}", size, display_type(inner, tcx)),
         Type::Slice(inner) => format!("[]{}", display_type(inner, tcx)),
         Type::Str(_) => "str".to_string(),
@@ -51,7 +52,10 @@ fn display_type(ty: &Type, tcx: &TypeCtx) -> String {
                 .join(", ")
         ),
         Type::Struct(ty) => ty.display(tcx),
+        Type::Enum(ty) => ty.display(tcx),
         Type::Type(_) | Type::AnyType => "type".to_string(),
+        Type::Trait(ty) => ty.name.to_string(),
+        Type::Param(ty) => ty.name.to_string(),
         Type::Module(_) => "{module}".to_string(),
         Type::Never => sym::NEVER.to_string(),
         Type::Infer(_, InferType::AnyInt) => "{integer}".to_string(),
@@ -82,6 +86,35 @@ impl DisplayType for StructType {
     }
 }
 
+impl DisplayType for EnumType {
+    fn display(&self, tcx: &TypeCtx) -> String {
+        if self.id.is_some() {
+            self.name.to_string()
+        } else {
+            format!(
+                "enum {{ {} }}",
+                self.variants
+                    .iter()
+                    .map(|v| if v.has_payload() {
+                        format!(
+                            "{}({})",
+                            v.name,
+                            v.payload
+                                .iter()
+                                .map(|t| display_type(t, tcx))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    } else {
+                        v.name.to_string()
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        }
+    }
+}
+
 impl DisplayType for FunctionType {
     fn display(&self, tcx: &TypeCtx) -> String {
         format!(
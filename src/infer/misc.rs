@@ -18,7 +18,7 @@ fn is_concrete_impl(ty: &Type) -> Result<(), TypeId> {
         Type::Array(inner, _) | Type::Slice(inner) | Type::Str(inner) | Type::Pointer(inner, _) => {
             is_concrete_impl(inner)
         }
-        Type::Function(f) => {
+        Type::Function(f) | Type::Closure(f) => {
             f.params
                 .iter()
                 .map(|p| is_concrete_impl(&p.ty))
@@ -44,7 +44,13 @@ fn is_concrete_impl(ty: &Type) -> Result<(), TypeId> {
             .iter()
             .map(|f| is_concrete_impl(&f.ty))
             .collect::<Result<_, _>>(),
-        Type::Module(_) | Type::Type(_) | Type::AnyType => Ok(()),
+        Type::Enum(e) => e
+            .variants
+            .iter()
+            .flat_map(|v| v.payload.iter())
+            .map(is_concrete_impl)
+            .collect::<Result<_, _>>(),
+        Type::Module(_) | Type::Trait(_) | Type::Param(_) | Type::Type(_) | Type::AnyType => Ok(()),
         Type::Var(ty) | Type::Infer(ty, _) => Err(*ty),
     }
 }
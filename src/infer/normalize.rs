@@ -67,25 +67,8 @@ impl NormalizeCtx {
     fn normalize_kind(&mut self, tcx: &TypeCtx, kind: &Type) -> Type {
         match kind {
             Type::Var(ty) => self.normalize_ty(tcx, *ty),
-            Type::Function(f) => Type::Function(FunctionType {
-                params: f
-                    .params
-                    .iter()
-                    .map(|p| FunctionTypeParam {
-                        name: p.name,
-                        ty: self.normalize_kind(tcx, &p.ty),
-                        default_value: p.default_value.clone(),
-                    })
-                    .collect(),
-                return_type: Box::new(self.normalize_kind(tcx, &f.return_type)),
-                varargs: f.varargs.as_ref().map(|v| {
-                    Box::new(FunctionTypeVarargs {
-                        name: v.name,
-                        ty: v.ty.as_ref().map(|ty| self.normalize_kind(tcx, ty)),
-                    })
-                }),
-                kind: f.kind.clone(),
-            }),
+            Type::Function(f) => Type::Function(self.normalize_function(tcx, f)),
+            Type::Closure(f) => Type::Closure(self.normalize_function(tcx, f)),
             Type::Pointer(inner, a) => Type::Pointer(Box::new(self.normalize_kind(tcx, inner)), *a),
             Type::Array(inner, a) => Type::Array(Box::new(self.normalize_kind(tcx, inner)), *a),
             Type::Slice(inner) => Type::Slice(Box::new(self.normalize_kind(tcx, inner))),
@@ -121,6 +104,19 @@ impl NormalizeCtx {
                     struct_type
                 }
             },
+            Type::Enum(enum_type) => Type::Enum(EnumType {
+                name: enum_type.name,
+                id: enum_type.id,
+                variants: enum_type
+                    .variants
+                    .iter()
+                    .map(|v| EnumVariant {
+                        name: v.name,
+                        payload: v.payload.iter().map(|ty| self.normalize_kind(tcx, ty)).collect(),
+                        span: v.span,
+                    })
+                    .collect(),
+            }),
             Type::Type(inner) => self.normalize_kind(tcx, inner).create_type(),
             Type::Infer(ty, InferType::AnyInt) => self.normalize_anyint(*ty),
             Type::Infer(ty, InferType::AnyFloat) => self.normalize_anyfloat(*ty),
@@ -131,10 +127,34 @@ impl NormalizeCtx {
             | Type::Uint(_)
             | Type::Float(_)
             | Type::Module(_)
+            | Type::Trait(_)
+            | Type::Param(_)
             | Type::AnyType => kind.clone(),
         }
     }
 
+    fn normalize_function(&mut self, tcx: &TypeCtx, f: &FunctionType) -> FunctionType {
+        FunctionType {
+            params: f
+                .params
+                .iter()
+                .map(|p| FunctionTypeParam {
+                    name: p.name,
+                    ty: self.normalize_kind(tcx, &p.ty),
+                    default_value: p.default_value.clone(),
+                })
+                .collect(),
+            return_type: Box::new(self.normalize_kind(tcx, &f.return_type)),
+            varargs: f.varargs.as_ref().map(|v| {
+                Box::new(FunctionTypeVarargs {
+                    name: v.name,
+                    ty: v.ty.as_ref().map(|ty| self.normalize_kind(tcx, ty)),
+                })
+            }),
+            kind: f.kind.clone(),
+        }
+    }
+
     fn normalize_anyint(&self, ty: TypeId) -> Type {
         if self.concrete {
             Type::int()
@@ -182,6 +182,10 @@ impl<'a> Substitute<'a> for hir::Control {
                 return_.value.substitute(sess);
             }
             hir::Control::Break(term) | hir::Control::Continue(term) => term.ty.substitute(sess, term.span),
+            hir::Control::Defer(defer) => {
+                defer.ty.substitute(sess, defer.span);
+                defer.expr.substitute(sess);
+            }
         }
     }
 }
@@ -288,6 +292,15 @@ impl<'a> Substitute<'a> for hir::Literal {
                 lit.ty.substitute(sess, lit.span);
                 lit.value.substitute(sess);
             }
+            hir::Literal::Enum(lit) => {
+                lit.ty.substitute(sess, lit.span);
+                lit.payload.substitute(sess);
+            }
+            hir::Literal::Closure(lit) => {
+                lit.ty.substitute(sess, lit.span);
+                lit.function.substitute(sess);
+                lit.env.substitute(sess);
+            }
         }
     }
 }
@@ -355,7 +368,7 @@ fn extract_free_type_vars(ty: &mut Type, free_types: &mut HashSet<TypeId>, tcx:
             *ty = concrete;
         }
 
-        Type::Function(f) => {
+        Type::Function(f) | Type::Closure(f) => {
             f.params
                 .iter_mut()
                 .for_each(|p| extract_free_type_vars(&mut p.ty, free_types, tcx));
@@ -379,6 +392,13 @@ fn extract_free_type_vars(ty: &mut Type, free_types: &mut HashSet<TypeId>, tcx:
                 .for_each(|f| extract_free_type_vars(&mut f.ty, free_types, tcx));
         }
 
+        Type::Enum(EnumType { variants, .. }) => {
+            variants
+                .iter_mut()
+                .flat_map(|v| v.payload.iter_mut())
+                .for_each(|ty| extract_free_type_vars(ty, free_types, tcx));
+        }
+
         Type::Never
         | Type::Unit
         | Type::Bool
@@ -386,6 +406,8 @@ fn extract_free_type_vars(ty: &mut Type, free_types: &mut HashSet<TypeId>, tcx:
         | Type::Uint(_)
         | Type::Float(_)
         | Type::Module(_)
+        | Type::Trait(_)
+        | Type::Param(_)
         | Type::AnyType => (),
     }
 }
@@ -2,7 +2,7 @@ use super::{display::DisplayType, inference_value::InferenceValue, normalize::No
 use crate::{
     common::id_cache::IdCache,
     span::Span,
-    types::{InferType, StructType, Type, TypeId},
+    types::{EnumType, InferType, StructType, Type, TypeId},
 };
 
 pub struct TypeCtx {
@@ -109,7 +109,7 @@ impl TypeCtx {
                 *ty = concrete;
             }
 
-            Type::Function(f) => {
+            Type::Function(f) | Type::Closure(f) => {
                 f.params.iter_mut().for_each(|p| self.make_concrete(&mut p.ty));
 
                 self.make_concrete(&mut f.return_type);
@@ -129,6 +129,13 @@ impl TypeCtx {
                 fields.iter_mut().for_each(|f| self.make_concrete(&mut f.ty));
             }
 
+            Type::Enum(EnumType { variants, .. }) => {
+                variants
+                    .iter_mut()
+                    .flat_map(|v| v.payload.iter_mut())
+                    .for_each(|ty| self.make_concrete(ty));
+            }
+
             Type::Never
             | Type::Unit
             | Type::Bool
@@ -136,6 +143,8 @@ impl TypeCtx {
             | Type::Uint(_)
             | Type::Float(_)
             | Type::Module(_)
+            | Type::Trait(_)
+            | Type::Param(_)
             | Type::AnyType
             | Type::Var(_) => (),
         }
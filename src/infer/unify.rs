@@ -64,7 +64,7 @@ impl UnifyType<Type> for Type {
                 Ok(())
             }
 
-            (Type::Function(f1), Type::Function(f2)) => f1.unify(f2, tcx),
+            (Type::Function(f1), Type::Function(f2)) | (Type::Closure(f1), Type::Closure(f2)) => f1.unify(f2, tcx),
 
             (Type::Array(t1, s1), Type::Array(t2, s2)) => {
                 if *s1 != *s2 {
@@ -87,6 +87,9 @@ impl UnifyType<Type> for Type {
             }
 
             (Type::Struct(t1), Type::Struct(t2)) => t1.unify(t2, tcx),
+            (Type::Enum(t1), Type::Enum(t2)) => t1.unify(t2, tcx),
+            (Type::Trait(t1), Type::Trait(t2)) if t1.id == t2.id => Ok(()),
+            (Type::Param(t1), Type::Param(t2)) if t1 == t2 => Ok(()),
 
             (Type::Type(t1), Type::Type(t2)) => t1.unify(t2.as_ref(), tcx),
             (Type::AnyType, Type::Type(_)) | (Type::Type(_), Type::AnyType) => Ok(()),
@@ -156,6 +159,37 @@ impl UnifyType<StructType> for StructType {
     }
 }
 
+impl UnifyType<EnumType> for EnumType {
+    fn unify(&self, other: &EnumType, tcx: &mut TypeCtx) -> UnifyTypeResult {
+        match (self.id, other.id) {
+            (None, None) => {
+                if self.variants.len() != other.variants.len() {
+                    Err(UnifyTypeErr::Mismatch)
+                } else {
+                    for (v1, v2) in self.variants.iter().zip(other.variants.iter()) {
+                        if v1.name != v2.name || v1.payload.len() != v2.payload.len() {
+                            return Err(UnifyTypeErr::Mismatch);
+                        }
+
+                        for (t1, t2) in v1.payload.iter().zip(v2.payload.iter()) {
+                            t1.unify(t2, tcx)?;
+                        }
+                    }
+                    Ok(())
+                }
+            }
+            (Some(_), None) | (None, Some(_)) => Err(UnifyTypeErr::Mismatch),
+            (Some(self_id), Some(other_id)) => {
+                if self_id == other_id {
+                    Ok(())
+                } else {
+                    Err(UnifyTypeErr::Mismatch)
+                }
+            }
+        }
+    }
+}
+
 fn unify_var_ty(var: TypeId, other: &Type, tcx: &mut TypeCtx) -> UnifyTypeResult {
     match tcx.value_of(var).clone() {
         InferenceValue::Bound(kind) => kind.unify(other, tcx),
@@ -215,10 +249,16 @@ pub fn occurs(var: TypeId, kind: &Type, tcx: &TypeCtx) -> bool {
                 AnyInt | AnyFloat | Unbound => var == other,
             }
         }
-        Type::Function(f) => f.params.iter().any(|p| occurs(var, &p.ty, tcx)) || occurs(var, &f.return_type, tcx),
+        Type::Function(f) | Type::Closure(f) => {
+            f.params.iter().any(|p| occurs(var, &p.ty, tcx)) || occurs(var, &f.return_type, tcx)
+        }
         Type::Array(ty, _) => occurs(var, ty, tcx),
         Type::Tuple(tys) => tys.iter().any(|ty| occurs(var, ty, tcx)),
         Type::Struct(st) => st.fields.iter().any(|f| occurs(var, &f.ty, tcx)),
+        Type::Enum(e) => e
+            .variants
+            .iter()
+            .any(|v| v.payload.iter().any(|ty| occurs(var, ty, tcx))),
         _ => false,
     }
 }
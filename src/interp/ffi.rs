@@ -5,7 +5,7 @@ use super::{
         value::{ExternFunction, Function, FunctionValue, Pointer, Value},
         VM,
     },
-    IS_64BIT,
+    IS_64BIT, WORD_SIZE,
 };
 use crate::types::*;
 use bumpalo::Bump;
@@ -239,7 +239,7 @@ unsafe extern "C" fn closure_callback(
     // we need the VM to Halt instead of Return
     *func.code.as_mut_slice().last_mut().unwrap() = u8::from(Op::Halt);
 
-    let value = (*userdata.vm).run_function(func);
+    let value = (*userdata.vm).run_function(&func);
 
     // pop the function args manually
     if arg_count > 0 {
@@ -266,7 +266,7 @@ unsafe extern "C" fn closure_callback(
         Value::F32(v) => *(result as *mut _ as *mut _) = v,
         Value::F64(v) => *(result as *mut _ as *mut _) = v,
         Value::Bool(v) => *(result as *mut _ as *mut _) = v,
-        Value::Pointer(v) => *(result as *mut _ as *mut _) = v.as_inner_raw(),
+        Value::Pointer(v) => *(result as *mut _ as *mut _) = v.address(),
         _ => panic!("unexpected value `{}`", value.to_string()),
     }
 }
@@ -318,8 +318,10 @@ impl AsFfiType for Type {
             },
             Type::Unit | Type::Pointer(_, _) | Type::Function(_) | Type::Array(_, _) => FfiType::pointer(),
             Type::Slice(_) | Type::Str(_) => FfiType::structure([FfiType::pointer(), FfiType::usize()]),
+            Type::Closure(_) => FfiType::structure([FfiType::pointer(), FfiType::pointer()]),
             Type::Tuple(tuple_elements) => FfiType::structure(tuple_elements.iter().map(|ty| ty.as_ffi_type())),
             Type::Struct(st) => FfiType::structure(st.fields.iter().map(|f| f.ty.as_ffi_type())),
+            Type::Enum(e) => Type::Struct(e.layout(WORD_SIZE)).as_ffi_type(),
             Type::Infer(_, ty) => match ty {
                 InferType::AnyInt => {
                     if IS_64BIT {
@@ -15,13 +15,19 @@ use crate::{
         target::{TargetMetrics, TargetPlatform},
     },
     error::diagnostic::Diagnostic,
-    hir,
+    hir::{
+        self,
+        const_value::{ConstFunction, ConstValue},
+    },
     infer::type_ctx::TypeCtx,
     types::{FunctionType, FunctionTypeKind, Type},
     workspace::{BindingId, ModuleId, Workspace},
 };
 use bumpalo::Bump;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    panic::{self, AssertUnwindSafe},
+};
 use ustr::{ustr, Ustr};
 
 pub type InterpResult = Result<Value, Vec<Diagnostic>>;
@@ -35,6 +41,9 @@ pub struct Interp {
     pub functions: HashMap<hir::FunctionId, Function>,
     pub extern_functions: HashMap<hir::FunctionId, ExternFunction>,
 
+    // The code of each deferred expression, used to run them when the VM panics
+    pub deferred_functions: Vec<Function>,
+
     pub ffi: Ffi,
     pub build_options: BuildOptions,
 
@@ -51,6 +60,7 @@ impl Interp {
             constants: vec![Value::unit()],
             functions: HashMap::new(),
             extern_functions: HashMap::new(),
+            deferred_functions: vec![],
             ffi: Ffi::new(),
             build_options,
             bindings_to_globals: HashMap::new(),
@@ -71,6 +81,7 @@ impl Interp {
             diagnostics: vec![],
             env_stack: vec![],
             loop_env_stack: vec![],
+            defer_scopes: vec![],
             statically_initialized_globals: vec![],
             lowered_functions: HashSet::new(),
             bump: Bump::new(),
@@ -95,6 +106,9 @@ pub struct InterpSess<'i> {
     pub env_stack: Vec<(ModuleId, Env)>,
     pub loop_env_stack: Vec<LoopEnv>,
 
+    // The deferred expressions of each scope in the function that is currently lowered
+    pub defer_scopes: Vec<Vec<hir::Node>>,
+
     // Globals that are going to be statically initialized when the VM starts
     pub statically_initialized_globals: Vec<Bytecode>,
 
@@ -107,13 +121,16 @@ pub struct InterpSess<'i> {
 pub struct LoopEnv {
     pub(super) break_offsets: Vec<usize>,
     pub(super) continue_offsets: Vec<usize>,
+    // The number of defer scopes outside of the loop, which `break` and `continue` don't exit
+    pub(super) defer_depth: usize,
 }
 
 impl LoopEnv {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(defer_depth: usize) -> Self {
         Self {
             break_offsets: vec![],
             continue_offsets: vec![],
+            defer_depth,
         }
     }
 }
@@ -122,6 +139,74 @@ pub(super) type Env = Scopes<BindingId, i16>;
 
 impl<'i> InterpSess<'i> {
     pub fn eval(&'i mut self, node: &hir::Node, module_id: ModuleId) -> InterpResult {
+        let start_func = self.lower_start_function(node, module_id)?;
+
+        let mut vm = self.create_vm();
+        let result = vm.run_function(&start_func);
+
+        Ok(result)
+    }
+
+    // Runs the workspace's entry point function, as if it was the program's `main`.
+    // Internal VM errors are reported as diagnostics, along with the VM's call stack
+    pub fn run_entry_point(&'i mut self) -> InterpResult {
+        let cache = self.cache;
+        let function = cache.entry_point_function().unwrap();
+
+        let node = hir::Node::Call(hir::Call {
+            callee: Box::new(hir::Node::Const(hir::Const {
+                value: ConstValue::Function(ConstFunction {
+                    id: function.id,
+                    name: function.qualified_name,
+                }),
+                ty: function.ty,
+                span: function.span,
+            })),
+            args: vec![],
+            ty: self.tcx.common_types.unit,
+            span: function.span,
+        });
+
+        let start_func = self.lower_start_function(&node, function.module_id)?;
+
+        let mut vm = self.create_vm();
+
+        // The panic is reported as a diagnostic, so the default hook's message is silenced while the VM runs
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| vm.run_function(&start_func)));
+
+        panic::set_hook(default_hook);
+
+        match result {
+            Ok(result) => Ok(result),
+            Err(payload) => {
+                let reason = payload
+                    .downcast_ref::<&str>()
+                    .map(|reason| reason.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown reason".to_string());
+
+                let call_stack = vm
+                    .frames
+                    .iter()
+                    .rev()
+                    .map(|frame| format!("in `{}`", frame.func().name))
+                    .collect::<Vec<_>>();
+
+                // Run the pending deferred expressions, as if the panic unwound through their scopes
+                vm.unwind_defers();
+
+                Err(vec![Diagnostic::error()
+                    .with_message(format!("the interpreter panicked: {}", reason))
+                    .with_notes(&call_stack)])
+            }
+        }
+    }
+
+    // Lowers `node` into the code of a function which the VM starts running from
+    fn lower_start_function(&mut self, node: &hir::Node, module_id: ModuleId) -> Result<Function, Vec<Diagnostic>> {
         let mut start_code = Bytecode::new();
 
         // lower expression tree into instructions
@@ -142,9 +227,7 @@ impl<'i> InterpSess<'i> {
                 dump_bytecode_to_file(&self.interp, &start_code);
             }
 
-            let mut vm = self.create_vm();
-
-            let start_func = Function {
+            Ok(Function {
                 id: hir::FunctionId::unknown(),
                 name: ustr("__vm_start"),
                 ty: FunctionType {
@@ -154,11 +237,7 @@ impl<'i> InterpSess<'i> {
                     kind: FunctionTypeKind::Orphan,
                 },
                 code: start_code,
-            };
-
-            let result = vm.run_function(start_func);
-
-            Ok(result)
+            })
         } else {
             Err(self.diagnostics.clone())
         }
@@ -3,7 +3,7 @@ use super::{
     vm::{
         byte_seq::{ByteSeq, PutValue},
         bytecode::{Bytecode, Inst},
-        value::{Buffer, ExternFunction, ExternVariable, Function, IntrinsicFunction, Value},
+        value::{Buffer, ExternFunction, ExternVariable, Function, IntrinsicFunction, Pointer, Value},
     },
     IS_64BIT, WORD_SIZE,
 };
@@ -15,10 +15,14 @@ use crate::{
     },
     infer::normalize::Normalize,
     interp::vm::value::FunctionAddress,
-    types::{offset_of::OffsetOf, size_of::SizeOf, FloatType, InferType, IntType, Type, TypeId, UintType},
+    types::{
+        offset_of::OffsetOf, size_of::SizeOf, FloatType, FunctionType, FunctionTypeKind, InferType, IntType, Type,
+        TypeId, UintType,
+    },
     workspace::{BindingId, BindingInfoKind},
 };
 use byteorder::{NativeEndian, WriteBytesExt};
+use std::ptr;
 use ustr::ustr;
 
 #[derive(Clone, Copy)]
@@ -69,6 +73,9 @@ impl Lower for hir::Function {
 
                 let mut function_code = Bytecode::new();
 
+                // Deferred expressions don't cross function boundaries
+                let defer_scopes = std::mem::take(&mut sess.defer_scopes);
+
                 for (index, param) in params.iter().enumerate() {
                     let offset = -(params.len() as i16) + index as i16;
                     sess.env_mut().insert(param.id, offset);
@@ -80,6 +87,7 @@ impl Lower for hir::Function {
 
                 function_code.write_inst(Inst::Return);
 
+                sess.defer_scopes = defer_scopes;
                 sess.env_mut().pop_scope();
 
                 sess.interp.functions.insert(
@@ -250,6 +258,7 @@ impl Lower for hir::Sequence {
     fn lower(&self, sess: &mut InterpSess, code: &mut Bytecode, ctx: LowerContext) {
         if self.is_scope {
             sess.env_mut().push_scope();
+            sess.defer_scopes.push(vec![]);
         }
 
         for (index, expr) in self.statements.iter().enumerate() {
@@ -263,6 +272,9 @@ impl Lower for hir::Sequence {
         }
 
         if self.is_scope {
+            let defers = sess.defer_scopes.pop().unwrap();
+            lower_deferred(sess, code, &defers);
+
             sess.env_mut().pop_scope();
         }
     }
@@ -275,17 +287,73 @@ impl Lower for hir::Control {
             hir::Control::While(x) => x.lower(sess, code, ctx),
             hir::Control::Return(x) => x.lower(sess, code, ctx),
             hir::Control::Break(_) => {
+                let defer_depth = sess.loop_env_stack.last().unwrap().defer_depth;
+                lower_deferred_from(sess, code, defer_depth);
+
                 let pos = code.write_inst(Inst::Jmp(INVALID_JMP_OFFSET));
                 sess.loop_env_stack.last_mut().unwrap().break_offsets.push(pos);
             }
             hir::Control::Continue(_) => {
+                let defer_depth = sess.loop_env_stack.last().unwrap().defer_depth;
+                lower_deferred_from(sess, code, defer_depth);
+
                 let pos = code.write_inst(Inst::Jmp(INVALID_JMP_OFFSET));
                 sess.loop_env_stack.last_mut().unwrap().break_offsets.push(pos);
             }
+            hir::Control::Defer(x) => x.lower(sess, code, ctx),
         }
     }
 }
 
+impl Lower for hir::Defer {
+    fn lower(&self, sess: &mut InterpSess, code: &mut Bytecode, _ctx: LowerContext) {
+        // The expression is also lowered into its own code, which shares the frame of the enclosing function.
+        // This lets the VM run it when it panics before the scope exits
+        let mut defer_code = Bytecode::new();
+        defer_code.locals = code.locals;
+
+        self.expr.lower(sess, &mut defer_code, LowerContext { take_ptr: false });
+        defer_code.write_inst(Inst::Halt);
+
+        // Make sure that the frame has room for the locals of the deferred expression
+        code.locals = defer_code.locals;
+
+        let index = sess.interp.deferred_functions.len();
+
+        sess.interp.deferred_functions.push(Function {
+            id: hir::FunctionId::unknown(),
+            name: ustr("defer"),
+            ty: FunctionType {
+                params: vec![],
+                return_type: Box::new(Type::Unit),
+                varargs: None,
+                kind: FunctionTypeKind::Orphan,
+            },
+            code: defer_code,
+        });
+
+        code.write_inst(Inst::PushDefer(index as u32));
+        sess.defer_scopes.last_mut().unwrap().push((*self.expr).clone());
+
+        sess.push_const_unit(code);
+    }
+}
+
+// Lowers the deferred expressions of all scopes starting from `depth`, from the innermost scope to the outermost
+fn lower_deferred_from(sess: &mut InterpSess, code: &mut Bytecode, depth: usize) {
+    let defers = sess.defer_scopes[depth..].concat();
+    lower_deferred(sess, code, &defers);
+}
+
+// Lowers deferred expressions in reverse order, unregistering each one before it runs
+fn lower_deferred(sess: &mut InterpSess, code: &mut Bytecode, defers: &[hir::Node]) {
+    for defer in defers.iter().rev() {
+        code.write_inst(Inst::PopDefer);
+        defer.lower(sess, code, LowerContext { take_ptr: false });
+        code.write_inst(Inst::Pop);
+    }
+}
+
 impl Lower for hir::If {
     fn lower(&self, sess: &mut InterpSess, code: &mut Bytecode, _ctx: LowerContext) {
         lower_conditional(
@@ -334,7 +402,7 @@ impl Lower for hir::While {
 
         let exit_jmp = code.write_inst(Inst::Jmpf(INVALID_JMP_OFFSET));
 
-        sess.loop_env_stack.push(LoopEnv::new());
+        sess.loop_env_stack.push(LoopEnv::new(sess.defer_scopes.len()));
 
         self.body.lower(sess, code, LowerContext { take_ptr: false });
 
@@ -370,6 +438,7 @@ impl Lower for hir::While {
 impl Lower for hir::Return {
     fn lower(&self, sess: &mut InterpSess, code: &mut Bytecode, _ctx: LowerContext) {
         self.value.lower(sess, code, LowerContext { take_ptr: false });
+        lower_deferred_from(sess, code, 0);
         code.write_inst(Inst::Return);
     }
 }
@@ -646,6 +715,8 @@ impl Lower for hir::Literal {
             hir::Literal::Tuple(x) => x.lower(sess, code, ctx),
             hir::Literal::Array(x) => x.lower(sess, code, ctx),
             hir::Literal::ArrayFill(x) => x.lower(sess, code, ctx),
+            hir::Literal::Enum(x) => x.lower(sess, code, ctx),
+            hir::Literal::Closure(x) => x.lower(sess, code, ctx),
         }
     }
 }
@@ -721,6 +792,36 @@ impl Lower for hir::ArrayFillLiteral {
     }
 }
 
+impl Lower for hir::EnumLiteral {
+    fn lower(&self, sess: &mut InterpSess, code: &mut Bytecode, _ctx: LowerContext) {
+        let ty = self.ty.normalize(sess.tcx);
+        let enum_size = ty.size_of(WORD_SIZE) as u32;
+        let payload_offset = ty.offset_of(1, WORD_SIZE) as u32;
+
+        sess.push_const(code, Value::Type(ty));
+        code.write_inst(Inst::BufferAlloc(enum_size));
+
+        sess.push_const(code, Value::U32(self.variant_index));
+        code.write_inst(Inst::BufferPut(0));
+
+        self.payload.lower(sess, code, LowerContext { take_ptr: false });
+        code.write_inst(Inst::BufferPut(payload_offset));
+    }
+}
+
+impl Lower for hir::ClosureLiteral {
+    fn lower(&self, sess: &mut InterpSess, code: &mut Bytecode, _ctx: LowerContext) {
+        self.function.lower(sess, code, LowerContext { take_ptr: false });
+
+        match &self.env {
+            Some(env) => env.lower(sess, code, LowerContext { take_ptr: false }),
+            None => sess.push_const(code, Value::Pointer(Pointer::U8(ptr::null_mut()))),
+        }
+
+        code.write_inst(Inst::MakeClosure);
+    }
+}
+
 fn const_value_to_value(const_value: &ConstValue, ty: TypeId, sess: &mut InterpSess) -> Value {
     let ty = ty.normalize(sess.tcx);
 
@@ -798,6 +899,7 @@ fn const_value_to_value(const_value: &ConstValue, ty: TypeId, sess: &mut InterpS
                 .map(|(_, elem)| const_value_to_value(&elem.value, elem.ty, sess)),
             ty,
         )),
+        ConstValue::Enum(e) => Value::Buffer(Buffer::from_values([Value::U32(e.variant_index as _)], ty)),
         ConstValue::Array(array) => {
             let array_len = array.values.len();
 
@@ -821,7 +923,7 @@ fn const_value_to_value(const_value: &ConstValue, ty: TypeId, sess: &mut InterpS
             let function = sess.cache.functions.get(f.id).unwrap();
 
             match &function.kind {
-                hir::FunctionKind::Intrinsic(intrinsic) => Value::Intrinsic(IntrinsicFunction::from(*intrinsic)),
+                hir::FunctionKind::Intrinsic(intrinsic) => Value::Intrinsic(IntrinsicFunction::new(*intrinsic, &ty)),
                 _ => {
                     function.lower(sess, &mut Bytecode::new(), LowerContext { take_ptr: false });
                     Value::Function(FunctionAddress {
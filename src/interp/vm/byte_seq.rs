@@ -1,14 +1,29 @@
 use super::{
     super::{IS_64BIT, WORD_SIZE},
-    value::{Buffer, Pointer, Value},
+    value::{Buffer, Closure, FunctionAddress, Pointer, Value},
 };
 use crate::types::{FloatType, InferType, IntType, Type, UintType};
 use byteorder::{NativeEndian, ReadBytesExt, WriteBytesExt};
 use std::{io::Write, slice};
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct ByteSeq {
-    pub inner: Box<[u8]>,
+    inner: Bytes,
+}
+
+#[derive(Debug)]
+enum Bytes {
+    Owned(Box<[u8]>),
+    // A view into memory that isn't owned by the VM, i.e: memory that was allocated through FFI.
+    // Writes to the view are visible to anyone else who points to the same memory
+    Foreign(*mut u8, usize),
+}
+
+// Cloning a view copies the memory it points to, since a cloned buffer is a value of its own
+impl Clone for ByteSeq {
+    fn clone(&self) -> Self {
+        Self::copy_from_slice(self.as_ref())
+    }
 }
 
 impl From<&[u8]> for ByteSeq {
@@ -22,7 +37,7 @@ impl From<&[u8]> for ByteSeq {
 impl ByteSeq {
     pub fn new(len: usize) -> Self {
         Self {
-            inner: vec![0u8; len].into_boxed_slice(),
+            inner: Bytes::Owned(vec![0u8; len].into_boxed_slice()),
         }
     }
 
@@ -40,38 +55,54 @@ impl ByteSeq {
         vec.copy_from_slice(slice);
 
         Self {
-            inner: vec.into_boxed_slice(),
+            inner: Bytes::Owned(vec.into_boxed_slice()),
+        }
+    }
+
+    // Creates a view into `len` bytes at `data`, without copying them.
+    // `data` must outlive the view, and must not be null
+    pub unsafe fn from_raw_parts(data: *mut u8, len: usize) -> Self {
+        debug_assert!(!data.is_null());
+
+        Self {
+            inner: Bytes::Foreign(data, len),
         }
     }
 
     pub fn offset(&self, offset: usize) -> &[u8] {
-        &self.inner[offset..]
+        &self.as_ref()[offset..]
     }
 
     pub fn offset_mut(&mut self, offset: usize) -> &mut [u8] {
-        &mut self.inner[offset..]
+        &mut self.as_mut()[offset..]
     }
 
     #[allow(unused)]
     pub fn len(&self) -> usize {
-        self.inner.len()
+        self.as_ref().len()
     }
 
     pub fn as_ref(&self) -> &[u8] {
-        &self.inner
+        match &self.inner {
+            Bytes::Owned(bytes) => bytes,
+            Bytes::Foreign(data, len) => unsafe { slice::from_raw_parts(*data, *len) },
+        }
     }
 
     pub fn as_mut(&mut self) -> &mut [u8] {
-        &mut self.inner
+        match &mut self.inner {
+            Bytes::Owned(bytes) => bytes,
+            Bytes::Foreign(data, len) => unsafe { slice::from_raw_parts_mut(*data, *len) },
+        }
     }
 
     #[allow(unused)]
     pub fn as_ptr(&self) -> *const u8 {
-        self.inner.as_ptr()
+        self.as_ref().as_ptr()
     }
 
     pub fn as_mut_ptr(&mut self) -> *mut u8 {
-        self.inner.as_mut_ptr()
+        self.as_mut().as_mut_ptr()
     }
 }
 
@@ -102,13 +133,22 @@ impl PutValue for [u8] {
             Value::F64(v) => self.as_mut().write_f64::<NativeEndian>(*v).unwrap(),
             Value::Bool(v) => self.as_mut().write_u8(*v as u8).unwrap(),
             Value::Buffer(v) => {
-                self.as_mut().write_all(v.bytes.inner.as_ref()).unwrap();
+                self.as_mut().write_all(v.bytes.as_ref()).unwrap();
             }
             Value::Pointer(v) => self
                 .as_mut()
-                .write_uint::<NativeEndian>(v.as_inner_raw() as u64, WORD_SIZE)
+                .write_uint::<NativeEndian>(v.address() as u64, WORD_SIZE)
+                .unwrap(),
+            Value::Function(v) => self
+                .as_mut()
+                .write_uint::<NativeEndian>(v.to_raw() as u64, WORD_SIZE)
                 .unwrap(),
-            Value::Function(_) => todo!(),
+            Value::Closure(v) => {
+                let (function, env) = v.to_raw_parts();
+                let mut bytes = self.as_mut();
+                bytes.write_uint::<NativeEndian>(function as u64, WORD_SIZE).unwrap();
+                bytes.write_uint::<NativeEndian>(env as u64, WORD_SIZE).unwrap();
+            }
             _ => panic!("can't convert `{}` to raw self.as_mut().inner", value.to_string()),
         }
     }
@@ -167,7 +207,15 @@ impl GetValue for [u8] {
                 )),
             },
 
-            Type::Array(_, _) | Type::Tuple(_) | Type::Struct(_) => Value::Buffer(Buffer {
+            Type::Function(_) => Value::Function(unsafe {
+                FunctionAddress::from_raw(_ref.read_uint::<NativeEndian>(WORD_SIZE).unwrap() as _)
+            }),
+            Type::Closure(_) => {
+                let function = _ref.read_uint::<NativeEndian>(WORD_SIZE).unwrap() as _;
+                let env = _ref.read_uint::<NativeEndian>(WORD_SIZE).unwrap() as _;
+                Value::Closure(unsafe { Closure::from_raw_parts(function, env) })
+            }
+            Type::Array(_, _) | Type::Tuple(_) | Type::Struct(_) | Type::Enum(_) => Value::Buffer(Buffer {
                 bytes: ByteSeq::copy_from_slice(self),
                 ty: ty.clone(),
             }),
@@ -183,3 +231,36 @@ impl GetValue for [u8] {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{hir::const_value::ConstValue, test_utils::*};
+
+    #[test]
+    fn functions_and_closures_in_struct_fields() {
+        // The fields are written into the struct's buffer, and read back from it by value and through a pointer
+        let result = check_source(
+            r#"
+type Handler = struct {
+    f: closure(x: int) -> int,
+    g: fn(x: int) -> int
+}
+
+fn make_adder(n: int) -> closure(x: int) -> int = fn(x) { x + n }
+
+fn double(x: int) -> int = x * 2
+
+let result = comptime {
+    let handler = Handler { f: make_adder(40), g: double }
+    let p = &handler
+    p.f(handler.g(1))
+}
+
+fn main() = ()
+"#,
+        );
+
+        assert!(errors(&result).is_empty());
+        assert_eq!(global_const(&result, "result"), Some(ConstValue::Int(42)));
+    }
+}
@@ -142,6 +142,13 @@ impl Bytecode {
                 addr
             }
             Inst::Halt => self.write_op(Op::Halt),
+            Inst::MakeClosure => self.write_op(Op::MakeClosure),
+            Inst::PushDefer(index) => {
+                let addr = self.write_op(Op::PushDefer);
+                self.write_u32(index);
+                addr
+            }
+            Inst::PopDefer => self.write_op(Op::PopDefer),
         }
     }
 
@@ -333,6 +340,9 @@ pub enum Op {
     Copy,
     Swap,
     Halt,
+    MakeClosure,
+    PushDefer,
+    PopDefer,
 }
 
 impl From<u8> for Op {
@@ -382,6 +392,9 @@ impl From<u8> for Op {
             40 => Copy,
             41 => Swap,
             42 => Halt,
+            43 => MakeClosure,
+            44 => PushDefer,
+            45 => PopDefer,
             _ => panic!(),
         }
     }
@@ -434,6 +447,9 @@ impl From<Op> for u8 {
             Copy => 40,
             Swap => 41,
             Halt => 42,
+            MakeClosure => 43,
+            PushDefer => 44,
+            PopDefer => 45,
         }
     }
 }
@@ -483,6 +499,9 @@ impl Display for Op {
             Op::Copy => write!(f, "copy"),
             Op::Swap => write!(f, "swap"),
             Op::Halt => write!(f, "halt"),
+            Op::MakeClosure => write!(f, "make_closure"),
+            Op::PushDefer => write!(f, "push_defer"),
+            Op::PopDefer => write!(f, "pop_defer"),
         }
     }
 }
@@ -531,4 +550,8 @@ pub enum Inst {
     Copy(u32),
     Swap(u32),
     Halt,
+    MakeClosure,
+    // Registers a deferred expression, so it can be run if the VM panics before the scope exits
+    PushDefer(u32),
+    PopDefer,
 }
@@ -114,6 +114,7 @@ pub(super) fn bytecode_reader_write_single_inst<'a, W: Write>(reader: &mut Bytec
             Op::BufferFill => write!(w, " {}", reader.read_u32()).unwrap(),
             Op::Copy => write!(w, " {}", reader.read_u32()).unwrap(),
             Op::Swap => write!(w, " {}", reader.read_u32()).unwrap(),
+            Op::PushDefer => write!(w, " {}", reader.read_u32()).unwrap(),
             _ => (),
         }
     }
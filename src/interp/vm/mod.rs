@@ -1,28 +1,35 @@
 use self::{
     bytecode::{BytecodeReader, Op},
-    value::{FunctionValue, IntrinsicFunction, Pointer},
+    value::{Closure, FunctionValue, IntrinsicFunction, Pointer},
 };
 use super::{
     ffi::RawPointer,
     interp::Interp,
     vm::{
-        byte_seq::{ByteSeq, PutValue},
+        byte_seq::{ByteSeq, GetValue, PutValue},
         disassemble::bytecode_reader_write_single_inst,
         value::{Buffer, Function, Value},
     },
-    workspace::{BuildTargetValue, OptimizationLevelValue, WorkspaceValue},
+    workspace::{BuildTargetValue, OptimizationLevelValue, OutputKindValue, WorkspaceValue},
+    WORD_SIZE,
 };
 use crate::{
     common::{
-        build_options::{BuildOptions, CodegenOptions, OptimizationLevel},
+        build_options::{BuildOptions, CodegenOptions, OptimizationLevel, OutputKind},
         target::TargetPlatform,
     },
-    types::{FloatType, InferType, IntType, Type, UintType},
+    types::{offset_of::OffsetOf, size_of::SizeOf, FloatType, InferType, IntType, Type, UintType},
 };
 use bumpalo::Bump;
 use colored::Colorize;
 use path_absolutize::Absolutize;
-use std::{ffi::c_void, fmt::Display, path::PathBuf, ptr};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    ptr,
+};
 use ustr::ustr;
 
 macro_rules! cast_to_int {
@@ -41,6 +48,7 @@ macro_rules! cast_to_int {
             Value::F32(v) => Value::$name(v as $to),
             Value::F64(v) => Value::$name(v as $to),
             Value::Bool(v) => Value::$name(v as $to),
+            Value::Pointer(ref v) => Value::$name(v.address() as $to),
             _ => panic!("invalid value {}", $value.to_string()),
         }
     };
@@ -142,6 +150,11 @@ impl<T: ToString, const CAPACITY: usize> Stack<T, CAPACITY> {
         self.inner[index] = value;
     }
 
+    #[inline]
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.inner.insert(index, value)
+    }
+
     #[inline]
     pub fn truncate(&mut self, len: usize) {
         self.inner.truncate(len)
@@ -232,7 +245,7 @@ macro_rules! compare_op {
             (Value::Uint(a), Value::Uint(b)) => $vm.stack.push(Value::Bool(a $op b)),
             (Value::F32(a), Value::F32(b)) => $vm.stack.push(Value::Bool(a $op b)),
             (Value::F64(a), Value::F64(b)) => $vm.stack.push(Value::Bool(a $op b)),
-            (Value::Pointer(a), Value::Pointer(b)) => $vm.stack.push(Value::Bool(a.as_inner_raw() $op b.as_inner_raw())),
+            (Value::Pointer(a), Value::Pointer(b)) => $vm.stack.push(Value::Bool(a.address() $op b.address())),
             _ => panic!("invalid types in compare operation `{}` and `{}`", a.to_string() ,b.to_string())
         }
     };
@@ -253,6 +266,8 @@ pub struct VM<'vm> {
     pub stack: Stack<Value, STACK_MAX>,
     pub frames: Stack<StackFrame<'vm>, FRAMES_MAX>,
     pub frame: *mut StackFrame<'vm>,
+    // Deferred expressions whose scope hasn't exited yet, along with the stack slot of the frame they belong to
+    pub defers: Vec<(u32, usize)>,
 }
 
 impl<'vm> VM<'vm> {
@@ -263,11 +278,12 @@ impl<'vm> VM<'vm> {
             stack: Stack::new(),
             frames: Stack::new(),
             frame: ptr::null_mut(),
+            defers: vec![],
         }
     }
 
-    pub fn run_function(&mut self, function: Function) -> Value {
-        self.push_frame(&function);
+    pub fn run_function(&mut self, function: &Function) -> Value {
+        self.push_frame(function);
         self.run_inner()
     }
 
@@ -447,7 +463,13 @@ impl<'vm> VM<'vm> {
                 }
                 Op::Deref => match self.stack.pop() {
                     Value::Pointer(ptr) => {
-                        let value = unsafe { ptr.deref_value() };
+                        let value = match unsafe { ptr.deref_value() } {
+                            // A pointer to a function or closure views its representation in memory
+                            Value::Buffer(buf) if matches!(buf.ty, Type::Function(_) | Type::Closure(_)) => {
+                                buf.bytes.get_value(&buf.ty)
+                            }
+                            value => value,
+                        };
                         self.stack.push(value);
                     }
                     value => panic!("invalid value {}", value.to_string()),
@@ -540,6 +562,26 @@ impl<'vm> VM<'vm> {
                                 }
                             }
                         }
+                        Value::Closure(closure) => {
+                            let function = self.interp.get_function(closure.function.id).unwrap_or_else(|| {
+                                panic!("couldn't find '{}' {:?}", closure.function.name, closure.function.id)
+                            });
+
+                            match function {
+                                FunctionValue::Orphan(function) => {
+                                    // The environment is passed as the first argument, before the caller's arguments
+                                    if !closure.env.is_null() {
+                                        let env_slot = self.stack.len() - arg_count as usize;
+                                        self.stack.insert(env_slot, Value::Pointer(closure.env));
+                                    }
+
+                                    self.push_frame(function);
+                                }
+                                FunctionValue::Extern(function) => {
+                                    panic!("extern function `{}` can't be called as a closure", function.name)
+                                }
+                            }
+                        }
                         Value::Intrinsic(intrinsic) => self.dispatch_intrinsic(intrinsic),
                         value => panic!("tried to call uncallable value `{}`", value.to_string()),
                     }
@@ -652,6 +694,46 @@ impl<'vm> VM<'vm> {
                     let result = self.stack.pop();
                     break result;
                 }
+                Op::PushDefer => {
+                    let index = reader.read_u32();
+                    let stack_slot = self.frame().stack_slot;
+                    self.defers.push((index, stack_slot));
+                }
+                Op::PopDefer => {
+                    self.defers.pop();
+                }
+                Op::MakeClosure => {
+                    // The environment is moved to the heap, so it outlives the frame that created the closure.
+                    // A plain function's environment is a null pointer
+                    let env = match self.stack.pop() {
+                        Value::Pointer(env) => env,
+                        value => Pointer::from(Box::leak(Box::new(value))),
+                    };
+
+                    let function = self.stack.pop().into_function();
+
+                    self.stack.push(Value::Closure(Closure { function, env }));
+                }
+            }
+        }
+    }
+
+    // Runs the deferred expressions that were pending when the VM panicked, from the innermost to the outermost.
+    // Each one runs in the frame it was deferred in. Unwinding stops if a deferred expression panics as well
+    pub fn unwind_defers(&mut self) {
+        while let Some((index, stack_slot)) = self.defers.pop() {
+            let function = &self.interp.deferred_functions[index as usize] as *const Function;
+            let frame_count = self.frames.len();
+
+            self.frames.push(StackFrame::<'vm>::new(function, stack_slot));
+            self.frame = self.frames.last_mut() as _;
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| self.run_inner()));
+
+            self.frames.truncate(frame_count);
+
+            if result.is_err() {
+                break;
             }
         }
     }
@@ -769,7 +851,7 @@ impl<'vm> VM<'vm> {
 
     fn dispatch_intrinsic(&mut self, intrinsic: IntrinsicFunction) {
         match intrinsic {
-            IntrinsicFunction::StartWorkspace => {
+            IntrinsicFunction::StartWorkspace(return_type) => {
                 let value = self.stack.pop();
                 let workspace_value = WorkspaceValue::from(&value);
 
@@ -787,14 +869,20 @@ impl<'vm> VM<'vm> {
                     source_file,
                     output_file: Some(output_file),
                     target_platform: match &workspace_value.build_options.target {
-                        BuildTargetValue::Auto => TargetPlatform::current().unwrap(),
+                        BuildTargetValue::Auto => self.interp.build_options.target_platform.clone(),
                         BuildTargetValue::Linux => TargetPlatform::LinuxAmd64,
                         BuildTargetValue::Windows => TargetPlatform::WindowsAmd64,
+                        BuildTargetValue::Platform(platform) => platform.clone(),
                     },
                     optimization_level: match &workspace_value.build_options.optimization_level {
                         OptimizationLevelValue::Debug => OptimizationLevel::Debug,
                         OptimizationLevelValue::Release => OptimizationLevel::Release,
                     },
+                    output_kind: match &workspace_value.build_options.output_kind {
+                        OutputKindValue::Executable => OutputKind::Executable,
+                        OutputKindValue::StaticLibrary => OutputKind::StaticLibrary,
+                        OutputKindValue::SharedLibrary => OutputKind::SharedLibrary,
+                    },
                     emit_times: self.interp.build_options.emit_times,
                     emit_hir: self.interp.build_options.emit_hir,
                     emit_bytecode: self.interp.build_options.emit_bytecode,
@@ -803,30 +891,46 @@ impl<'vm> VM<'vm> {
                         emit_llvm_ir: self.interp.build_options.codegen_options.emit_llvm_ir(),
                     },
                     include_paths: vec![],
+                    linker: self.interp.build_options.linker.clone(),
                     check_mode: false,
+                    completion_offset: None,
+                    source_overrides: HashMap::new(),
+                    incremental: self.interp.build_options.incremental,
+                    test_mode: false,
                 };
 
                 let result = crate::driver::start_workspace(workspace_value.name.to_string(), build_options);
 
-                let (output_file, ok) = if let Some(output_file) = &result.output_file {
-                    // TODO: Remove null terminator after implementing printing/formatting
-                    (
-                        self.bump
-                            .alloc_slice_copy(format!("{}\0", output_file.to_str().unwrap()).as_bytes()),
-                        true,
-                    )
-                } else {
-                    (self.bump.alloc_slice_copy(b""), false)
+                // The output file is returned as `some`, and a failed build as `none`
+                let variant_name = if result.output_file.is_some() { "some" } else { "none" };
+
+                let variant_index = return_type
+                    .as_enum()
+                    .variants
+                    .iter()
+                    .position(|variant| variant.name == variant_name)
+                    .unwrap();
+
+                let mut result_value = Buffer {
+                    bytes: ByteSeq::new(return_type.size_of(WORD_SIZE)),
+                    ty: return_type.clone(),
                 };
 
-                let result_type = Type::Tuple(vec![Type::str_pointer(), Type::Bool]);
+                result_value
+                    .bytes
+                    .offset_mut(0)
+                    .put_value(&Value::U32(variant_index as u32));
 
-                let result_value = Value::Buffer(Buffer::from_values(
-                    [Value::Buffer(Buffer::from_str_bytes(output_file)), Value::Bool(ok)],
-                    result_type,
-                ));
+                if let Some(output_file) = &result.output_file {
+                    let output_file = self.bump.alloc_slice_copy(output_file.to_str().unwrap().as_bytes());
 
-                self.stack.push(result_value);
+                    result_value
+                        .bytes
+                        .offset_mut(return_type.offset_of(1, WORD_SIZE))
+                        .put_value(&Value::Buffer(Buffer::from_str_bytes(output_file)));
+                }
+
+                self.stack.push(Value::Buffer(result_value));
             }
         }
     }
@@ -854,20 +958,16 @@ impl<'vm> VM<'vm> {
             Type::Float(FloatType::Float) | Type::Infer(_, InferType::AnyFloat) => {
                 cast_to_float!(value => F64, f64)
             }
+            // Fat pointers that are cast to each other share the same layout, i.e: `*[]u8` and `*str`
+            Type::Pointer(..) if ty.is_fat_pointer() => match value {
+                Value::Buffer(buf) => Value::Buffer(Buffer { bytes: buf.bytes, ty }),
+                _ => panic!("invalid value {}", value.to_string()),
+            },
             Type::Pointer(inner, _) => {
                 let raw_ptr = match value {
                     Value::Int(value) => value as RawPointer,
                     Value::Uint(value) => value as RawPointer,
-                    Value::Pointer(ptr) => match ptr {
-                        Pointer::Buffer(buf) => {
-                            if buf.is_null() {
-                                std::ptr::null_mut::<c_void>()
-                            } else {
-                                unsafe { &mut *buf }.bytes.as_mut_ptr() as _
-                            }
-                        }
-                        _ => ptr.as_inner_raw(),
-                    },
+                    Value::Pointer(ptr) => ptr.address(),
                     _ => panic!("invalid value {}", value.to_string()),
                 };
 
@@ -925,3 +1025,40 @@ pub enum TraceLevel {
     Minimal,
     Full,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{hir::const_value::ConstValue, test_utils::*};
+
+    #[test]
+    fn string_appended_to_itself() {
+        // Appending grows the string past its capacity, which moves its bytes while they're being appended
+        let result = check_source(
+            r#"
+use std.string.String
+
+let result = comptime {
+    let mut s = String.from_str("abcde")
+    s.append(&s)
+
+    let expected = "abcdeabcde"
+    let mut equal = s.len == expected.len
+    let mut i: uint = 0
+
+    while equal && i < s.len {
+        equal = s.ptr[i] == expected[i]
+        i += 1
+    }
+
+    s.free()
+    equal
+}
+
+fn main() = ()
+"#,
+        );
+
+        assert!(errors(&result).is_empty());
+        assert_eq!(global_const(&result, "result"), Some(ConstValue::Bool(true)));
+    }
+}
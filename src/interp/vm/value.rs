@@ -8,7 +8,7 @@ use crate::{
     ast::ExternLibrary,
     hir::{
         self,
-        const_value::{ConstArray, ConstElement, ConstExternVariable, ConstFunction, ConstValue},
+        const_value::{ConstArray, ConstElement, ConstEnum, ConstExternVariable, ConstFunction, ConstValue},
     },
     infer::type_ctx::TypeCtx,
     interp::interp::Interp,
@@ -21,7 +21,7 @@ use crate::{
 use byteorder::{NativeEndian, ReadBytesExt, WriteBytesExt};
 use indexmap::IndexMap;
 use paste::paste;
-use std::{fmt::Display, mem, slice, str};
+use std::{cell::RefCell, collections::HashMap, fmt::Display, mem, slice, str};
 use ustr::{ustr, Ustr};
 
 macro_rules! impl_value {
@@ -256,6 +256,7 @@ impl_value! {
     Buffer(Buffer),
     Pointer(Pointer),
     Function(FunctionAddress),
+    Closure(Closure),
     ExternVariable(ExternVariable),
     Intrinsic(IntrinsicFunction),
     Type(Type),
@@ -287,16 +288,7 @@ impl Buffer {
     }
 
     pub fn as_str(&self) -> &str {
-        let slice = self.as_slice::<u8>();
-
-        // TODO: remove this nul terminator handle hack
-        let slice_without_nul = if slice.last().map_or(false, |c| *c == b'\0') {
-            &slice[..slice.len() - 1]
-        } else {
-            slice
-        };
-
-        std::str::from_utf8(slice_without_nul).unwrap()
+        std::str::from_utf8(self.as_slice::<u8>()).unwrap()
     }
 
     #[allow(unused)]
@@ -379,6 +371,8 @@ impl Buffer {
                 .enumerate()
                 .map(|(index, _)| self.get_value_at_index(index))
                 .collect(),
+            // Only the tag is read, since the payload's type depends on it
+            Type::Enum(_) => vec![self.get_value_at_index(0)],
             Type::Array(_, array_len) => (0..*array_len)
                 .into_iter()
                 .map(|index| self.get_value_at_index(index))
@@ -409,6 +403,13 @@ impl Buffer {
             Type::Unit => panic!("{}", index),
             Type::Struct(struct_type) => self.bytes.offset(offset).get_value(&struct_type.fields[index].ty),
             Type::Tuple(elements) => self.bytes.offset(offset).get_value(&elements[index]),
+            Type::Enum(enum_type) => match index {
+                0 => self.bytes.offset(offset).get_value(&Type::u32()),
+                _ => self
+                    .bytes
+                    .offset(offset)
+                    .get_value(&enum_type.variants[index - 1].payload_type()),
+            },
             Type::Array(ty, _) => self.bytes.offset(offset).get_value(ty),
             Type::Pointer(inner, _) => match inner.as_ref() {
                 Type::Slice(ty) | Type::Str(ty) => match index {
@@ -438,6 +439,55 @@ pub struct FunctionAddress {
     pub name: Ustr,
 }
 
+// A function, paired with a pointer to its captured environment.
+// A null environment means that the function doesn't capture anything, and is called as-is
+#[derive(Debug, Clone)]
+pub struct Closure {
+    pub function: FunctionAddress,
+    pub env: Pointer,
+}
+
+impl FunctionAddress {
+    // A function is stored in memory as a pointer to its address.
+    // Each address is allocated once and never freed, so a stored function stays valid for as long as the VM runs
+    pub fn to_raw(&self) -> RawPointer {
+        thread_local! {
+            static ADDRESSES: RefCell<HashMap<(hir::FunctionId, bool, Ustr), RawPointer>> = RefCell::new(HashMap::new());
+        }
+
+        ADDRESSES.with(|addresses| {
+            *addresses
+                .borrow_mut()
+                .entry((self.id, self.is_extern, self.name))
+                .or_insert_with(|| Box::leak(Box::new(self.clone())) as *mut FunctionAddress as RawPointer)
+        })
+    }
+
+    pub unsafe fn from_raw(ptr: RawPointer) -> Self {
+        assert!(!ptr.is_null(), "tried to read a function from a null pointer");
+        (*(ptr as *const FunctionAddress)).clone()
+    }
+}
+
+impl Closure {
+    // A closure is stored in memory as its function, followed by its environment pointer
+    pub fn to_raw_parts(&self) -> (RawPointer, RawPointer) {
+        (self.function.to_raw(), self.env.as_inner_raw())
+    }
+
+    // The environment is always a struct that was moved to the heap, or null
+    pub unsafe fn from_raw_parts(function: RawPointer, env: RawPointer) -> Self {
+        Self {
+            function: FunctionAddress::from_raw(function),
+            env: if env.is_null() {
+                Pointer::U8(std::ptr::null_mut())
+            } else {
+                Pointer::Buffer(env as *mut Buffer)
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExternFunction {
     pub lib_path: Ustr,
@@ -452,19 +502,24 @@ pub struct ExternVariable {
     pub ty: Type,
 }
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum IntrinsicFunction {
-    StartWorkspace,
+    // Holds its return type, which is the `Option` that the output file is returned in
+    StartWorkspace(Type),
 }
 
-impl From<hir::Intrinsic> for IntrinsicFunction {
-    fn from(intrinsic: hir::Intrinsic) -> Self {
+impl IntrinsicFunction {
+    pub fn new(intrinsic: hir::Intrinsic, ty: &Type) -> Self {
         match intrinsic {
-            hir::Intrinsic::StartWorkspace => IntrinsicFunction::StartWorkspace,
+            hir::Intrinsic::StartWorkspace => {
+                IntrinsicFunction::StartWorkspace(ty.as_function().return_type.as_ref().clone())
+            }
             hir::Intrinsic::Location
             | hir::Intrinsic::CallerLocation
             | hir::Intrinsic::CompilerError
-            | hir::Intrinsic::CompilerWarning => panic!(
+            | hir::Intrinsic::CompilerWarning
+            | hir::Intrinsic::Print
+            | hir::Intrinsic::Println => panic!(
                 "intrinsic function '{}' should have been evaluated at compile-time",
                 intrinsic
             ),
@@ -485,7 +540,7 @@ impl Display for IntrinsicFunction {
             f,
             "{}",
             match self {
-                IntrinsicFunction::StartWorkspace => "start_workspace",
+                IntrinsicFunction::StartWorkspace(_) => "start_workspace",
             }
         )
     }
@@ -526,8 +581,9 @@ impl From<&Type> for ValueKind {
                 _ => Self::Pointer,
             },
             Type::Function(_) => Self::Function,
-            Type::Array(_, _) | Type::Tuple(_) | Type::Struct(_) => Self::Buffer,
-            Type::Module(_) => panic!(),
+            Type::Closure(_) => Self::Closure,
+            Type::Array(_, _) | Type::Tuple(_) | Type::Struct(_) | Type::Enum(_) => Self::Buffer,
+            Type::Module(_) | Type::Trait(_) | Type::Param(_) => panic!(),
             Type::Type(_) => Self::Type,
             Type::Infer(_, InferType::AnyInt) => Self::Int,
             Type::Infer(_, InferType::AnyFloat) => {
@@ -580,7 +636,9 @@ impl Value {
                 }
             },
             Type::Pointer(ty, _) => Self::Pointer(Pointer::from_type_and_ptr(ty, *(ptr as *mut RawPointer))),
-            Type::Function(_) => todo!(),
+            Type::Function(_) | Type::Closure(_) => {
+                slice::from_raw_parts(ptr as *const u8, ty.size_of(WORD_SIZE)).get_value(ty)
+            }
             Type::Array(inner, size) => Self::Buffer(Buffer {
                 bytes: ByteSeq::copy_from_raw_parts(ptr as _, *size * inner.size_of(WORD_SIZE)),
                 ty: ty.clone(),
@@ -594,8 +652,8 @@ impl Value {
                     ty: ty.clone(),
                 })
             }
-            Type::Struct(struct_ty) => {
-                let size = struct_ty.size_of(WORD_SIZE);
+            Type::Struct(_) | Type::Enum(_) => {
+                let size = ty.size_of(WORD_SIZE);
                 let slice = slice::from_raw_parts(ptr as *const u8, size);
 
                 Self::Buffer(Buffer {
@@ -637,6 +695,21 @@ impl Value {
             } else {
                 interp.functions.get(&f.id).unwrap().ty.clone()
             }),
+            Self::Closure(c) => Type::Closure(if c.function.is_extern {
+                interp.extern_functions.get(&c.function.id).unwrap().ty.clone()
+            } else {
+                let ty = &interp.functions.get(&c.function.id).unwrap().ty;
+
+                // The environment parameter isn't part of the closure's signature
+                if c.env.is_null() {
+                    ty.clone()
+                } else {
+                    FunctionType {
+                        params: ty.params[1..].to_vec(),
+                        ..ty.clone()
+                    }
+                }
+            }),
             Self::ExternVariable(v) => v.ty.clone(),
             Self::Intrinsic(_) | Self::Type(_) => todo!(),
         }
@@ -731,6 +804,19 @@ impl Value {
 
                     Ok(ConstValue::Struct(fields))
                 }
+                Type::Enum(enum_type) => {
+                    let variant_index = buf.get_value_at_index(0).into_u32() as usize;
+                    let variant = &enum_type.variants[variant_index];
+
+                    if variant.has_payload() {
+                        Err("enum variant with a payload")
+                    } else {
+                        Ok(ConstValue::Enum(ConstEnum {
+                            variant_index,
+                            variant_name: variant.name,
+                        }))
+                    }
+                }
                 ty => panic!("value type mismatch. expected an aggregate type, got {:?}", ty),
             },
             Self::Function(f) => Ok(ConstValue::Function(ConstFunction { id: f.id, name: f.name })),
@@ -741,6 +827,7 @@ impl Value {
                 ty: tcx.bound(v.ty, eval_span),
             })),
             Self::Pointer(_) => Err("pointer"),
+            Self::Closure(_) => Err("closure"),
             Self::Intrinsic(_) => Err("intrinsic function"),
         }
     }
@@ -785,45 +872,13 @@ impl Pointer {
                 }
             },
             Type::Pointer(inner, _) => match inner.as_ref() {
-                Type::Slice(_) | Type::Str(_) => {
-                    if ptr.is_null() {
-                        Self::Buffer(std::ptr::null_mut())
-                    } else {
-                        let bytes = ByteSeq::copy_from_raw_parts(ptr as _, ty.size_of(WORD_SIZE));
-
-                        let buf = Box::new(Buffer { bytes, ty: ty.clone() });
-
-                        // Note (Ron): Leak
-                        Self::Buffer(Box::leak(buf) as *mut Buffer)
-                    }
-                }
+                Type::Slice(_) | Type::Str(_) => Self::buffer_view(ty, ptr, ty.size_of(WORD_SIZE)),
                 _ => Self::from_type_and_ptr(inner, ptr),
             },
-            Type::Function(_) => todo!(),
-            Type::Array(inner, size) => {
-                if ptr.is_null() {
-                    Self::Buffer(std::ptr::null_mut())
-                } else {
-                    let bytes = ByteSeq::copy_from_raw_parts(ptr as _, *size * inner.size_of(WORD_SIZE));
-
-                    let buf = Box::new(Buffer { bytes, ty: ty.clone() });
-
-                    // Note (Ron): Leak
-                    Self::Buffer(Box::leak(buf) as *mut Buffer)
-                }
-            }
-            Type::Tuple(_) | Type::Struct(_) => {
-                if ptr.is_null() {
-                    Self::Buffer(std::ptr::null_mut())
-                } else {
-                    let bytes = ByteSeq::copy_from_raw_parts(ptr as _, ty.size_of(WORD_SIZE));
-
-                    let buf = Box::new(Buffer { bytes, ty: ty.clone() });
-
-                    // Note (Ron): Leak
-                    Self::Buffer(Box::leak(buf) as *mut Buffer)
-                }
-            }
+            // Functions and closures are viewed through their representation in memory, see `FunctionAddress::to_raw`
+            Type::Function(_) | Type::Closure(_) => Self::buffer_view(ty, ptr, ty.size_of(WORD_SIZE)),
+            Type::Array(inner, size) => Self::buffer_view(ty, ptr, *size * inner.size_of(WORD_SIZE)),
+            Type::Tuple(_) | Type::Struct(_) | Type::Enum(_) => Self::buffer_view(ty, ptr, ty.size_of(WORD_SIZE)),
             Type::Infer(_, InferType::AnyInt) => Self::Int(ptr as _),
             Type::Infer(_, InferType::AnyFloat) => {
                 if IS_64BIT {
@@ -836,6 +891,34 @@ impl Pointer {
         }
     }
 
+    // A pointer to a buffer that views the memory at `ptr`, so writing through the pointer writes to `ptr`
+    fn buffer_view(ty: &Type, ptr: RawPointer, size: usize) -> Self {
+        if ptr.is_null() {
+            Self::Buffer(std::ptr::null_mut())
+        } else {
+            let bytes = unsafe { ByteSeq::from_raw_parts(ptr as _, size) };
+
+            let buf = Box::new(Buffer { bytes, ty: ty.clone() });
+
+            // Note (Ron): Leak
+            Self::Buffer(Box::leak(buf) as *mut Buffer)
+        }
+    }
+
+    // The address this pointer points to. A pointer to a buffer points to the buffer's bytes
+    pub fn address(&self) -> RawPointer {
+        match self {
+            Self::Buffer(buf) => {
+                if buf.is_null() {
+                    std::ptr::null_mut()
+                } else {
+                    unsafe { &mut **buf }.bytes.as_mut_ptr() as _
+                }
+            }
+            _ => self.as_inner_raw(),
+        }
+    }
+
     pub fn get_type(&self) -> Type {
         match self {
             Self::I8(_) => Type::i8(),
@@ -884,9 +967,22 @@ impl Pointer {
             (Self::F32(p), Value::F32(v)) => slice(p).write_f32::<NativeEndian>(v).unwrap(),
             (Self::F64(p), Value::F64(v)) => slice(p).write_f64::<NativeEndian>(v).unwrap(),
             (Self::Bool(p), Value::Bool(v)) => slice(p).write_u8(v as u8).unwrap(),
-            (Self::Buffer(p), Value::Buffer(v)) => **p = v,
+            (Self::Buffer(p), Value::Buffer(v)) => {
+                let buf = &mut **p;
+
+                // The buffer is written in place, so pointers to its bytes stay valid.
+                // This also writes through buffers that view foreign memory
+                if buf.bytes.len() == v.bytes.len() {
+                    buf.bytes.as_mut().copy_from_slice(v.bytes.as_ref());
+                    buf.ty = v.ty;
+                } else {
+                    *buf = v;
+                }
+            }
+            (Self::Buffer(p), value @ (Value::Function(_) | Value::Closure(_))) => (**p).bytes.put_value(&value),
             (Self::Pointer(p), Value::Pointer(v)) => **p = v,
             (Self::Function(p), Value::Function(v)) => **p = v,
+            (Self::Closure(p), Value::Closure(v)) => **p = v,
             (Self::Type(p), Value::Type(v)) => **p = v,
             (p, v) => panic!("invalid pair {:?} , {}", p, v.to_string()),
         }
@@ -917,6 +1013,7 @@ impl Display for Value {
                 Value::Buffer(v) => v.to_string(),
                 Value::Pointer(p) => p.to_string(),
                 Value::Function(f) => f.to_string(),
+                Value::Closure(c) => c.to_string(),
                 Value::ExternVariable(v) => v.to_string(),
                 Value::Intrinsic(v) => v.to_string(),
                 Value::Type(ty) => format!("type {:?}", ty),
@@ -940,6 +1037,7 @@ impl Display for Buffer {
 
             let len = match &self.ty {
                 Type::Struct(s) => s.fields.len(),
+                Type::Enum(_) => 1,
                 Type::Tuple(elements) => elements.len(),
                 Type::Array(_, size) => *size,
                 Type::Pointer(inner, _) => match inner.as_ref() {
@@ -960,6 +1058,10 @@ impl Display for Buffer {
                 Type::Struct(_) => {
                     write!(f, "{{{}{}}}", values_joined, extra_values_str)
                 }
+                Type::Enum(enum_type) => {
+                    let variant_index = self.get_value_at_index(0).into_u32() as usize;
+                    write!(f, "{}", enum_type.variants[variant_index].name)
+                }
                 Type::Tuple(_) => {
                     write!(f, "({}{})", values_joined, extra_values_str)
                 }
@@ -988,6 +1090,12 @@ impl Display for FunctionAddress {
     }
 }
 
+impl Display for Closure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "closure {}", self.function.name)
+    }
+}
+
 impl Display for ExternFunction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "extern fn {}", self.name)
@@ -1023,6 +1131,7 @@ impl Display for Pointer {
                     Pointer::Buffer(v) => (**v).to_string(),
                     Pointer::Pointer(p) => (**p).to_string(),
                     Pointer::Function(f) => (**f).to_string(),
+                    Pointer::Closure(c) => (**c).to_string(),
                     Pointer::ExternVariable(v) => (**v).to_string(),
                     Pointer::Intrinsic(v) => (**v).to_string(),
                     Pointer::Type(ty) => format!("type {:?}", (**ty)),
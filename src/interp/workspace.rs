@@ -2,7 +2,8 @@ use super::{
     vm::{byte_seq::GetValue, value::Value},
     WORD_SIZE,
 };
-use crate::types::offset_of::OffsetOf;
+use crate::{common::target::TargetPlatform, types::offset_of::OffsetOf};
+use ustr::Ustr;
 
 #[derive(Debug, Clone)]
 pub struct WorkspaceValue {
@@ -36,6 +37,7 @@ pub struct BuildOptionsValue {
     pub output_file: String,
     pub target: BuildTargetValue,
     pub optimization_level: OptimizationLevelValue,
+    pub output_kind: OutputKindValue,
 }
 
 impl From<&Value> for BuildOptionsValue {
@@ -69,31 +71,40 @@ impl From<&Value> for BuildOptionsValue {
                 .offset(buf.ty.offset_of(3, WORD_SIZE))
                 .get_value(&field_types[3].ty),
         );
+        let output_kind = OutputKindValue::from(
+            &buf.bytes
+                .offset(buf.ty.offset_of(4, WORD_SIZE))
+                .get_value(&field_types[4].ty),
+        );
 
         Self {
             input_file,
             output_file,
             target,
             optimization_level,
+            output_kind,
         }
     }
 }
 
 #[derive(Debug, Clone)]
-#[repr(usize)]
 pub enum BuildTargetValue {
-    Auto = 0,
-    Linux = 1,
-    Windows = 2,
+    Auto,
+    Linux,
+    Windows,
+    Platform(TargetPlatform),
 }
 
 impl From<&Value> for BuildTargetValue {
     fn from(value: &Value) -> Self {
-        match value.as_uint() {
-            0 => Self::Auto,
-            1 => Self::Linux,
-            2 => Self::Windows,
-            x => panic!("{}", x),
+        match variant_name(value).as_str() {
+            "auto" => Self::Auto,
+            "linux" => Self::Linux,
+            "windows" => Self::Windows,
+            name => match TargetPlatform::from_name(name) {
+                Some(platform) => Self::Platform(platform),
+                None => panic!("`BuildTarget.{}` is not a known target platform", name),
+            },
         }
     }
 }
@@ -107,10 +118,61 @@ pub enum OptimizationLevelValue {
 
 impl From<&Value> for OptimizationLevelValue {
     fn from(value: &Value) -> Self {
-        match value.as_uint() {
-            0 => Self::Debug,
-            1 => Self::Release,
-            x => panic!("{}", x),
+        match variant_name(value).as_str() {
+            "debug" => Self::Debug,
+            "release" => Self::Release,
+            name => panic!("`OptimizationLevel.{}` is not a known optimization level", name),
         }
     }
 }
+
+#[derive(Debug, Clone)]
+#[repr(usize)]
+pub enum OutputKindValue {
+    Executable = 0,
+    StaticLibrary = 1,
+    SharedLibrary = 2,
+}
+
+impl From<&Value> for OutputKindValue {
+    fn from(value: &Value) -> Self {
+        match variant_name(value).as_str() {
+            "executable" => Self::Executable,
+            "static_library" => Self::StaticLibrary,
+            "shared_library" => Self::SharedLibrary,
+            name => panic!("`OutputKind.{}` is not a known output kind", name),
+        }
+    }
+}
+
+// Enum values are mapped by their variant's name, so that they don't depend on the order of the variants in `std.build`
+fn variant_name(value: &Value) -> Ustr {
+    let buf = value.as_buffer();
+    let index = buf.get_value_at_index(0).into_u32() as usize;
+    buf.ty.as_enum().variants[index].name
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::target::TargetPlatform;
+
+    // Every target platform can be built for from a build script, and every target of a build script is known
+    #[test]
+    fn build_targets_are_target_platforms() {
+        let source = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/stdlib/build.chl")).unwrap();
+
+        let start = source.find("pub type BuildTarget = enum {").unwrap();
+        let end = start + source[start..].find('}').unwrap();
+
+        let platforms: Vec<TargetPlatform> = source[start..end]
+            .lines()
+            .skip(1)
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty() && !line.starts_with("//"))
+            .filter(|name| !matches!(*name, "auto" | "linux" | "windows"))
+            .map(|name| TargetPlatform::from_name(name).unwrap_or_else(|| panic!("unknown target `{}`", name)))
+            .collect();
+
+        assert_eq!(platforms, TargetPlatform::all());
+    }
+}
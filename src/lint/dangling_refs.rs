@@ -0,0 +1,409 @@
+use super::LintSess;
+use crate::{
+    error::diagnostic::{Diagnostic, Label},
+    hir,
+    infer::normalize::Normalize,
+    span::Span,
+    types::Type,
+    workspace::{BindingId, ScopeLevel},
+};
+use std::collections::{HashMap, HashSet};
+
+// The local binding a reference points into, and the scope it lives in
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    id: BindingId,
+    scope_level: ScopeLevel,
+    // The span of the reference
+    span: Span,
+}
+
+impl Region {
+    // Picks the region that ends first
+    fn innermost(a: Option<Region>, b: Option<Region>) -> Option<Region> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b.scope_level > a.scope_level { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+enum DanglingRefErr {
+    Returned { region: Region },
+    OutlivedBy { region: Region, id: BindingId, span: Span },
+}
+
+impl<'s> LintSess<'s> {
+    // Finds references to local bindings that outlive the binding they point to.
+    // A reference escapes its binding's scope when it is returned, or stored in a binding of an outer scope.
+    // References that are stored in literals are tracked through the literal's value.
+    pub fn check_dangling_refs(&mut self, function: &hir::Function) {
+        let body = match &function.kind {
+            hir::FunctionKind::Orphan { body: Some(body), .. } => body,
+            _ => return,
+        };
+
+        let mut checker = RegionChecker {
+            sess: self,
+            regions: HashMap::new(),
+            errors: vec![],
+            reported: HashSet::new(),
+        };
+
+        let region = checker.sequence(body);
+
+        // The function's body evaluates to its return value
+        if let Type::Function(function_type) = function.ty.normalize(checker.sess.tcx) {
+            if !function_type.return_type.normalize(checker.sess.tcx).is_unit() {
+                checker.check_return(region);
+            }
+        }
+
+        let errors = std::mem::take(&mut checker.errors);
+
+        for err in errors {
+            let diagnostic = self.dangling_ref_err_to_diagnostic(err);
+            self.workspace.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn dangling_ref_err_to_diagnostic(&self, err: DanglingRefErr) -> Diagnostic {
+        match err {
+            DanglingRefErr::Returned { region } => {
+                let binding_info = self.workspace.binding_infos.get(region.id).unwrap();
+
+                Diagnostic::error()
+                    .with_message(format!(
+                        "cannot return a reference to local binding `{}`",
+                        binding_info.name
+                    ))
+                    .with_label(Label::primary(
+                        region.span,
+                        "returns a reference to data owned by the current function",
+                    ))
+                    .with_label(Label::secondary(
+                        binding_info.span,
+                        format!("`{}` is declared here", binding_info.name),
+                    ))
+            }
+            DanglingRefErr::OutlivedBy { region, id, span } => {
+                let binding_info = self.workspace.binding_infos.get(region.id).unwrap();
+                let outer_binding_info = self.workspace.binding_infos.get(id).unwrap();
+
+                Diagnostic::error()
+                    .with_message(format!("`{}` does not live long enough", binding_info.name))
+                    .with_label(Label::primary(
+                        region.span,
+                        "referenced value does not live long enough",
+                    ))
+                    .with_label(Label::secondary(
+                        span,
+                        format!("the reference is stored in `{}` here", outer_binding_info.name),
+                    ))
+                    .with_note(format!(
+                        "`{}` goes out of scope before `{}` does",
+                        binding_info.name, outer_binding_info.name
+                    ))
+            }
+        }
+    }
+}
+
+struct RegionChecker<'a, 's> {
+    sess: &'a mut LintSess<'s>,
+    // The innermost region that each local binding's value may refer to
+    regions: HashMap<BindingId, Region>,
+    errors: Vec<DanglingRefErr>,
+    // Each reference is reported once
+    reported: HashSet<Span>,
+}
+
+impl<'a, 's> RegionChecker<'a, 's> {
+    // Returns the innermost region the node's value may refer to
+    fn node(&mut self, node: &hir::Node) -> Option<Region> {
+        let region = self.node_inner(node);
+
+        // Only values that can hold a reference carry a region, i.e: `p.*` or `p as uint` don't
+        if self.can_hold_ref(&node.ty().normalize(self.sess.tcx)) {
+            region
+        } else {
+            None
+        }
+    }
+
+    fn node_inner(&mut self, node: &hir::Node) -> Option<Region> {
+        match node {
+            hir::Node::Const(_) => None,
+            hir::Node::Binding(binding) => {
+                let region = self.node(&binding.value);
+                self.store(binding.id, region, binding.span);
+                None
+            }
+            hir::Node::Id(id) => self.regions.get(&id.id).copied(),
+            hir::Node::Assign(assign) => {
+                let region = self.node(&assign.rhs);
+
+                match self.root_binding(&assign.lhs) {
+                    Some(id) => self.store(id, region, assign.lhs.span()),
+                    None => {
+                        self.node(&assign.lhs);
+                    }
+                }
+
+                None
+            }
+            hir::Node::MemberAccess(access) => self.node(&access.value),
+            hir::Node::Call(call) => {
+                self.node(&call.callee);
+
+                for arg in call.args.iter() {
+                    self.node(arg);
+                }
+
+                // Without knowing where the returned value points to, it isn't tracked
+                None
+            }
+            hir::Node::Cast(cast) => self.node(&cast.value),
+            hir::Node::Sequence(sequence) => self.sequence(sequence),
+            hir::Node::Control(control) => match control {
+                hir::Control::If(if_) => {
+                    self.node(&if_.condition);
+                    let then = self.node(&if_.then);
+                    let otherwise = if_.otherwise.as_ref().and_then(|otherwise| self.node(otherwise));
+                    Region::innermost(then, otherwise)
+                }
+                hir::Control::While(while_) => {
+                    self.node(&while_.condition);
+                    self.node(&while_.body);
+                    None
+                }
+                hir::Control::Return(ret) => {
+                    let region = self.node(&ret.value);
+                    self.check_return(region);
+                    None
+                }
+                hir::Control::Defer(defer) => {
+                    self.node(&defer.expr);
+                    None
+                }
+                hir::Control::Break(_) | hir::Control::Continue(_) => None,
+            },
+            hir::Node::Builtin(builtin) => match builtin {
+                hir::Builtin::Add(binary)
+                | hir::Builtin::Sub(binary)
+                | hir::Builtin::Mul(binary)
+                | hir::Builtin::Div(binary)
+                | hir::Builtin::Rem(binary)
+                | hir::Builtin::Shl(binary)
+                | hir::Builtin::Shr(binary)
+                | hir::Builtin::And(binary)
+                | hir::Builtin::Or(binary)
+                | hir::Builtin::Lt(binary)
+                | hir::Builtin::Le(binary)
+                | hir::Builtin::Gt(binary)
+                | hir::Builtin::Ge(binary)
+                | hir::Builtin::Eq(binary)
+                | hir::Builtin::Ne(binary)
+                | hir::Builtin::BitAnd(binary)
+                | hir::Builtin::BitOr(binary)
+                | hir::Builtin::BitXor(binary) => {
+                    let lhs = self.node(&binary.lhs);
+                    let rhs = self.node(&binary.rhs);
+                    Region::innermost(lhs, rhs)
+                }
+                hir::Builtin::Not(unary) | hir::Builtin::Neg(unary) => self.node(&unary.value),
+                hir::Builtin::Deref(unary) => {
+                    // The loaded value's region is unknown
+                    self.node(&unary.value);
+                    None
+                }
+                hir::Builtin::Ref(ref_) => self.referenced_region(&ref_.value, ref_.span),
+                hir::Builtin::Offset(offset) => {
+                    self.node(&offset.index);
+                    self.node(&offset.value)
+                }
+                hir::Builtin::Slice(slice) => self.referenced_region(node, slice.span),
+            },
+            hir::Node::Literal(literal) => match literal {
+                hir::Literal::Struct(lit) => lit
+                    .fields
+                    .iter()
+                    .fold(None, |region, field| Region::innermost(region, self.node(&field.value))),
+                hir::Literal::Tuple(lit) => lit
+                    .elements
+                    .iter()
+                    .fold(None, |region, element| Region::innermost(region, self.node(element))),
+                hir::Literal::Array(lit) => lit
+                    .elements
+                    .iter()
+                    .fold(None, |region, element| Region::innermost(region, self.node(element))),
+                hir::Literal::ArrayFill(lit) => self.node(&lit.value),
+                hir::Literal::Enum(lit) => self.node(&lit.payload),
+                // A closure refers to whatever its captured values refer to
+                hir::Literal::Closure(lit) => {
+                    self.node(&lit.function);
+                    lit.env.as_ref().and_then(|env| self.node(env))
+                }
+            },
+        }
+    }
+
+    fn sequence(&mut self, sequence: &hir::Sequence) -> Option<Region> {
+        let mut region = None;
+
+        for statement in sequence.statements.iter() {
+            region = self.node(statement);
+        }
+
+        region
+    }
+
+    // Returns the region of a reference to `value`
+    fn referenced_region(&mut self, value: &hir::Node, span: Span) -> Option<Region> {
+        match value {
+            hir::Node::Id(id) => {
+                let binding_info = self.sess.workspace.binding_infos.get(id.id).unwrap();
+
+                if binding_info.scope_level.is_global() {
+                    None
+                } else {
+                    Some(Region {
+                        id: id.id,
+                        scope_level: binding_info.scope_level,
+                        span,
+                    })
+                }
+            }
+            hir::Node::MemberAccess(access) => self.referenced_region(&access.value, span),
+            // Referencing a value behind a pointer points to wherever the pointer points to
+            hir::Node::Builtin(hir::Builtin::Deref(unary)) => self.node(&unary.value),
+            hir::Node::Builtin(hir::Builtin::Offset(offset)) => {
+                self.node(&offset.index);
+
+                if offset.value.ty().normalize(self.sess.tcx).is_array() {
+                    self.referenced_region(&offset.value, span)
+                } else {
+                    self.node(&offset.value)
+                }
+            }
+            hir::Node::Builtin(hir::Builtin::Slice(slice)) => {
+                self.node(&slice.low);
+                self.node(&slice.high);
+
+                if slice.value.ty().normalize(self.sess.tcx).is_array() {
+                    self.referenced_region(&slice.value, span)
+                } else {
+                    self.node(&slice.value)
+                }
+            }
+            _ => {
+                self.node(value);
+                None
+            }
+        }
+    }
+
+    // Records that `id` may refer to `region`, and reports if `id` outlives it
+    fn store(&mut self, id: BindingId, region: Option<Region>, span: Span) {
+        let region = match region {
+            Some(region) => region,
+            None => return,
+        };
+
+        let scope_level = self.sess.workspace.binding_infos.get(id).unwrap().scope_level;
+
+        if region.scope_level > scope_level {
+            self.report(DanglingRefErr::OutlivedBy { region, id, span });
+        }
+
+        let region = Region::innermost(self.regions.get(&id).copied(), Some(region)).unwrap();
+        self.regions.insert(id, region);
+    }
+
+    fn check_return(&mut self, region: Option<Region>) {
+        if let Some(region) = region {
+            self.report(DanglingRefErr::Returned { region });
+        }
+    }
+
+    fn report(&mut self, err: DanglingRefErr) {
+        let span = match &err {
+            DanglingRefErr::Returned { region } | DanglingRefErr::OutlivedBy { region, .. } => region.span,
+        };
+
+        if self.reported.insert(span) {
+            self.errors.push(err);
+        }
+    }
+
+    // The binding a place belongs to, i.e: `x` in `x.a[0]`. Places behind a pointer aren't tracked
+    fn root_binding(&self, node: &hir::Node) -> Option<BindingId> {
+        match node {
+            hir::Node::Id(id) => Some(id.id),
+            hir::Node::MemberAccess(access) => self.root_binding(&access.value),
+            hir::Node::Builtin(hir::Builtin::Offset(offset))
+                if offset.value.ty().normalize(self.sess.tcx).is_array() =>
+            {
+                self.root_binding(&offset.value)
+            }
+            _ => None,
+        }
+    }
+
+    // Whether a value of this type can hold a reference
+    fn can_hold_ref(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Pointer(..) | Type::Slice(_) | Type::Str(_) | Type::Closure(_) => true,
+            Type::Array(inner, _) => self.can_hold_ref(inner),
+            Type::Tuple(elements) => elements.iter().any(|ty| self.can_hold_ref(ty)),
+            Type::Struct(struct_type) => struct_type.fields.iter().any(|field| self.can_hold_ref(&field.ty)),
+            Type::Enum(enum_type) => enum_type
+                .variants
+                .iter()
+                .any(|variant| variant.payload.iter().any(|ty| self.can_hold_ref(ty))),
+            Type::Var(_) => match ty.normalize(self.sess.tcx) {
+                Type::Var(_) => false,
+                ty => self.can_hold_ref(&ty),
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[test]
+    fn returned_closure_captures_local_ref() {
+        let result = check_source(
+            r#"
+fn make_reader() -> closure() -> int = {
+    let x = 42
+    let p = &x
+    fn() { p.* }
+}
+
+fn main() = ()
+"#,
+        );
+
+        assert!(has_error(&result, "cannot return a reference to local binding `x`"));
+    }
+
+    #[test]
+    fn returned_closure_captures_value() {
+        let result = check_source(
+            r#"
+fn make_reader() -> closure() -> int = {
+    let x = 42
+    fn() { x }
+}
+
+fn main() = ()
+"#,
+        );
+
+        assert!(errors(&result).is_empty());
+    }
+}
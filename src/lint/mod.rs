@@ -1,3 +1,5 @@
+mod dangling_refs;
+mod moves;
 mod ref_access;
 mod type_limits;
 
@@ -81,6 +83,9 @@ impl Lint for hir::Function {
             hir::FunctionKind::Orphan { body, .. } => body.lint(sess),
             hir::FunctionKind::Extern { .. } | hir::FunctionKind::Intrinsic(..) => (),
         }
+
+        sess.check_moves(self);
+        sess.check_dangling_refs(self);
     }
 }
 
@@ -138,6 +143,7 @@ impl Lint for hir::Control {
                 while_.body.lint(sess);
             }
             hir::Control::Return(return_) => return_.value.lint(sess),
+            hir::Control::Defer(defer) => defer.expr.lint(sess),
             hir::Control::Break(_) | hir::Control::Continue(_) => (),
         }
     }
@@ -229,6 +235,11 @@ impl Lint for hir::Literal {
                 }
             }
             hir::Literal::ArrayFill(lit) => lit.value.lint(sess),
+            hir::Literal::Enum(lit) => lit.payload.lint(sess),
+            hir::Literal::Closure(lit) => {
+                lit.function.lint(sess);
+                lit.env.lint(sess);
+            }
         }
     }
 }
@@ -0,0 +1,553 @@
+use super::LintSess;
+use crate::{
+    error::diagnostic::{Diagnostic, Label},
+    hir,
+    infer::{display::DisplayType, normalize::Normalize},
+    span::Span,
+    types::{StructType, Type},
+    workspace::{BindingId, BindingInfoFlags},
+};
+use std::collections::HashSet;
+use ustr::Ustr;
+
+// A local binding, or one of its (possibly nested) members, i.e: `x` or `x.a.b`
+#[derive(Debug, Clone, PartialEq)]
+struct Place {
+    id: BindingId,
+    path: Vec<(u32, Ustr)>,
+}
+
+impl Place {
+    fn starts_with(&self, prefix: &Place) -> bool {
+        self.id == prefix.id
+            && self.path.len() >= prefix.path.len()
+            && self.path.iter().zip(prefix.path.iter()).all(|(a, b)| a.0 == b.0)
+    }
+
+    // Whether one of the places contains the other, i.e: `x` and `x.a`
+    fn overlaps(&self, other: &Place) -> bool {
+        self.starts_with(other) || other.starts_with(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Move {
+    place: Place,
+    span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    // The value is moved out of its place, unless its type is copied
+    Move,
+    // The value is only read, i.e: a referenced value, or the operand of a builtin operator
+    Read,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UseKind {
+    Use,
+    Borrow,
+    Assign,
+}
+
+enum MoveErr {
+    UseAfterMove {
+        kind: UseKind,
+        place: Place,
+        span: Span,
+        moved: Move,
+        in_previous_iteration: bool,
+    },
+}
+
+impl<'s> LintSess<'s> {
+    // Tracks the moves of local bindings through a function's body, and reports any value that is used after it was moved.
+    // A value is moved when it is used by value - bound, assigned, passed to a function, returned, or stored in a literal.
+    // Only values with a move-only type are moved, every other value is copied.
+    pub fn check_moves(&mut self, function: &hir::Function) {
+        let body = match &function.kind {
+            hir::FunctionKind::Orphan { body: Some(body), .. } => body,
+            _ => return,
+        };
+
+        let mut checker = MoveChecker {
+            sess: self,
+            moves: vec![],
+            errors: vec![],
+            reported: HashSet::new(),
+            is_repeated_iteration: false,
+            defer_scopes: vec![],
+            loop_defer_depths: vec![],
+        };
+
+        checker.sequence(body);
+
+        let errors = std::mem::take(&mut checker.errors);
+
+        for err in errors {
+            let diagnostic = self.move_err_to_diagnostic(err);
+            self.workspace.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn move_err_to_diagnostic(&self, err: MoveErr) -> Diagnostic {
+        match err {
+            MoveErr::UseAfterMove {
+                kind,
+                place,
+                span,
+                moved,
+                in_previous_iteration,
+            } => {
+                let binding_info = self.workspace.binding_infos.get(moved.place.id).unwrap();
+                let used_name = place_name(binding_info.name, &place);
+                let moved_name = place_name(binding_info.name, &moved.place);
+
+                let (message, label) = match kind {
+                    UseKind::Use => ("use of moved value", "value used here after move"),
+                    UseKind::Borrow => ("borrow of moved value", "value borrowed here after move"),
+                    UseKind::Assign => ("assignment to part of moved value", "value assigned here after move"),
+                };
+
+                let moved_label = if in_previous_iteration {
+                    "value moved here, in previous iteration of loop"
+                } else {
+                    "value moved here"
+                };
+
+                Diagnostic::error()
+                    .with_message(format!("{} `{}`", message, used_name))
+                    .with_label(Label::primary(span, label))
+                    .with_label(Label::secondary(moved.span, moved_label))
+                    .with_note(format!(
+                        "the move occurs because `{}` has type `{}`, which is moved instead of copied",
+                        moved_name,
+                        self.place_type(&moved.place).display(self.tcx)
+                    ))
+            }
+        }
+    }
+
+    // Finds the type of a place, by following its path from its binding's type
+    fn place_type(&self, place: &Place) -> Type {
+        let binding_info = self.workspace.binding_infos.get(place.id).unwrap();
+        let mut ty = binding_info.ty.normalize(self.tcx);
+
+        for (index, _) in place.path.iter() {
+            let index = *index as usize;
+
+            ty = match ty {
+                Type::Struct(struct_type) => struct_type.fields[index].ty.normalize(self.tcx),
+                Type::Tuple(elements) => elements[index].normalize(self.tcx),
+                // Index 0 of an enum is its tag, and the rest are its variants' payloads
+                Type::Enum(enum_type) if index > 0 => enum_type.variants[index - 1].payload_type().normalize(self.tcx),
+                _ => return ty,
+            };
+        }
+
+        ty
+    }
+
+    // Whether a value of this type is copied when it's used by value.
+    // Named structs are moved, unless they are marked with `@copy`.
+    // Any other type is copied, as long as all of the types it contains are copied
+    fn is_copy(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Never
+            | Type::Unit
+            | Type::Bool
+            | Type::Int(_)
+            | Type::Uint(_)
+            | Type::Float(_)
+            | Type::Pointer(..)
+            | Type::Function(_)
+            | Type::Closure(_)
+            | Type::Slice(_)
+            | Type::Str(_)
+            | Type::Trait(_)
+            | Type::Module(_)
+            | Type::Type(_)
+            | Type::AnyType
+            | Type::Infer(..) => true,
+            // Generic bodies aren't linted, only their instances are
+            Type::Param(_) => false,
+            Type::Var(_) => match ty.normalize(self.tcx) {
+                Type::Var(_) => true,
+                ty => self.is_copy(&ty),
+            },
+            Type::Array(inner, _) => self.is_copy(inner),
+            Type::Tuple(elements) => elements.iter().all(|ty| self.is_copy(ty)),
+            Type::Struct(StructType { id, fields, .. }) => {
+                let is_marked_copy = match id {
+                    Some(id) => self
+                        .workspace
+                        .binding_infos
+                        .get(*id)
+                        .unwrap()
+                        .flags
+                        .contains(BindingInfoFlags::COPY),
+                    // Anonymous structs are structural, just like tuples
+                    None => true,
+                };
+
+                is_marked_copy && fields.iter().all(|field| self.is_copy(&field.ty))
+            }
+            Type::Enum(enum_type) => enum_type
+                .variants
+                .iter()
+                .all(|variant| variant.payload.iter().all(|ty| self.is_copy(ty))),
+        }
+    }
+}
+
+fn place_name(binding_name: Ustr, place: &Place) -> String {
+    let mut name = binding_name.to_string();
+
+    for (_, member) in place.path.iter() {
+        name.push('.');
+        name.push_str(member);
+    }
+
+    name
+}
+
+struct MoveChecker<'a, 's> {
+    sess: &'a mut LintSess<'s>,
+    // The places that are currently moved, along with the span of their move
+    moves: Vec<Move>,
+    errors: Vec<MoveErr>,
+    // Each use is reported once, even though loop bodies are checked twice
+    reported: HashSet<Span>,
+    // Set while checking the second iteration of a loop body
+    is_repeated_iteration: bool,
+    // The deferred expressions of each enclosing scope, which are checked at each of the scope's exits
+    defer_scopes: Vec<Vec<hir::Node>>,
+    // The number of defer scopes that enclose each loop
+    loop_defer_depths: Vec<usize>,
+}
+
+impl<'a, 's> MoveChecker<'a, 's> {
+    fn node(&mut self, node: &hir::Node, mode: Mode) {
+        match node {
+            hir::Node::Const(_) => (),
+            hir::Node::Binding(binding) => {
+                self.node(&binding.value, Mode::Move);
+
+                // A binding in a loop is initialized again in each iteration
+                self.moves.retain(|m| m.place.id != binding.id);
+            }
+            hir::Node::Id(_) | hir::Node::MemberAccess(_) => match self.place(node) {
+                Some(place) => {
+                    self.check_use(&place, UseKind::Use, node.span());
+
+                    if mode == Mode::Move && !self.sess.is_copy(&node.ty().normalize(self.sess.tcx)) {
+                        self.moves.push(Move {
+                            place,
+                            span: node.span(),
+                        });
+                    }
+                }
+                None => {
+                    if let hir::Node::MemberAccess(access) = node {
+                        self.node(&access.value, Mode::Read);
+                    }
+                }
+            },
+            hir::Node::Assign(assign) => {
+                self.node(&assign.rhs, Mode::Move);
+
+                match self.place(&assign.lhs) {
+                    Some(place) => {
+                        // Assigning to a member of a moved value is an error, but assigning a whole moved value initializes it again
+                        if let Some(moved) = self
+                            .moves
+                            .iter()
+                            .find(|m| place.starts_with(&m.place) && m.place != place)
+                            .cloned()
+                        {
+                            self.report(UseKind::Assign, place.clone(), assign.lhs.span(), moved);
+                        }
+
+                        self.moves.retain(|m| !m.place.starts_with(&place));
+                    }
+                    None => self.node(&assign.lhs, Mode::Read),
+                }
+            }
+            hir::Node::Call(call) => {
+                self.node(&call.callee, Mode::Read);
+
+                for arg in call.args.iter() {
+                    self.node(arg, Mode::Move);
+                }
+            }
+            hir::Node::Cast(cast) => self.node(&cast.value, mode),
+            hir::Node::Sequence(sequence) => self.sequence(sequence),
+            hir::Node::Control(control) => self.control(control),
+            hir::Node::Builtin(builtin) => self.builtin(builtin),
+            hir::Node::Literal(literal) => match literal {
+                hir::Literal::Struct(lit) => {
+                    for field in lit.fields.iter() {
+                        self.node(&field.value, Mode::Move);
+                    }
+                }
+                hir::Literal::Tuple(lit) => {
+                    for element in lit.elements.iter() {
+                        self.node(element, Mode::Move);
+                    }
+                }
+                hir::Literal::Array(lit) => {
+                    for element in lit.elements.iter() {
+                        self.node(element, Mode::Move);
+                    }
+                }
+                hir::Literal::ArrayFill(lit) => self.node(&lit.value, Mode::Move),
+                hir::Literal::Enum(lit) => self.node(&lit.payload, Mode::Move),
+                hir::Literal::Closure(lit) => {
+                    self.node(&lit.function, Mode::Read);
+
+                    if let Some(env) = &lit.env {
+                        self.node(env, Mode::Read);
+                    }
+                }
+            },
+        }
+    }
+
+    fn sequence(&mut self, sequence: &hir::Sequence) {
+        if sequence.is_scope {
+            self.defer_scopes.push(vec![]);
+        }
+
+        let mut diverges = false;
+
+        for statement in sequence.statements.iter() {
+            self.node(statement, Mode::Move);
+
+            // The rest of the sequence is unreachable
+            if statement.ty().normalize(self.sess.tcx).is_never() {
+                diverges = true;
+                break;
+            }
+        }
+
+        if sequence.is_scope {
+            let defers = self.defer_scopes.pop().unwrap();
+
+            // A diverging scope already checked its deferred expressions where it exited
+            if !diverges {
+                self.deferred(&defers);
+            }
+        }
+    }
+
+    // Checks deferred expressions in the order they run, from last to first
+    fn deferred(&mut self, defers: &[hir::Node]) {
+        for defer in defers.iter().rev() {
+            self.node(defer, Mode::Move);
+        }
+    }
+
+    // Checks the deferred expressions of all scopes starting from `depth`, when control flow jumps out of them.
+    // Their moves are discarded, since the code after the jump isn't reachable from here
+    fn deferred_from(&mut self, depth: usize) {
+        let defers = self.defer_scopes[depth..].concat();
+        let moves_before = self.moves.clone();
+        self.deferred(&defers);
+        self.moves = moves_before;
+    }
+
+    fn control(&mut self, control: &hir::Control) {
+        match control {
+            hir::Control::If(if_) => {
+                self.node(&if_.condition, Mode::Read);
+
+                let moves_before = self.moves.clone();
+
+                self.node(&if_.then, Mode::Move);
+                let then_diverges = if_.then.ty().normalize(self.sess.tcx).is_never();
+                let then_moves = std::mem::replace(&mut self.moves, moves_before);
+
+                if let Some(otherwise) = &if_.otherwise {
+                    self.node(otherwise, Mode::Move);
+
+                    // A value that is moved in one of the branches may have been moved after the `if`
+                    if otherwise.ty().normalize(self.sess.tcx).is_never() {
+                        self.moves = then_moves;
+                        return;
+                    }
+                }
+
+                if !then_diverges {
+                    self.merge_moves(then_moves);
+                }
+            }
+            hir::Control::While(while_) => {
+                let moves_before = self.moves.clone();
+                self.loop_defer_depths.push(self.defer_scopes.len());
+
+                self.node(&while_.condition, Mode::Read);
+                self.node(&while_.body, Mode::Move);
+
+                // Check the body again, to find values that are moved in one iteration and used in the next
+                let was_repeated_iteration = self.is_repeated_iteration;
+                self.is_repeated_iteration = true;
+
+                self.node(&while_.condition, Mode::Read);
+                self.node(&while_.body, Mode::Move);
+
+                self.is_repeated_iteration = was_repeated_iteration;
+                self.loop_defer_depths.pop();
+
+                // The loop may not run at all
+                self.merge_moves(moves_before);
+            }
+            hir::Control::Return(ret) => {
+                self.node(&ret.value, Mode::Move);
+                self.deferred_from(0);
+            }
+            hir::Control::Defer(defer) => {
+                // The deferred expression only runs when its scope exits, so it's checked at each of the scope's exits
+                self.defer_scopes.last_mut().unwrap().push((*defer.expr).clone());
+            }
+            hir::Control::Break(_) | hir::Control::Continue(_) => {
+                let depth = *self.loop_defer_depths.last().unwrap();
+                self.deferred_from(depth);
+            }
+        }
+    }
+
+    fn builtin(&mut self, builtin: &hir::Builtin) {
+        match builtin {
+            hir::Builtin::Add(binary)
+            | hir::Builtin::Sub(binary)
+            | hir::Builtin::Mul(binary)
+            | hir::Builtin::Div(binary)
+            | hir::Builtin::Rem(binary)
+            | hir::Builtin::Shl(binary)
+            | hir::Builtin::Shr(binary)
+            | hir::Builtin::And(binary)
+            | hir::Builtin::Or(binary)
+            | hir::Builtin::Lt(binary)
+            | hir::Builtin::Le(binary)
+            | hir::Builtin::Gt(binary)
+            | hir::Builtin::Ge(binary)
+            | hir::Builtin::Eq(binary)
+            | hir::Builtin::Ne(binary)
+            | hir::Builtin::BitAnd(binary)
+            | hir::Builtin::BitOr(binary)
+            | hir::Builtin::BitXor(binary) => {
+                self.node(&binary.lhs, Mode::Read);
+                self.node(&binary.rhs, Mode::Read);
+            }
+            hir::Builtin::Not(unary) | hir::Builtin::Neg(unary) | hir::Builtin::Deref(unary) => {
+                self.node(&unary.value, Mode::Read)
+            }
+            hir::Builtin::Ref(ref_) => match self.place(&ref_.value) {
+                Some(place) => self.check_use(&place, UseKind::Borrow, ref_.value.span()),
+                None => self.node(&ref_.value, Mode::Read),
+            },
+            hir::Builtin::Offset(offset) => {
+                self.node(&offset.value, Mode::Read);
+                self.node(&offset.index, Mode::Read);
+            }
+            hir::Builtin::Slice(slice) => {
+                self.node(&slice.value, Mode::Read);
+                self.node(&slice.low, Mode::Read);
+                self.node(&slice.high, Mode::Read);
+            }
+        }
+    }
+
+    // Returns the place a node refers to, if it refers to a local binding or one of its members.
+    // Values behind pointers aren't tracked
+    fn place(&self, node: &hir::Node) -> Option<Place> {
+        match node {
+            hir::Node::Id(id) => {
+                let binding_info = self.sess.workspace.binding_infos.get(id.id).unwrap();
+
+                if binding_info.scope_level.is_global() {
+                    None
+                } else {
+                    Some(Place {
+                        id: id.id,
+                        path: vec![],
+                    })
+                }
+            }
+            hir::Node::MemberAccess(access) => {
+                let mut place = self.place(&access.value)?;
+                place.path.push((access.member_index, access.member_name));
+                Some(place)
+            }
+            _ => None,
+        }
+    }
+
+    fn check_use(&mut self, place: &Place, kind: UseKind, span: Span) {
+        if let Some(moved) = self.moves.iter().find(|m| m.place.overlaps(place)).cloned() {
+            self.report(kind, place.clone(), span, moved);
+        }
+    }
+
+    fn report(&mut self, kind: UseKind, place: Place, span: Span, moved: Move) {
+        if !self.reported.insert(span) {
+            return;
+        }
+
+        // In a loop's second iteration, a value that is moved after its use was moved by the previous iteration
+        let in_previous_iteration = self.is_repeated_iteration && moved.span.start.index >= span.start.index;
+
+        self.errors.push(MoveErr::UseAfterMove {
+            kind,
+            place,
+            span,
+            moved,
+            in_previous_iteration,
+        });
+    }
+
+    fn merge_moves(&mut self, moves: Vec<Move>) {
+        for m in moves {
+            if !self.moves.iter().any(|existing| existing.place == m.place) {
+                self.moves.push(m);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[test]
+    fn std_enum_is_copied() {
+        let result = check_source(
+            "use std.fs\n\nfn main() = {\n    let kind = fs.FileKind.file\n    let _ = kind\n    let _ = kind\n}\n",
+        );
+
+        assert!(errors(&result).is_empty());
+    }
+
+    const FILE: &str = "type File = struct { fd: int }\n\nimpl File {\n    fn close(self: *Self) = {}\n}\n\nfn consume(f: File) = {}\n\n";
+
+    #[test]
+    fn deferred_use_after_move() {
+        let result = check_source(&format!(
+            "{}fn main() = {{\n    let f = File {{ fd: 1 }}\n    defer f.close()\n    consume(f)\n}}\n",
+            FILE
+        ));
+
+        assert!(has_error(&result, "borrow of moved value `f`"));
+    }
+
+    #[test]
+    fn deferred_use_at_break() {
+        let result = check_source(&format!(
+            "{}fn main() = {{\n    let f = File {{ fd: 1 }}\n    while true {{\n        defer f.close()\n        consume(f)\n        break\n    }}\n}}\n",
+            FILE
+        ));
+
+        assert!(has_error(&result, "borrow of moved value `f`"));
+    }
+}
@@ -0,0 +1,531 @@
+mod types;
+mod util;
+
+use crate::{
+    common::{
+        build_options::{BuildOptions, CodegenOptions, DiagnosticOptions, OptimizationLevel, OutputKind},
+        target::TargetPlatform,
+    },
+    driver::{self, StartWorkspaceResult},
+    ide::{
+        self,
+        types::{HintKind, IdeDiagnosticSeverity, IdeObject, IdeSpan},
+    },
+    span::FileId,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+};
+use types::*;
+use util::*;
+
+// Starts a language server, which speaks the Language Server Protocol over stdin/stdout.
+// The last checked workspace is kept alive between requests, so that queries don't re-check the workspace.
+pub fn start(target_platform: TargetPlatform, include_paths: Vec<PathBuf>, incremental: bool) {
+    let mut server = Server::new(target_platform, include_paths, incremental);
+
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+
+    loop {
+        match read_message(&mut stdin) {
+            Ok(Some(content)) => match serde_json::from_str::<Message>(&content) {
+                Ok(message) => {
+                    if let Some(exit_code) = server.handle_message(message) {
+                        std::process::exit(exit_code);
+                    }
+                }
+                Err(err) => eprintln!("chili lsp: failed to parse message: {}", err),
+            },
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("chili lsp: failed to read message: {}", err);
+                break;
+            }
+        }
+    }
+}
+
+struct Server {
+    target_platform: TargetPlatform,
+    include_paths: Vec<PathBuf>,
+    incremental: bool,
+
+    // The text of all documents currently opened by the client
+    // uri -> text
+    documents: HashMap<String, String>,
+
+    // The result of the last check, rooted at the last validated document
+    state: Option<StartWorkspaceResult>,
+
+    // Uris which were sent diagnostics in the last publish, so they can be cleared when fixed
+    published_uris: HashSet<String>,
+
+    shutdown_requested: bool,
+}
+
+impl Server {
+    fn new(target_platform: TargetPlatform, include_paths: Vec<PathBuf>, incremental: bool) -> Self {
+        Self {
+            target_platform,
+            include_paths,
+            incremental,
+            documents: HashMap::new(),
+            state: None,
+            published_uris: HashSet::new(),
+            shutdown_requested: false,
+        }
+    }
+
+    // Handles a single incoming message. Returns an exit code when the server should exit.
+    fn handle_message(&mut self, message: Message) -> Option<i32> {
+        let method = match &message.method {
+            Some(method) => method.as_str(),
+            // This is a response to a request we sent. We don't send any requests, so it's ignored.
+            None => return None,
+        };
+
+        match message.id {
+            Some(id) => {
+                let response = match self.handle_request(method, message.params) {
+                    Ok(result) => Response {
+                        jsonrpc: JSONRPC_VERSION,
+                        id,
+                        result: Some(result),
+                        error: None,
+                    },
+                    Err(error) => Response {
+                        jsonrpc: JSONRPC_VERSION,
+                        id,
+                        result: None,
+                        error: Some(error),
+                    },
+                };
+
+                write_message(&response);
+
+                None
+            }
+            None => self.handle_notification(method, message.params),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        // Full
+                        "change": 1,
+                        "save": { "includeText": false },
+                    },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "renameProvider": true,
+                    "inlayHintProvider": { "resolveProvider": false },
+                },
+                "serverInfo": {
+                    "name": "chili",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                Ok(to_value(self.hover(params)))
+            }
+            "textDocument/definition" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                Ok(to_value(self.definition(params)))
+            }
+            "textDocument/references" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                Ok(to_value(self.references(params)))
+            }
+            "textDocument/rename" => {
+                let params: RenameParams = parse_params(params)?;
+                self.rename(params).map(|edit| to_value(Some(edit)))
+            }
+            "textDocument/inlayHint" => {
+                let params: InlayHintParams = parse_params(params)?;
+                Ok(to_value(self.inlay_hints(params)))
+            }
+            _ => Err(ResponseError::new(
+                ResponseError::METHOD_NOT_FOUND,
+                format!("unsupported method `{}`", method),
+            )),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: Value) -> Option<i32> {
+        match method {
+            "exit" => return Some(if self.shutdown_requested { 0 } else { 1 }),
+            "textDocument/didOpen" => {
+                if let Ok(params) = parse_params::<DidOpenTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents.insert(uri.clone(), params.text_document.text);
+                    self.validate(&uri);
+                }
+            }
+            "textDocument/didChange" => {
+                if let Ok(params) = parse_params::<DidChangeTextDocumentParams>(params) {
+                    // We only support full document sync, so the last change is the whole document
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        let uri = params.text_document.uri;
+                        self.documents.insert(uri.clone(), change.text);
+                        self.validate(&uri);
+                    }
+                }
+            }
+            "textDocument/didSave" => {
+                if let Ok(params) = parse_params::<DidSaveTextDocumentParams>(params) {
+                    self.validate(&params.text_document.uri);
+                }
+            }
+            "textDocument/didClose" => {
+                if let Ok(params) = parse_params::<DidCloseTextDocumentParams>(params) {
+                    self.documents.remove(&params.text_document.uri);
+
+                    // The last check may have used the document's unsaved text, which is now discarded
+                    self.state = None;
+                }
+            }
+            // Notifications we don't care about, such as `initialized` and `$/cancelRequest`
+            _ => (),
+        }
+
+        None
+    }
+
+    // Checks the workspace rooted at the given document, and publishes its diagnostics
+    fn validate(&mut self, uri: &str) {
+        let path = match uri_to_path(uri) {
+            Some(path) => path,
+            None => return,
+        };
+
+        self.check(&path);
+
+        let state = self.state.as_ref().unwrap();
+
+        let mut diagnostics: HashMap<String, Vec<Diagnostic>> = HashMap::new();
+
+        for object in ide::collect_objects(&state.workspace, state.tcx.as_ref(), state.cache.as_ref()) {
+            if let IdeObject::Diagnostic(diagnostic) = object {
+                let range = match self.span_to_range(&diagnostic.span) {
+                    Some(range) => range,
+                    None => continue,
+                };
+
+                diagnostics
+                    .entry(path_to_uri(Path::new(&diagnostic.span.file)))
+                    .or_default()
+                    .push(Diagnostic {
+                        range,
+                        severity: match diagnostic.severity {
+                            IdeDiagnosticSeverity::Error => Diagnostic::SEVERITY_ERROR,
+                            IdeDiagnosticSeverity::Warning => Diagnostic::SEVERITY_WARNING,
+                        },
+                        source: "chili",
+                        message: diagnostic.message,
+                    });
+            }
+        }
+
+        // Clear diagnostics of documents which no longer have any
+        for uri in self.published_uris.drain() {
+            if !diagnostics.contains_key(&uri) {
+                publish_diagnostics(uri, vec![]);
+            }
+        }
+
+        // Make sure the validated document is always cleared, even if it wasn't published before
+        if !diagnostics.contains_key(uri) {
+            publish_diagnostics(uri.to_string(), vec![]);
+        }
+
+        for (uri, diagnostics) in diagnostics {
+            self.published_uris.insert(uri.clone());
+            publish_diagnostics(uri, diagnostics);
+        }
+    }
+
+    fn hover(&mut self, params: TextDocumentPositionParams) -> Option<Hover> {
+        let (file_id, offset) = self.resolve_position(&params.text_document.uri, params.position)?;
+        let state = self.state.as_ref()?;
+
+        let hover_info = ide::find_hover_info(
+            &state.workspace,
+            state.tcx.as_ref(),
+            state.cache.as_ref(),
+            file_id,
+            offset,
+        )?;
+
+        Some(Hover {
+            contents: MarkupContent {
+                kind: "markdown",
+                value: format!("```chili\n{}\n```", hover_info.contents),
+            },
+        })
+    }
+
+    fn definition(&mut self, params: TextDocumentPositionParams) -> Option<Location> {
+        let (file_id, offset) = self.resolve_position(&params.text_document.uri, params.position)?;
+
+        let state = self.state.as_ref()?;
+        let span = ide::find_definition(&state.workspace, state.tcx.as_ref(), file_id, offset)?;
+
+        Some(Location {
+            uri: path_to_uri(Path::new(&span.file)),
+            range: self.span_to_range(&span)?,
+        })
+    }
+
+    fn references(&mut self, params: TextDocumentPositionParams) -> Option<Vec<Location>> {
+        let (file_id, offset) = self.resolve_position(&params.text_document.uri, params.position)?;
+
+        let state = self.state.as_ref()?;
+        let spans = ide::find_reference_spans(&state.workspace, file_id, offset)?;
+
+        let locations = spans
+            .iter()
+            .filter_map(|span| {
+                Some(Location {
+                    uri: path_to_uri(Path::new(&span.file)),
+                    range: self.span_to_range(span)?,
+                })
+            })
+            .collect();
+
+        Some(locations)
+    }
+
+    fn rename(&mut self, params: RenameParams) -> Result<WorkspaceEdit, ResponseError> {
+        let (file_id, offset) = self
+            .resolve_position(&params.text_document.uri, params.position)
+            .ok_or_else(|| {
+                ResponseError::new(ResponseError::REQUEST_FAILED, "document is not part of the workspace")
+            })?;
+
+        let state = self.state.as_ref().unwrap();
+
+        let edit = ide::find_rename_edit(&state.workspace, file_id, offset, &params.new_name)
+            .map_err(|message| ResponseError::new(ResponseError::REQUEST_FAILED, message))?;
+
+        let mut changes: HashMap<String, Vec<TextEdit>> = HashMap::new();
+
+        for edit in edit.edits {
+            if let Some(range) = self.span_to_range(&edit.span) {
+                changes
+                    .entry(path_to_uri(Path::new(&edit.span.file)))
+                    .or_default()
+                    .push(TextEdit {
+                        range,
+                        new_text: edit.new_text,
+                    });
+            }
+        }
+
+        Ok(WorkspaceEdit { changes })
+    }
+
+    fn inlay_hints(&mut self, params: InlayHintParams) -> Option<Vec<InlayHint>> {
+        let path = uri_to_path(&params.text_document.uri)?;
+        let source = self.checked_source(&path)?;
+
+        let start = position_to_offset(&source, params.range.start);
+        let end = position_to_offset(&source, params.range.end);
+
+        let file = path.to_str()?;
+        let state = self.state.as_ref().unwrap();
+
+        let hints = ide::collect_objects(&state.workspace, state.tcx.as_ref(), state.cache.as_ref())
+            .into_iter()
+            .filter_map(|object| match object {
+                IdeObject::Hint(hint) if hint.span.file == file && (start..=end).contains(&hint.span.end) => {
+                    let (label, kind) = if hint.kind == HintKind::Binding.to_string() {
+                        (format!(": {}", hint.type_name), InlayHint::KIND_TYPE)
+                    } else if hint.kind == HintKind::ReturnType.to_string() {
+                        (format!(" -> {}", hint.type_name), InlayHint::KIND_TYPE)
+                    } else {
+                        (format!("({})", hint.type_name), InlayHint::KIND_PARAMETER)
+                    };
+
+                    Some(InlayHint {
+                        position: offset_to_position(&source, hint.span.end),
+                        label,
+                        kind,
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+
+        Some(hints)
+    }
+
+    // Resolves an lsp position into the path and byte offset the compiler understands.
+    // This also makes sure that the workspace is rooted at the given document.
+    // Resolves a position in a document to its file and offset, in the source seen by the checker.
+    // Any module of the last checked workspace can be queried, so the document is only checked if it isn't a part of it
+    fn resolve_position(&mut self, uri: &str, position: Position) -> Option<(FileId, usize)> {
+        let path = uri_to_path(uri)?;
+
+        if self.find_checked_file_id(&path).is_none() {
+            self.check(&path);
+        }
+
+        let file_id = self.find_checked_file_id(&path)?;
+        let state = self.state.as_ref()?;
+
+        let source = state.workspace.diagnostics.get_file(file_id)?.source();
+        Some((file_id, position_to_offset(source, position)))
+    }
+
+    fn find_checked_file_id(&self, path: &Path) -> Option<FileId> {
+        let state = self.state.as_ref()?;
+        let file = path.to_str()?;
+
+        state
+            .workspace
+            .module_infos
+            .iter()
+            .find(|(_, module_info)| module_info.file_path == file)
+            .map(|(_, module_info)| module_info.file_id)
+    }
+
+    // Returns the source of the given file, as it was seen by the checker.
+    // Offsets sent to/from the compiler are only meaningful against this source.
+    fn checked_source(&mut self, path: &Path) -> Option<String> {
+        let state = self.checked_state(path);
+        let file = path.to_str()?;
+
+        state
+            .workspace
+            .module_infos
+            .iter()
+            .find(|(_, module_info)| module_info.file_path == file)
+            .and_then(|(_, module_info)| state.workspace.diagnostics.get_file(module_info.file_id))
+            .map(|file| file.source().clone())
+    }
+
+    fn span_to_range(&self, span: &IdeSpan) -> Option<Range> {
+        let state = self.state.as_ref()?;
+
+        let source = state
+            .workspace
+            .module_infos
+            .iter()
+            .find(|(_, module_info)| module_info.file_path == span.file.as_str())
+            .and_then(|(_, module_info)| state.workspace.diagnostics.get_file(module_info.file_id))
+            .map(|file| file.source().clone())
+            .or_else(|| self.documents.get(&path_to_uri(Path::new(&span.file))).cloned())
+            .or_else(|| std::fs::read_to_string(&span.file).ok())?;
+
+        Some(util::span_to_range(&source, span.start, span.end))
+    }
+
+    fn checked_state(&mut self, path: &Path) -> &StartWorkspaceResult {
+        let is_checked = self
+            .state
+            .as_ref()
+            .map_or(false, |state| state.workspace.build_options.source_file == path);
+
+        if !is_checked {
+            self.check(path);
+        }
+
+        self.state.as_ref().unwrap()
+    }
+
+    // Open documents are checked with their unsaved text, instead of their contents on disk
+    fn source_overrides(&self) -> HashMap<PathBuf, String> {
+        self.documents
+            .iter()
+            .filter_map(|(uri, text)| Some((uri_to_path(uri)?, text.clone())))
+            .collect()
+    }
+
+    fn check(&mut self, path: &Path) {
+        let name = path
+            .file_stem()
+            .map_or("root", |stem| stem.to_str().unwrap())
+            .to_string();
+
+        let mut include_paths = self.include_paths.clone();
+
+        if let Some(parent) = path.parent() {
+            include_paths.push(parent.to_path_buf());
+        }
+
+        let build_options = BuildOptions {
+            source_file: path.to_path_buf(),
+            output_file: None,
+            target_platform: self.target_platform.clone(),
+            optimization_level: OptimizationLevel::Debug,
+            output_kind: OutputKind::Executable,
+            emit_times: false,
+            emit_hir: false,
+            emit_bytecode: false,
+            diagnostic_options: DiagnosticOptions::DontEmit,
+            codegen_options: CodegenOptions::Skip { emit_llvm_ir: false },
+            include_paths,
+            linker: None,
+            check_mode: true,
+            completion_offset: None,
+            source_overrides: self.source_overrides(),
+            incremental: self.incremental,
+            test_mode: false,
+        };
+
+        self.state = Some(driver::start_workspace(name, build_options));
+    }
+}
+
+fn publish_diagnostics(uri: String, diagnostics: Vec<Diagnostic>) {
+    write_message(&Notification {
+        jsonrpc: JSONRPC_VERSION,
+        method: "textDocument/publishDiagnostics",
+        params: PublishDiagnosticsParams { uri, diagnostics },
+    });
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, ResponseError> {
+    serde_json::from_value(params).map_err(|err| ResponseError::new(ResponseError::INVALID_PARAMS, err.to_string()))
+}
+
+fn to_value<T: serde::Serialize>(value: Option<T>) -> Value {
+    value.map_or(Value::Null, |value| serde_json::to_value(value).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn checks_unsaved_document_text() {
+        let path = write_sources(&[("main.chl", "fn main() = ()\n")]);
+        let mut server = Server::new(TargetPlatform::current().unwrap(), vec![], false);
+
+        server.check(&path);
+        assert!(errors(server.state.as_ref().unwrap()).is_empty());
+
+        server
+            .documents
+            .insert(path_to_uri(&path), "fn main() = undefined_function()\n".to_string());
+
+        server.check(&path);
+        assert!(!errors(server.state.as_ref().unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use super::types::{Position, Range};
+use std::{
+    io::{self, BufRead, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const CONTENT_LENGTH_HEADER: &str = "Content-Length: ";
+
+// Reads a single JSON-RPC message's content from the given reader.
+// Returns None when the stream has reached EOF.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut content_length: Option<usize> = None;
+
+    loop {
+        let mut header = String::new();
+
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(length) = header.strip_prefix(CONTENT_LENGTH_HEADER) {
+            content_length = Some(
+                length
+                    .parse()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid Content-Length header"))?,
+            );
+        }
+    }
+
+    let content_length =
+        content_length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header"))?;
+
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "message content is not valid utf-8"))
+}
+
+pub fn write_message<T>(value: &T)
+where
+    T: ?Sized + serde::Serialize,
+{
+    let content = serde_json::to_string(value).unwrap();
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    write!(stdout, "{}{}\r\n\r\n{}", CONTENT_LENGTH_HEADER, content.len(), content).unwrap();
+    stdout.flush().unwrap();
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+
+    let mut bytes = vec![];
+    let mut path_bytes = path.bytes();
+
+    while let Some(byte) = path_bytes.next() {
+        if byte == b'%' {
+            let hex = [path_bytes.next()?, path_bytes.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+
+    // Windows uris look like `file:///c:/foo/bar.chl`
+    if cfg!(windows) {
+        Some(PathBuf::from(path.trim_start_matches('/')))
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_str().unwrap().replace('\\', "/");
+
+    let mut uri = String::from(if path.starts_with('/') { "file://" } else { "file:///" });
+
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+// Converts a byte offset in `source` into an lsp position, which uses utf-16 code units for columns
+pub fn offset_to_position(source: &str, offset: usize) -> Position {
+    let mut offset = offset.min(source.len());
+
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line = source[..line_start].matches('\n').count();
+
+    let character: usize = source[line_start..offset].chars().map(|ch| ch.len_utf16()).sum();
+
+    Position {
+        line: line as u32,
+        character: character as u32,
+    }
+}
+
+// Converts an lsp position into a byte offset in `source`
+pub fn position_to_offset(source: &str, position: Position) -> usize {
+    let line_start = if position.line == 0 {
+        0
+    } else {
+        match source.match_indices('\n').nth(position.line as usize - 1) {
+            Some((index, _)) => index + 1,
+            None => return source.len(),
+        }
+    };
+
+    let mut character = 0;
+
+    for (index, ch) in source[line_start..].char_indices() {
+        if character >= position.character as usize || ch == '\n' {
+            return line_start + index;
+        }
+
+        character += ch.len_utf16();
+    }
+
+    source.len()
+}
+
+pub fn span_to_range(source: &str, start: usize, end: usize) -> Range {
+    Range {
+        start: offset_to_position(source, start),
+        end: offset_to_position(source, end),
+    }
+}
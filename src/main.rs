@@ -7,26 +7,31 @@ mod driver;
 mod error;
 mod hir;
 mod ide;
+mod incremental;
 mod infer;
 mod interp;
 mod lint;
+mod lsp;
 mod parse;
 mod span;
 mod sym;
+#[cfg(test)]
+mod test_utils;
 mod token;
 mod types;
 mod workspace;
 
 use crate::common::{
-    build_options::{BuildOptions, CodegenOptions, DiagnosticOptions, OptimizationLevel},
+    build_options::{BuildOptions, CodegenOptions, DiagnosticOptions, OptimizationLevel, OutputKind},
     target::TargetPlatform,
 };
 use clap::*;
 use colored::Colorize;
 use path_absolutize::Absolutize;
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
-    process::Command,
+    process,
 };
 
 #[derive(Parser, Debug)]
@@ -38,8 +43,11 @@ use std::{
     long_about = "Compiler for the Chili programming language"
 )]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// The main action the compiler should take.
-    input: String,
+    input: Option<String>,
 
     // Modes
     //
@@ -53,6 +61,24 @@ struct Args {
     #[clap(long, short)]
     check: bool,
 
+    /// Only available in Run mode.
+    /// Runs the input file in the bytecode VM, instead of compiling it with LLVM and the system linker.
+    #[clap(long)]
+    interp: bool,
+
+    // Target options
+    //
+    //
+    /// The platform to build for, given as a platform name (i.e: `linux_arm64`) or a target triple (i.e: `aarch64-linux-gnu`).
+    /// Defaults to the current platform.
+    #[clap(long)]
+    target: Option<String>,
+
+    /// The linker to use instead of the default one (i.e: `aarch64-linux-gnu-gcc`).
+    /// Without it, building for a platform other than the current one outputs an object file instead of an executable.
+    #[clap(long)]
+    linker: Option<String>,
+
     // Verbosity/Dump options
     //
     //
@@ -83,6 +109,11 @@ struct Args {
     #[clap(long)]
     include_paths: Option<String>,
 
+    /// Check modules which didn't change since the last compilation lazily, only checking the bindings that other modules use.
+    /// The module cache is stored in a `.chili-cache` directory, next to the input file.
+    #[clap(long)]
+    incremental: bool,
+
     // Check mode options
     //
     //
@@ -100,12 +131,63 @@ struct Args {
     /// Return the hover info for a given index, in the given input file.
     #[clap(long)]
     goto_def: Option<usize>,
+
+    /// Only available in Check mode.
+    /// Return the spans of all references to the binding at a given index, in all files of the workspace.
+    #[clap(long)]
+    find_references: Option<usize>,
+
+    /// Only available in Check mode.
+    /// Return the workspace edit needed to rename the binding at a given index to a new name.
+    #[clap(long, number_of_values = 2, value_names = &["OFFSET", "NEW_NAME"])]
+    rename: Option<Vec<String>>,
+
+    /// Only available in Check mode.
+    /// Return the completion candidates for a partially typed identifier or member access at a given index.
+    #[clap(long)]
+    completions: Option<usize>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Starts a language server, speaking the Language Server Protocol over stdin/stdout.
+    Lsp,
+
+    /// Compiles and runs all `@test` functions in the input file, and in all files imported by it - recursively.
+    Test {
+        /// The root file of the tested workspace.
+        input: String,
+    },
 }
 
 fn cli() {
     let args = Args::parse();
 
-    match get_file_path(&args.input) {
+    match &args.command {
+        Some(Command::Lsp) => {
+            lsp::start(
+                target_platform(&args.target),
+                get_include_paths(&args.include_paths),
+                args.incremental,
+            );
+            return;
+        }
+        Some(Command::Test { input }) => {
+            test(&args, input);
+            return;
+        }
+        None => (),
+    }
+
+    let input = match &args.input {
+        Some(input) => input,
+        None => {
+            print_err("no input file was given");
+            return;
+        }
+    };
+
+    match get_file_path(input) {
         Ok(source_file) => {
             let name = get_workspace_name(&source_file);
 
@@ -113,39 +195,75 @@ fn cli() {
                 let build_options = BuildOptions {
                     source_file,
                     output_file: None,
-                    target_platform: current_target_platform(),
+                    target_platform: target_platform(&args.target),
                     optimization_level: OptimizationLevel::Release,
+                    output_kind: OutputKind::Executable,
                     emit_times: args.emit_times,
                     emit_hir: args.emit_hir,
                     emit_bytecode: args.emit_bytecode,
                     diagnostic_options: DiagnosticOptions::Emit {
                         no_color: args.no_color,
                     },
-                    codegen_options: CodegenOptions::Codegen {
-                        emit_llvm_ir: args.emit_llvm_ir,
+                    codegen_options: if args.interp {
+                        CodegenOptions::Interp
+                    } else {
+                        CodegenOptions::Codegen {
+                            emit_llvm_ir: args.emit_llvm_ir,
+                        }
                     },
                     include_paths: get_include_paths(&args.include_paths),
+                    linker: args.linker.as_ref().map(PathBuf::from),
                     check_mode: false,
+                    completion_offset: None,
+                    source_overrides: HashMap::new(),
+                    incremental: args.incremental,
+                    test_mode: false,
                 };
 
+                if build_options.is_cross_compiling() {
+                    print_err(&format!(
+                        "cannot run a program built for `{}` on the current platform",
+                        build_options.target_platform.name()
+                    ));
+                    return;
+                }
+
                 let result = driver::start_workspace(name, build_options);
 
+                // Compilation errors, and panics of a program run by the VM, are reported as errors
+                if result.workspace.diagnostics.has_errors() {
+                    process::exit(1);
+                }
+
+                // In interp mode, the program has already been run by the VM
                 if let Some(output_file) = &result.output_file {
-                    let _ = Command::new(output_file).spawn();
+                    match process::Command::new(output_file).status() {
+                        Ok(status) => process::exit(status.code().unwrap_or(1)),
+                        Err(e) => {
+                            print_err(&format!("failed to run `{}`: {}", output_file.display(), e));
+                            process::exit(1);
+                        }
+                    }
                 }
             } else if args.check {
                 let build_options = BuildOptions {
                     source_file,
                     output_file: None,
-                    target_platform: current_target_platform(),
+                    target_platform: target_platform(&args.target),
                     optimization_level: OptimizationLevel::Debug,
+                    output_kind: OutputKind::Executable,
                     emit_times: false,
                     emit_hir: false,
                     emit_bytecode: false,
                     diagnostic_options: DiagnosticOptions::DontEmit,
                     codegen_options: CodegenOptions::Skip { emit_llvm_ir: false },
                     include_paths: get_include_paths(&args.include_paths),
+                    linker: args.linker.as_ref().map(PathBuf::from),
                     check_mode: true,
+                    completion_offset: args.completions,
+                    source_overrides: HashMap::new(),
+                    incremental: args.incremental,
+                    test_mode: false,
                 };
 
                 let result = driver::start_workspace(name, build_options);
@@ -153,16 +271,26 @@ fn cli() {
                 if args.diagnostics {
                     ide::diagnostics(&result.workspace, result.tcx.as_ref(), result.cache.as_ref());
                 } else if let Some(offset) = args.hover_info {
-                    ide::hover_info(&result.workspace, result.tcx.as_ref(), offset);
+                    ide::hover_info(&result.workspace, result.tcx.as_ref(), result.cache.as_ref(), offset);
                 } else if let Some(offset) = args.goto_def {
                     ide::goto_definition(&result.workspace, result.tcx.as_ref(), offset);
+                } else if let Some(offset) = args.find_references {
+                    ide::find_references(&result.workspace, offset);
+                } else if let Some(rename) = &args.rename {
+                    match rename[0].parse::<usize>() {
+                        Ok(offset) => ide::rename(&result.workspace, offset, &rename[1]),
+                        Err(_) => print_err(&format!("invalid rename offset `{}`", rename[0])),
+                    }
+                } else if let Some(offset) = args.completions {
+                    ide::completions(&result.workspace, result.tcx.as_ref(), result.cache.as_ref(), offset);
                 }
             } else {
                 let build_options = BuildOptions {
                     source_file,
                     output_file: None,
-                    target_platform: current_target_platform(),
+                    target_platform: target_platform(&args.target),
                     optimization_level: OptimizationLevel::Debug,
+                    output_kind: OutputKind::Executable,
                     emit_times: args.emit_times,
                     emit_hir: args.emit_hir,
                     emit_bytecode: args.emit_bytecode,
@@ -173,7 +301,12 @@ fn cli() {
                         emit_llvm_ir: args.emit_llvm_ir,
                     },
                     include_paths: get_include_paths(&args.include_paths),
+                    linker: args.linker.as_ref().map(PathBuf::from),
                     check_mode: false,
+                    completion_offset: None,
+                    source_overrides: HashMap::new(),
+                    incremental: args.incremental,
+                    test_mode: false,
                 };
 
                 driver::start_workspace(name, build_options);
@@ -183,6 +316,68 @@ fn cli() {
     }
 }
 
+fn test(args: &Args, input: &str) {
+    let source_file = match get_file_path(input) {
+        Ok(source_file) => source_file,
+        Err(e) => {
+            print_err(&e);
+            return;
+        }
+    };
+
+    let name = get_workspace_name(&source_file);
+
+    let build_options = BuildOptions {
+        source_file,
+        output_file: None,
+        target_platform: target_platform(&args.target),
+        optimization_level: OptimizationLevel::Debug,
+        output_kind: OutputKind::Executable,
+        emit_times: args.emit_times,
+        emit_hir: args.emit_hir,
+        emit_bytecode: args.emit_bytecode,
+        diagnostic_options: DiagnosticOptions::Emit {
+            no_color: args.no_color,
+        },
+        codegen_options: CodegenOptions::Codegen {
+            emit_llvm_ir: args.emit_llvm_ir,
+        },
+        include_paths: get_include_paths(&args.include_paths),
+        linker: args.linker.as_ref().map(PathBuf::from),
+        check_mode: false,
+        completion_offset: None,
+        source_overrides: HashMap::new(),
+        // Lazily checked modules don't have all of their test functions collected
+        incremental: false,
+        test_mode: true,
+    };
+
+    // The test runner is run right after it's built, so it must be built for the current platform
+    if build_options.is_cross_compiling() {
+        print_err(&format!(
+            "cannot run tests built for `{}` on the current platform",
+            build_options.target_platform.name()
+        ));
+        process::exit(1);
+    }
+
+    let result = driver::start_workspace(name, build_options);
+
+    // The test runner exits with a failure code if any test failed, so the same code is propagated
+    let output_file = match &result.output_file {
+        Some(output_file) => output_file,
+        None => process::exit(1),
+    };
+
+    match process::Command::new(output_file).status() {
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            print_err(&format!("failed to run tests: {}", e));
+            process::exit(1);
+        }
+    }
+}
+
 fn get_workspace_name(source_file: &Path) -> String {
     source_file
         .parent()
@@ -205,6 +400,26 @@ fn get_file_path(input_file: &str) -> Result<PathBuf, String> {
     }
 }
 
+fn target_platform(target: &Option<String>) -> TargetPlatform {
+    match target {
+        Some(target) => match TargetPlatform::from_name(target) {
+            Some(platform) => platform,
+            None => {
+                let names: Vec<String> = TargetPlatform::all().iter().map(|platform| platform.name()).collect();
+
+                print_err(&format!(
+                    "unknown target `{}`, expected a target triple or one of: {}",
+                    target,
+                    names.join(", ")
+                ));
+
+                std::process::exit(1);
+            }
+        },
+        None => current_target_platform(),
+    }
+}
+
 fn current_target_platform() -> TargetPlatform {
     match TargetPlatform::current() {
         Ok(t) => t,
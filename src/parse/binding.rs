@@ -1,6 +1,7 @@
 use super::*;
 use crate::{
     ast::pat::{GlobPat, NamePat, Pat, StructPat, StructSubPat},
+    error::diagnostic::Label,
     types::FunctionTypeKind,
     workspace::BindingId,
 };
@@ -81,6 +82,8 @@ impl Parser {
 
         let name_and_span = ast::NameAndSpan { name, span: id.span };
 
+        let generic_params = self.parse_generic_params()?;
+
         let (sig, _) = self.parse_function_sig(Some(name), FunctionTypeKind::Orphan, true)?;
 
         require!(self, Eq, "=")?;
@@ -92,6 +95,7 @@ impl Parser {
             vis,
             kind: ast::BindingKind::Function {
                 name: name_and_span,
+                generic_params,
                 sig,
                 body,
             },
@@ -152,6 +156,8 @@ impl Parser {
         let id = self.require_ident()?;
         let name = id.name();
 
+        let generic_params = self.parse_generic_params()?;
+
         require!(self, Eq, "=")?;
 
         let mut type_expr = self.parse_expression(false, false)?;
@@ -162,12 +168,55 @@ impl Parser {
             vis,
             kind: ast::BindingKind::Type {
                 name: ast::NameAndSpan { name, span: id.span },
+                generic_params,
                 type_expr: Box::new(type_expr),
             },
             span: start_span.to(self.previous_span()),
         })
     }
 
+    // Parses an optional generic parameter list, i.e: `[T, N: uint]`
+    fn parse_generic_params(&mut self) -> DiagnosticResult<Vec<ast::GenericParam>> {
+        if !eat!(self, OpenBracket) {
+            return Ok(vec![]);
+        }
+
+        let start_span = self.previous_span();
+
+        let generic_params = parse_delimited_list!(
+            self,
+            CloseBracket,
+            Comma,
+            {
+                let id = self.require_ident()?;
+
+                let type_expr = if eat!(self, Colon) {
+                    Some(Box::new(self.parse_expression(false, true)?))
+                } else {
+                    None
+                };
+
+                ast::GenericParam {
+                    name: ast::NameAndSpan::new(id.name(), id.span),
+                    type_expr,
+                    span: id.span.to(self.previous_span()),
+                }
+            },
+            ", or ]"
+        );
+
+        if generic_params.is_empty() {
+            return Err(Diagnostic::error()
+                .with_message("expected at least one generic parameter")
+                .with_label(Label::primary(
+                    start_span.to(self.previous_span()),
+                    "empty generic parameter list",
+                )));
+        }
+
+        Ok(generic_params)
+    }
+
     fn parse_import_binding(&mut self, attrs: Vec<ast::Attr>, vis: ast::Vis) -> DiagnosticResult<ast::Binding> {
         let start_span = self.previous_span();
 
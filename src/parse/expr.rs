@@ -170,6 +170,46 @@ impl Parser {
         }))
     }
 
+    pub fn parse_match(&mut self) -> DiagnosticResult<Ast> {
+        let start_span = self.previous_span();
+
+        self.skip_newlines();
+
+        let value = self.parse_expression_res(self.restrictions | Restrictions::NO_STRUCT_LITERAL, false, true)?;
+
+        self.skip_newlines();
+
+        require!(self, OpenCurly, "{")?;
+
+        let arms = parse_delimited_list!(
+            self,
+            CloseCurly,
+            Comma | Newline,
+            {
+                let pat = self.parse_match_pat()?;
+
+                self.skip_newlines();
+                require!(self, FatArrow, "=>")?;
+                self.skip_newlines();
+
+                let expr = self.parse_expression(false, false)?;
+
+                ast::MatchArm {
+                    span: pat.span().to(expr.span()),
+                    pat,
+                    expr,
+                }
+            },
+            ", or }"
+        );
+
+        Ok(Ast::Match(ast::Match {
+            value: Box::new(value),
+            arms,
+            span: start_span.to(self.previous_span()),
+        }))
+    }
+
     pub fn parse_block(&mut self) -> DiagnosticResult<ast::Block> {
         let start_span = require!(self, OpenCurly, "{")?.span;
 
@@ -344,6 +384,8 @@ impl Parser {
             }))
         } else if eat!(self, If) {
             self.parse_if()
+        } else if eat!(self, Match) {
+            self.parse_match()
         } else if eat!(self, Loop) {
             self.parse_loop()
         } else if eat!(self, While) {
@@ -356,6 +398,8 @@ impl Parser {
             self.parse_array_type_or_literal()
         } else if eat!(self, Break | Continue | Return) {
             self.parse_terminator()
+        } else if eat!(self, Defer) {
+            self.parse_defer()
         } else if eat!(self, Nil | True | False | Int(_) | Float(_) | Str(_) | Char(_)) {
             self.parse_literal()
         } else if eat!(self, OpenParen) {
@@ -379,8 +423,14 @@ impl Parser {
             }
         } else if eat!(self, Fn) {
             self.parse_function_expr(None, FunctionTypeKind::Orphan)
+        } else if eat!(self, Closure) {
+            self.parse_closure_type()
         } else if eat!(self, Struct) {
             self.parse_struct_type()
+        } else if eat!(self, Enum) {
+            self.parse_enum_type()
+        } else if eat!(self, Trait) {
+            self.parse_trait_type()
         } else if eat!(self, Extern) {
             require!(self, Union, "union")?;
             self.parse_struct_union_type()
@@ -465,6 +515,69 @@ impl Parser {
         }))
     }
 
+    fn parse_enum_type(&mut self) -> DiagnosticResult<Ast> {
+        let start_span = self.previous_span();
+
+        require!(self, OpenCurly, "{")?;
+
+        let variants = parse_delimited_list!(
+            self,
+            CloseCurly,
+            Comma,
+            {
+                let id = self.require_ident()?;
+
+                let payload = if eat!(self, OpenParen) {
+                    parse_delimited_list!(self, CloseParen, Comma, self.parse_expression(false, true)?, ", or )")
+                } else {
+                    vec![]
+                };
+
+                let span = id.span.to(self.previous_span());
+
+                self.skip_newlines();
+
+                ast::EnumTypeVariant {
+                    name: id.name(),
+                    payload,
+                    span,
+                }
+            },
+            "a , or }"
+        );
+
+        Ok(Ast::EnumType(ast::EnumType {
+            name: ustr(""),
+            variants,
+            span: start_span.to(self.previous_span()),
+        }))
+    }
+
+    fn parse_trait_type(&mut self) -> DiagnosticResult<Ast> {
+        let start_span = self.previous_span();
+
+        require!(self, OpenCurly, "{")?;
+
+        let functions = parse_delimited_list!(
+            self,
+            CloseCurly,
+            Semicolon | Newline,
+            {
+                require!(self, Fn, "fn")?;
+                let id = self.require_ident()?;
+                let (sig, _) = self.parse_function_sig(Some(id.name()), FunctionTypeKind::Orphan, true)?;
+                sig
+            },
+            "a newline, ; or }"
+        );
+
+        Ok(Ast::TraitType(ast::TraitType {
+            name: ustr(""),
+            functions,
+            span: start_span.to(self.previous_span()),
+        }))
+    }
+
     fn parse_struct_union_type(&mut self) -> DiagnosticResult<Ast> {
         let start_span = self.previous_span();
 
@@ -631,6 +744,19 @@ impl Parser {
         }
     }
 
+    pub fn parse_defer(&mut self) -> DiagnosticResult<Ast> {
+        let start_span = self.previous_span();
+
+        self.skip_newlines();
+
+        let expr = self.parse_expression(false, true)?;
+
+        Ok(Ast::Defer(ast::Defer {
+            expr: Box::new(expr),
+            span: start_span.to(self.previous_span()),
+        }))
+    }
+
     pub fn parse_comptime(&mut self) -> DiagnosticResult<ast::Comptime> {
         let start_span = self.previous_span();
 
@@ -650,7 +776,9 @@ impl Parser {
         match ast {
             Ast::Function(function) => function.sig.name = Some(name),
             Ast::StructType(struct_type) => struct_type.name = name,
-            Ast::FunctionType(sig) => sig.name = Some(name),
+            Ast::EnumType(enum_type) => enum_type.name = name,
+            Ast::TraitType(trait_type) => trait_type.name = name,
+            Ast::FunctionType(sig) | Ast::ClosureType(sig) => sig.name = Some(name),
             _ => (),
         }
     }
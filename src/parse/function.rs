@@ -27,6 +27,12 @@ impl Parser {
         }
     }
 
+    // Parses a closure type, i.e: `closure(x: int) -> int`
+    pub fn parse_closure_type(&mut self) -> DiagnosticResult<Ast> {
+        let (sig, _) = self.parse_function_sig(None, FunctionTypeKind::Orphan, true)?;
+        Ok(Ast::ClosureType(sig))
+    }
+
     pub fn parse_function_sig(
         &mut self,
         name: Option<Ustr>,
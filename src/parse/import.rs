@@ -23,7 +23,7 @@ impl Parser {
         self.search_import_name(name, span)
     }
 
-    pub fn search_import_name(&self, name: Ustr, span: Span) -> DiagnosticResult<ast::Ast> {
+    pub fn search_import_name(&mut self, name: Ustr, span: Span) -> DiagnosticResult<ast::Ast> {
         let mut search_notes = vec![];
 
         match name.as_str() {
@@ -43,10 +43,12 @@ impl Parser {
                                 search_notes.push(format!("searched path: {}", path.display()));
 
                                 // Search for a library named `foo`
-                                match self.cache.lock().libraries.get(&name) {
+                                let library = self.cache.lock().libraries.get(&name).cloned();
+
+                                match library {
                                     Some(library) => {
-                                        let module_path =
-                                            ModulePath::new(library.clone(), vec![ustr(library.root_file_stem())]);
+                                        let components = vec![ustr(library.root_file_stem())];
+                                        let module_path = ModulePath::new(library, components);
 
                                         self.finish_parse_import(module_path, span)
                                     }
@@ -67,9 +69,11 @@ impl Parser {
         }
     }
 
-    fn finish_parse_import(&self, module_path: ModulePath, span: Span) -> DiagnosticResult<ast::Ast> {
+    fn finish_parse_import(&mut self, module_path: ModulePath, span: Span) -> DiagnosticResult<ast::Ast> {
         let path = module_path.path();
 
+        self.imports.push(module_path.clone());
+
         spawn_parser(
             self.thread_pool.clone(),
             self.tx.clone(),
@@ -12,6 +12,7 @@ use crate::{
     ast::{self, Ast},
     common::id_cache::IdCache,
     error::{diagnostic::Diagnostic, DiagnosticResult, Diagnostics, SyntaxError},
+    incremental::CachedAst,
     span::{FileId, Span},
     token::{lexer::Lexer, Token, TokenKind::*},
     workspace::{library::Library, ModuleId, ModuleInfo, ModulePath},
@@ -19,9 +20,9 @@ use crate::{
 use bitflags::bitflags;
 use parking_lot::Mutex;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::Debug,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{mpsc::Sender, Arc},
 };
 use threadpool::ThreadPool;
@@ -133,6 +134,8 @@ pub struct Parser {
     module_info: ModuleInfo,
     module_path: ModulePath,
     restrictions: Restrictions,
+    // The modules imported by this module, which are recorded in its cached AST
+    imports: Vec<ModulePath>,
 }
 
 #[derive(Debug)]
@@ -143,6 +146,9 @@ pub struct ParserCache {
     pub diagnostics: Diagnostics,
     pub parsed_files: HashSet<Ustr>,
     pub total_lines: u32,
+    // Where parsed ASTs are cached, when compiling incrementally
+    pub ast_cache_dir: Option<PathBuf>,
+    pub source_overrides: HashMap<PathBuf, String>,
 }
 
 pub enum ParserResult {
@@ -169,6 +175,7 @@ impl Parser {
             module_info,
             module_path,
             restrictions: Restrictions::empty(),
+            imports: vec![],
         }
     }
 
@@ -184,7 +191,15 @@ impl Parser {
             if !cache.parsed_files.insert(self.module_info.file_path) {
                 return ParserResult::AlreadyParsed;
             } else {
-                match std::fs::read_to_string(self.module_info.file_path.as_str()) {
+                let file_path = Path::new(self.module_info.file_path.as_str());
+
+                // Files that are open in an editor are checked with their unsaved contents
+                let source = match cache.source_overrides.get(file_path) {
+                    Some(source) => Ok(source.clone()),
+                    None => std::fs::read_to_string(file_path),
+                };
+
+                match source {
                     Ok(source) => {
                         cache.total_lines += source.lines().count() as u32;
 
@@ -202,12 +217,52 @@ impl Parser {
             }
         };
 
-        match Lexer::new(file_id, &source).scan() {
+        if let Some(result) = self.load_cached_ast(file_id, &source) {
+            return result;
+        }
+
+        let result = match Lexer::new(file_id, &source).scan() {
             Ok(tokens) => {
                 self.tokens = tokens;
                 self.parse_module(file_id)
             }
             Err(diag) => ParserResult::LexerFailed(ast::Module::new(file_id, self.module_info), diag),
+        };
+
+        if let ParserResult::NewModule(module) = &result {
+            self.save_cached_ast(module, &source);
+        }
+
+        result
+    }
+
+    // A module which didn't change since it was last parsed is restored from its cached AST.
+    // Its imports are spawned here, since they are usually spawned while the module is parsed
+    fn load_cached_ast(&self, file_id: FileId, source: &str) -> Option<ParserResult> {
+        let cache_dir = self.cache.lock().ast_cache_dir.clone()?;
+        let cached = CachedAst::load(&cache_dir, self.module_info.file_path.as_str(), source, file_id)?;
+
+        let libraries = self.cache.lock().libraries.clone();
+        let (module, imports) = cached.into_module(self.module_info, &libraries)?;
+
+        for module_path in imports {
+            spawn_parser(
+                self.thread_pool.clone(),
+                self.tx.clone(),
+                Arc::clone(&self.cache),
+                module_path,
+                Some(self.module_info.id),
+            );
+        }
+
+        Some(ParserResult::NewModule(module))
+    }
+
+    fn save_cached_ast(&self, module: &ast::Module, source: &str) {
+        let cache_dir = self.cache.lock().ast_cache_dir.clone();
+
+        if let Some(cache_dir) = cache_dir {
+            CachedAst::new(module, &self.imports).save(&cache_dir, self.module_info.file_path.as_str(), source);
         }
     }
 
@@ -1,6 +1,12 @@
 use super::*;
 use crate::{
-    ast::pat::{GlobPat, HybridPat, NamePat, Pat, StructPat, StructSubPat, TuplePat, UnpackPatKind},
+    ast::{
+        pat::{
+            GlobPat, HybridPat, LiteralPat, MatchPat, NamePat, Pat, StructPat, StructSubPat, TuplePat, UnpackPatKind,
+            VariantPat,
+        },
+        LiteralKind,
+    },
     error::SyntaxError,
     workspace::BindingId,
 };
@@ -149,4 +155,89 @@ impl Parser {
             ignore,
         })
     }
+
+    pub fn parse_match_pat(&mut self) -> DiagnosticResult<MatchPat> {
+        self.skip_newlines();
+
+        if eat!(self, Dot) {
+            // .variant
+            let start_span = self.previous_span();
+            let ident = self.require_ident()?;
+            let name = ast::NameAndSpan::new(ident.name(), ident.span);
+
+            self.parse_variant_pat(None, name, start_span)
+        } else if eat!(self, Ident(_)) {
+            if is!(self, Dot) {
+                // Type.variant, module.Type.variant
+                let token = self.previous().clone();
+
+                let mut enum_type = Ast::Ident(ast::Ident {
+                    name: token.name(),
+                    span: token.span,
+                });
+
+                let mut variant: Option<ast::NameAndSpan> = None;
+
+                while eat!(self, Dot) {
+                    let ident = self.require_ident()?;
+
+                    if let Some(member) = variant.replace(ast::NameAndSpan::new(ident.name(), ident.span)) {
+                        enum_type = Ast::MemberAccess(ast::MemberAccess {
+                            expr: Box::new(enum_type),
+                            member: member.name,
+                            member_span: member.span,
+                            span: token.span.to(member.span),
+                        });
+                    }
+                }
+
+                self.parse_variant_pat(Some(Box::new(enum_type)), variant.unwrap(), token.span)
+            } else {
+                self.revert(1);
+                self.parse_name_pat().map(MatchPat::Name)
+            }
+        } else if is!(self, Mut | Placeholder) {
+            self.parse_name_pat().map(MatchPat::Name)
+        } else if eat!(self, Minus) {
+            let start_span = self.previous_span();
+
+            match require!(self, Int(_), "an integer")?.kind {
+                Int(value) => Ok(MatchPat::Literal(LiteralPat {
+                    kind: LiteralKind::Int(-(value as i128)),
+                    span: start_span.to(self.previous_span()),
+                })),
+                _ => unreachable!(),
+            }
+        } else if eat!(self, True | False | Int(_) | Char(_)) {
+            match self.parse_literal()? {
+                Ast::Literal(ast::Literal { kind, span }) => Ok(MatchPat::Literal(LiteralPat { kind, span })),
+                _ => unreachable!(),
+            }
+        } else {
+            Err(SyntaxError::expected(self.span(), "a pattern"))
+        }
+    }
+
+    fn parse_variant_pat(
+        &mut self,
+        enum_type: Option<Box<Ast>>,
+        name: ast::NameAndSpan,
+        start_span: Span,
+    ) -> DiagnosticResult<MatchPat> {
+        let payload = if eat!(self, OpenParen) {
+            match self.parse_tuple_unpack()? {
+                Pat::Tuple(pat) => Some(pat),
+                _ => unreachable!(),
+            }
+        } else {
+            None
+        };
+
+        Ok(MatchPat::Variant(VariantPat {
+            enum_type,
+            name,
+            payload,
+            span: start_span.to(self.previous_span()),
+        }))
+    }
 }
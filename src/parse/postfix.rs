@@ -33,6 +33,13 @@ impl Parser {
                     self.parse_subscript_or_slice(expr)?
                 } else if !self.restrictions.contains(Restrictions::NO_CAST) && eat!(self, As) {
                     self.parse_cast(expr)?
+                } else if eat!(self, QuestionMark) {
+                    let span = expr.span().to(self.previous_span());
+
+                    Ast::Try(ast::Try {
+                        expr: Box::new(expr),
+                        span,
+                    })
                 } else if eat!(self, Fn) {
                     let start_span = expr.span();
 
@@ -92,6 +99,24 @@ impl Parser {
 
         self.skip_newlines();
 
+        if !is!(self, Ident(_) | Int(_) | Float(_) | Star) {
+            // Recover from a missing member, i.e: `foo.`, so that the accessed expression can still be checked.
+            // This allows language support features to offer completions for the accessed value.
+            self.cache
+                .lock()
+                .diagnostics
+                .push(SyntaxError::expected(self.span(), "an identifier, number or *"));
+
+            let member_span = self.previous_span().after();
+
+            return Ok(Ast::MemberAccess(ast::MemberAccess {
+                expr: Box::new(expr),
+                member: ustr(""),
+                member_span,
+                span: start_span.to(member_span),
+            }));
+        }
+
         let token = self.bump();
 
         let expr = match token.kind {
@@ -144,7 +169,7 @@ impl Parser {
                 span: start_span.to(token.span),
             }),
 
-            _ => return Err(SyntaxError::expected(self.span(), "an identifier, number or *")),
+            _ => unreachable!(),
         };
 
         Ok(expr)
@@ -209,6 +234,23 @@ impl Parser {
                     high,
                     span: start_span.to(self.previous_span()),
                 }))
+            } else if eat!(self, Comma) {
+                // this is an instantiation of a generic binding, i.e: `Foo[i32, 4]`
+                let mut args = vec![index];
+
+                args.extend(parse_delimited_list!(
+                    self,
+                    CloseBracket,
+                    Comma,
+                    self.parse_expression(false, true)?,
+                    ", or ]"
+                ));
+
+                Ok(Ast::Instantiation(ast::Instantiation {
+                    expr: Box::new(expr),
+                    args,
+                    span: start_span.to(self.previous_span()),
+                }))
             } else {
                 require!(self, CloseBracket, "]")?;
 
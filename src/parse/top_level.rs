@@ -41,6 +41,16 @@ impl Parser {
                     if is!(self, Comptime) {
                         module.comptime_blocks.push(self.parse_comptime()?);
                         Ok(())
+                    } else if eat!(self, Impl) {
+                        if vis == ast::Vis::Public {
+                            return Err(Diagnostic::error()
+                                .with_message("impl blocks cannot be public")
+                                .with_label(Label::primary(self.previous_span(), "remove the `pub`"))
+                                .with_note("the visibility of each associated function is declared separately"));
+                        }
+
+                        module.impls.push(self.parse_impl()?);
+                        Ok(())
                     } else if eat!(self, Semicolon | Newline) {
                         // Ignore
                         Ok(())
@@ -58,4 +68,68 @@ impl Parser {
             }
         }
     }
+
+    fn parse_impl(&mut self) -> DiagnosticResult<ast::Impl> {
+        let start_span = self.previous_span();
+
+        let first = self.require_ident()?;
+
+        // `impl Trait for Type { ... }`
+        let (target, trait_name) = if eat!(self, For) {
+            let target = self.require_ident()?;
+            (target, Some(ast::NameAndSpan::new(first.name(), first.span)))
+        } else {
+            (first, None)
+        };
+
+        require!(self, OpenCurly, "{")?;
+
+        let mut bindings = vec![];
+
+        loop {
+            while eat!(self, Semicolon | Newline) {}
+
+            if eat!(self, CloseCurly) {
+                break;
+            }
+
+            let attrs = self.parse_attrs()?;
+
+            let vis = if eat!(self, Pub) {
+                ast::Vis::Public
+            } else {
+                ast::Vis::Private
+            };
+
+            let span = self.span();
+
+            match self.try_parse_any_binding(attrs, vis, true)? {
+                Some(binding) => {
+                    let binding = binding?;
+
+                    match &binding.kind {
+                        ast::BindingKind::Function { .. } => bindings.push(binding),
+                        _ => {
+                            return Err(Diagnostic::error()
+                                .with_message("impl blocks can only contain functions")
+                                .with_label(Label::primary(span, "not a function")))
+                        }
+                    }
+                }
+                None => {
+                    return Err(SyntaxError::expected(
+                        self.span(),
+                        &format!("a function or }}, got `{}`", self.peek().kind.lexeme()),
+                    ))
+                }
+            }
+        }
+
+        Ok(ast::Impl {
+            target: ast::NameAndSpan::new(target.name(), target.span),
+            trait_name,
+            bindings,
+            span: start_span.to(self.previous_span()),
+        })
+    }
 }
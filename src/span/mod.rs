@@ -1,7 +1,22 @@
-use std::ops::Range;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{cell::Cell, ops::Range};
 
 pub type FileId = usize;
 
+thread_local! {
+    // Spans are serialized without their file, since files get a new id in each compilation.
+    // Deserialized spans belong to the file that is being deserialized on the current thread
+    static DESERIALIZED_FILE_ID: Cell<FileId> = Cell::new(FileId::MAX);
+}
+
+// Runs `f`, assigning `file_id` to every span it deserializes
+pub fn deserialize_in_file<T>(file_id: FileId, f: impl FnOnce() -> T) -> T {
+    DESERIALIZED_FILE_ID.with(|id| id.set(file_id));
+    let result = f();
+    DESERIALIZED_FILE_ID.with(|id| id.set(FileId::MAX));
+    result
+}
+
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Span {
     pub file_id: FileId,
@@ -87,6 +102,20 @@ impl Span {
     }
 }
 
+impl Serialize for Span {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.start, self.end).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Span {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (start, end) = <(Position, EndPosition)>::deserialize(deserializer)?;
+        let file_id = DESERIALIZED_FILE_ID.with(Cell::get);
+        Ok(Self::new(file_id, start, end))
+    }
+}
+
 impl Ord for Span {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         (self.start, self.end).cmp(&(other.start, other.end))
@@ -99,7 +128,7 @@ impl PartialOrd for Span {
     }
 }
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Position {
     pub index: usize,
     pub line: u32,
@@ -120,7 +149,7 @@ impl Position {
     }
 }
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct EndPosition {
     pub index: usize,
 }
@@ -20,11 +20,19 @@ pub const FLOAT: &str = "float";
 pub const STR: &str = "str";
 pub const NEVER: &str = "never";
 
+pub const OPTION: &str = "Option";
+pub const OPTION_SOME: &str = "some";
+pub const OPTION_NONE: &str = "none";
+pub const RESULT: &str = "Result";
+pub const RESULT_OK: &str = "ok";
+pub const RESULT_ERR: &str = "err";
+
 pub const BUILTIN_FIELD_LEN: &str = "len";
 pub const BUILTIN_FIELD_PTR: &str = "ptr";
 
 pub const TRACK_CALLER_LOCATION_PARAM: &str = "track_caller@location";
+pub const CLOSURE_ENV_PARAM: &str = "closure@env";
 
 pub fn is_implicitly_generated_param(name: &str) -> bool {
-    name == TRACK_CALLER_LOCATION_PARAM
+    name == TRACK_CALLER_LOCATION_PARAM || name == CLOSURE_ENV_PARAM
 }
@@ -0,0 +1,115 @@
+// Helpers for tests that compile a Chili program, and inspect the result.
+// Each program is written to its own temporary directory, since the compiler reads its sources from disk
+use crate::{
+    common::{
+        build_options::{BuildOptions, CodegenOptions, DiagnosticOptions, OptimizationLevel, OutputKind},
+        target::TargetPlatform,
+    },
+    driver::{self, StartWorkspaceResult},
+    error::diagnostic::{Diagnostic, DiagnosticSeverity},
+    hir::const_value::ConstValue,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// Checks and lints `source`, without generating code
+pub(crate) fn check_source(source: &str) -> StartWorkspaceResult {
+    check_file(&write_sources(&[("main.chl", source)]), false)
+}
+
+// Runs `source`'s entry point in the bytecode VM
+pub(crate) fn run_source(source: &str) -> StartWorkspaceResult {
+    start(&write_sources(&[("main.chl", source)]), CodegenOptions::Interp, false)
+}
+
+// Checks and lints a file written by `write_sources`, which can be checked more than once
+pub(crate) fn check_file(source_file: &Path, incremental: bool) -> StartWorkspaceResult {
+    start(source_file, CodegenOptions::Skip { emit_llvm_ir: false }, incremental)
+}
+
+pub(crate) fn errors(result: &StartWorkspaceResult) -> Vec<&Diagnostic> {
+    diagnostics_of_severity(result, DiagnosticSeverity::Error)
+}
+
+pub(crate) fn warnings(result: &StartWorkspaceResult) -> Vec<&Diagnostic> {
+    diagnostics_of_severity(result, DiagnosticSeverity::Warning)
+}
+
+fn diagnostics_of_severity(result: &StartWorkspaceResult, severity: DiagnosticSeverity) -> Vec<&Diagnostic> {
+    result
+        .workspace
+        .diagnostics
+        .items()
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == severity)
+        .collect()
+}
+
+// Whether the compilation reported an error with the given message
+pub(crate) fn has_error(result: &StartWorkspaceResult, message: &str) -> bool {
+    errors(result)
+        .iter()
+        .any(|diagnostic| diagnostic.message.as_deref() == Some(message))
+}
+
+// The compile-time value of a top-level binding in the root module
+pub(crate) fn global_const(result: &StartWorkspaceResult, name: &str) -> Option<ConstValue> {
+    let workspace = &result.workspace;
+
+    workspace
+        .binding_infos
+        .iter()
+        .map(|(_, binding_info)| binding_info)
+        .find(|binding_info| {
+            binding_info.module_id == workspace.root_module_id
+                && binding_info.scope_level.is_global()
+                && binding_info.name == name
+        })
+        .and_then(|binding_info| binding_info.const_value.clone())
+}
+
+fn start(source_file: &Path, codegen_options: CodegenOptions, incremental: bool) -> StartWorkspaceResult {
+    let build_options = BuildOptions {
+        source_file: source_file.to_path_buf(),
+        output_file: None,
+        target_platform: TargetPlatform::current().unwrap(),
+        optimization_level: OptimizationLevel::Debug,
+        output_kind: OutputKind::Executable,
+        emit_times: false,
+        emit_hir: false,
+        emit_bytecode: false,
+        diagnostic_options: DiagnosticOptions::DontEmit,
+        codegen_options,
+        include_paths: vec![],
+        linker: None,
+        check_mode: false,
+        completion_offset: None,
+        source_overrides: HashMap::new(),
+        incremental,
+        test_mode: false,
+    };
+
+    driver::start_workspace("test".to_string(), build_options)
+}
+
+// Writes each file to a new directory, and returns the path of the first one
+pub(crate) fn write_sources(files: &[(&str, &str)]) -> PathBuf {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "chili-test-{}-{}",
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for (name, source) in files {
+        std::fs::write(dir.join(name), source).unwrap();
+    }
+
+    dir.join(files[0].0)
+}
@@ -168,6 +168,8 @@ impl<'lx> Lexer<'lx> {
                 '=' => {
                     if self.eat('=') {
                         EqEq
+                    } else if self.eat('>') {
+                        FatArrow
                     } else {
                         Eq
                     }
@@ -83,6 +83,7 @@ pub enum TokenKind {
     Dot,
     DotDot,
     RightArrow,
+    FatArrow,
 
     // Keywords
     If,
@@ -93,6 +94,7 @@ pub enum TokenKind {
     Break,
     Continue,
     Return,
+    Defer,
     Let,
     Type,
     Fn,
@@ -104,8 +106,12 @@ pub enum TokenKind {
     As,
     Struct,
     Union,
+    Enum,
+    Trait,
     Match,
     Comptime,
+    Impl,
+    Closure,
 
     // Accessors
     Placeholder,
@@ -137,6 +143,7 @@ impl From<&str> for TokenKind {
             "break" => Break,
             "continue" => Continue,
             "return" => Return,
+            "defer" => Defer,
             "let" => Let,
             "type" => Type,
             "fn" => Fn,
@@ -148,8 +155,12 @@ impl From<&str> for TokenKind {
             "as" => As,
             "struct" => Struct,
             "union" => Union,
+            "enum" => Enum,
+            "trait" => Trait,
             "match" => Match,
             "comptime" => Comptime,
+            "impl" => Impl,
+            "closure" => Closure,
             "_" => Placeholder,
             s => Ident(ustr(s)),
         }
@@ -208,6 +219,7 @@ impl TokenKind {
             Dot => ".",
             DotDot => "..",
             RightArrow => "->",
+            FatArrow => "=>",
             If => "if",
             Else => "else",
             Loop => "loop",
@@ -216,6 +228,7 @@ impl TokenKind {
             Break => "break",
             Continue => "continue",
             Return => "return",
+            Defer => "defer",
             Let => "let",
             Type => "type",
             Fn => "fn",
@@ -228,7 +241,11 @@ impl TokenKind {
             Struct => "struct",
             Comptime => "comptime",
             Union => "union",
+            Enum => "enum",
+            Trait => "trait",
             Match => "match",
+            Impl => "impl",
+            Closure => "closure",
             Placeholder => "_",
             Ident(_) => "identifier",
             Nil => "nil",
@@ -261,12 +278,16 @@ impl TokenKind {
                 | Break
                 | Continue
                 | Return
+                | Defer
                 | Let
                 | Fn
+                | Closure
                 | Extern
                 | Pub
                 | Struct
                 | Union
+                | Enum
+                | Trait
                 | Match
                 | Placeholder
                 | Ident(_)
@@ -12,7 +12,7 @@ impl AlignOf for Type {
             Type::Int(ty) => ty.align_of(word_size),
             Type::Uint(ty) => ty.align_of(word_size),
             Type::Float(ty) => ty.align_of(word_size),
-            Type::Pointer(..) | Type::Function(..) => word_size,
+            Type::Pointer(..) | Type::Function(..) | Type::Closure(..) => word_size,
             Type::Array(ty, ..) => ty.align_of(word_size),
             Type::Tuple(elems) => StructType::temp(
                 elems.iter().map(|t| StructTypeField::temp(t.clone())).collect(),
@@ -20,8 +20,11 @@ impl AlignOf for Type {
             )
             .align_of(word_size),
             Type::Struct(s) => s.align_of(word_size),
+            Type::Enum(e) => e.layout(word_size).align_of(word_size),
             Type::Infer(_, InferType::AnyInt) => IntType::Int.align_of(word_size),
             Type::Infer(_, InferType::AnyFloat) => FloatType::Float.align_of(word_size),
+            // Only used while a generic body is checked, instances see the real alignment
+            Type::Param(_) => word_size,
             _ => panic!("type {:?} is unsized", self),
         }
     }
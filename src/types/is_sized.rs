@@ -19,15 +19,25 @@ impl IsSized for Type {
             | Type::Float(_)
             | Type::Pointer(..)
             | Type::Function(..)
+            | Type::Closure(..)
             | Type::Array(..)
             | Type::Infer(_, InferType::AnyInt)
-            | Type::Infer(_, InferType::AnyFloat) => true,
+            | Type::Infer(_, InferType::AnyFloat)
+            | Type::Param(_) => true,
 
-            Type::Module(_) | Type::Type(_) | Type::AnyType | Type::Var(_) | Type::Slice(_) | Type::Str(_) => false,
+            Type::Module(_)
+            | Type::Trait(_)
+            | Type::Type(_)
+            | Type::AnyType
+            | Type::Var(_)
+            | Type::Slice(_)
+            | Type::Str(_) => false,
 
             Type::Tuple(elems) => elems.iter().all(|e| e.is_sized()),
 
             Type::Struct(s) => s.fields.iter().all(|f| f.ty.is_sized()),
+
+            Type::Enum(e) => e.variants.iter().all(|v| v.payload.iter().all(|ty| ty.is_sized())),
         }
     }
 }
@@ -7,8 +7,12 @@ use crate::{
     define_id_type,
     hir::const_value::ConstValue,
     span::Span,
+    sym,
     workspace::{BindingId, ModuleId},
 };
+use align_of::AlignOf;
+use serde::{Deserialize, Serialize};
+use size_of::SizeOf;
 use ustr::{ustr, Ustr};
 
 define_id_type!(TypeId);
@@ -35,11 +39,19 @@ pub enum Type {
     Float(FloatType),
     Pointer(Box<Type>, bool),
     Function(FunctionType),
+    // A function which captures its environment, passed around as a pair of its function and environment pointers
+    Closure(FunctionType),
     Array(Box<Type>, usize),
     Slice(Box<Type>),
     Str(Box<Type>),
     Tuple(Vec<Type>),
     Struct(StructType),
+    Enum(EnumType),
+    // A trait isn't a value type. It can only be implemented by types, and used as a generic parameter's bound
+    Trait(TraitType),
+    // A generic type parameter, as seen while its generic function's body is checked.
+    // It only unifies with itself, and only supports what its bound provides
+    Param(ParamType),
     Module(ModuleId),
     Type(Box<Type>),
     AnyType,
@@ -109,6 +121,32 @@ impl FunctionType {
     pub fn has_c_varargs(&self) -> bool {
         self.varargs.is_some()
     }
+
+    // The `self` parameter of a method, which must be its first parameter
+    pub fn self_param(&self) -> Option<&FunctionTypeParam> {
+        self.params
+            .iter()
+            .find(|param| !sym::is_implicitly_generated_param(&param.name))
+            .filter(|param| param.name == sym::SELF)
+    }
+
+    // The type of a closure's underlying function, which receives the closure's environment as its first parameter
+    pub fn with_closure_env_param(&self) -> FunctionType {
+        let mut params = vec![FunctionTypeParam {
+            name: ustr(sym::CLOSURE_ENV_PARAM),
+            ty: Type::raw_pointer(false),
+            default_value: None,
+        }];
+
+        params.extend(self.params.iter().cloned());
+
+        FunctionType {
+            params,
+            return_type: self.return_type.clone(),
+            varargs: self.varargs.clone(),
+            kind: self.kind.clone(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -124,7 +162,7 @@ pub struct FunctionTypeVarargs {
     pub ty: Option<Type>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum FunctionTypeKind {
     Orphan,
     Extern,
@@ -210,7 +248,7 @@ impl StructType {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum StructTypeKind {
     Struct,
     PackedStruct,
@@ -234,6 +272,116 @@ impl StructTypeField {
     }
 }
 
+pub const ENUM_TAG_FIELD: &str = "tag";
+pub const ENUM_PAYLOAD_FIELD: &str = "payload";
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraitType {
+    pub name: Ustr,
+    pub id: BindingId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParamType {
+    pub name: Ustr,
+    pub generic_id: BindingId,
+    pub index: usize,
+    pub bound: Option<TraitType>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumType {
+    pub name: Ustr,
+    pub id: Option<BindingId>,
+    pub variants: Vec<EnumVariant>,
+}
+
+impl EnumType {
+    pub fn variant(&self, name: impl AsRef<str>) -> Option<&EnumVariant> {
+        let variant = name.as_ref();
+        self.variants.iter().find(|v| v.name == variant)
+    }
+
+    pub fn variant_and_position(&self, name: impl AsRef<str>) -> Option<(usize, &EnumVariant)> {
+        let variant = name.as_ref();
+        self.variants.iter().enumerate().find(|(_, v)| v.name == variant)
+    }
+
+    pub fn has_payloads(&self) -> bool {
+        self.variants.iter().any(|v| v.has_payload())
+    }
+
+    #[allow(unused)]
+    pub fn is_anonymous(&self) -> bool {
+        self.name.is_empty()
+    }
+
+    // An enum is laid out as a struct with a `u32` tag, followed by the payloads of all variants, which overlap.
+    // The payload's storage is made of integers as wide as the strictest payload alignment,
+    // so that any variant's payload can be read by reinterpreting a pointer to it.
+    pub fn layout(&self, word_size: usize) -> StructType {
+        let mut fields = vec![StructTypeField {
+            name: ustr(ENUM_TAG_FIELD),
+            ty: Type::u32(),
+            span: Span::unknown(),
+        }];
+
+        if self.has_payloads() {
+            let (size, align) =
+                self.variants
+                    .iter()
+                    .map(|v| v.payload_type())
+                    .fold((0, 1), |(size, align), payload| {
+                        (
+                            size.max(payload.size_of(word_size)),
+                            align.max(payload.align_of(word_size)),
+                        )
+                    });
+
+            let element_type = match align {
+                1 => Type::u8(),
+                2 => Type::u16(),
+                4 => Type::u32(),
+                _ => Type::u64(),
+            };
+
+            let element_size = element_type.size_of(word_size);
+
+            fields.push(StructTypeField {
+                name: ustr(ENUM_PAYLOAD_FIELD),
+                ty: Type::Array(Box::new(element_type), (size + element_size - 1) / element_size),
+                span: Span::unknown(),
+            });
+        }
+
+        StructType::temp(fields, StructTypeKind::Struct)
+    }
+}
+
+impl From<EnumType> for Type {
+    fn from(ty: EnumType) -> Self {
+        Type::Enum(ty)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumVariant {
+    pub name: Ustr,
+    pub payload: Vec<Type>,
+    pub span: Span,
+}
+
+impl EnumVariant {
+    pub fn has_payload(&self) -> bool {
+        !self.payload.is_empty()
+    }
+
+    // A variant's payload is stored as a tuple of its types
+    pub fn payload_type(&self) -> Type {
+        Type::Tuple(self.payload.clone())
+    }
+}
+
 impl Type {
     #[allow(unused)]
     pub fn as_inner(&self) -> &Type {
@@ -283,6 +431,14 @@ impl Type {
         matches!(self, Type::Module(_))
     }
 
+    pub fn is_trait(&self) -> bool {
+        matches!(self, Type::Trait(_))
+    }
+
+    pub fn is_param(&self) -> bool {
+        matches!(self, Type::Param(_))
+    }
+
     #[allow(unused)]
     pub fn is_number(&self) -> bool {
         self.is_any_integer() || self.is_float()
@@ -332,6 +488,11 @@ impl Type {
         matches!(self, Type::Function(..))
     }
 
+    #[allow(unused)]
+    pub fn is_closure(&self) -> bool {
+        matches!(self, Type::Closure(..))
+    }
+
     #[allow(unused)]
     pub fn is_var(&self) -> bool {
         matches!(self, Type::Var(..))
@@ -360,6 +521,18 @@ impl Type {
         matches!(self, Type::Struct(_))
     }
 
+    #[allow(unused)]
+    pub fn is_enum(&self) -> bool {
+        matches!(self, Type::Enum(_))
+    }
+
+    pub fn as_enum(&self) -> &EnumType {
+        match self {
+            Type::Enum(ty) => ty,
+            _ => panic!("expected enum, got {:?}", self),
+        }
+    }
+
     pub fn as_struct(&self) -> &StructType {
         match self {
             Type::Struct(ty) => ty,
@@ -29,6 +29,8 @@ impl OffsetOf for Type {
             )
             .offset_of(index, word_size),
             Type::Struct(s) => s.offset_of(index, word_size),
+            // Index 0 is the enum's tag, and any other index is the payload of the variant at `index - 1`
+            Type::Enum(e) => e.layout(word_size).offset_of(index.min(1), word_size),
             ty => panic!("{:?} isn't an aggregate type", ty),
         }
     }
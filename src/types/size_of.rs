@@ -20,6 +20,7 @@ impl SizeOf for Type {
                 _ => word_size,
             },
             Type::Function(..) => word_size,
+            Type::Closure(..) => word_size * 2,
             Type::Array(ty, len) => ty.size_of(word_size) * len,
             Type::Tuple(elems) => StructType::temp(
                 elems.iter().map(|t| StructTypeField::temp(t.clone())).collect(),
@@ -27,8 +28,11 @@ impl SizeOf for Type {
             )
             .size_of(word_size),
             Type::Struct(s) => s.size_of(word_size),
+            Type::Enum(e) => e.layout(word_size).size_of(word_size),
             Type::Infer(_, InferType::AnyInt) => IntType::Int.size_of(word_size),
             Type::Infer(_, InferType::AnyFloat) => FloatType::Float.size_of(word_size),
+            // Only used while a generic body is checked, instances see the real size
+            Type::Param(_) => word_size,
             _ => panic!("type {:?} is unsized", self),
         }
     }
@@ -27,8 +27,13 @@ impl WithId<LibraryId> for Library {
 
 impl Library {
     pub fn std() -> Self {
-        let mut root_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
-        root_dir.push("stdlib");
+        // The stdlib is copied next to the compiler's executable by the build script.
+        // Tests run from another directory, so they use the stdlib in the repository
+        #[cfg(not(test))]
+        let root_dir = env::current_exe().unwrap().parent().unwrap().join("stdlib");
+
+        #[cfg(test)]
+        let root_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("stdlib");
 
         let root_file = root_dir.join(Path::new("lib.chl"));
 
@@ -62,6 +62,8 @@ pub struct BindingInfo {
     pub kind: BindingInfoKind,
     // the scope depth of the binding
     pub scope_level: ScopeLevel,
+    // the scopes enclosing the binding, from the outermost one. global bindings have no enclosing scopes
+    pub scope_path: Vec<ScopeId>,
     // the fully qualified name of the binding,
     // including the module name and the binding name i.e: `module_name.binding_name`
     pub qualified_name: Ustr,
@@ -126,6 +128,12 @@ impl BindingInfo {
     pub fn is_no_const_fold(&self) -> bool {
         self.flags.contains(BindingInfoFlags::NO_CONST_FOLD)
     }
+
+    #[inline]
+    #[allow(unused)]
+    pub fn is_associated_function(&self) -> bool {
+        self.flags.contains(BindingInfoFlags::ASSOCIATED_FUNCTION)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -138,6 +146,7 @@ pub struct PartialBindingInfo {
     pub is_mutable: bool,
     pub kind: BindingInfoKind,
     pub scope_level: ScopeLevel,
+    pub scope_path: Vec<ScopeId>,
     pub qualified_name: Ustr,
     pub span: Span,
     pub flags: BindingInfoFlags,
@@ -155,6 +164,7 @@ impl PartialBindingInfo {
             is_mutable: self.is_mutable,
             kind: self.kind,
             scope_level: self.scope_level,
+            scope_path: self.scope_path,
             qualified_name: self.qualified_name,
             flags: self.flags,
             uses: vec![],
@@ -177,6 +187,10 @@ bitflags! {
         const NO_CONST_FOLD = 1 << 4;
         // Whether this binding was ignored using `_`
         const IGNORE = 1 << 5;
+        // Whether this is a function declared in an `impl` block
+        const ASSOCIATED_FUNCTION = 1 << 6;
+        // Whether this is a struct type marked with `@copy`, which is copied instead of moved
+        const COPY = 1 << 7;
     }
 }
 
@@ -252,6 +266,7 @@ const LIBRARY_ID_MAIN: LibraryId = LibraryId(1);
 
 define_id_type!(ModuleId);
 define_id_type!(BindingId);
+define_id_type!(ScopeId);
 
 #[derive(Debug, Default, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct ModuleInfo {
@@ -299,12 +314,10 @@ impl ModulePath {
         self.components.pop()
     }
 
-    #[allow(unused)]
     pub fn library(&self) -> &Library {
         &self.library
     }
 
-    #[allow(unused)]
     pub fn components(&self) -> &[Ustr] {
         &self.components
     }